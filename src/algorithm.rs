@@ -0,0 +1,356 @@
+use std::fmt;
+
+use rand::Rng;
+
+use crate::error::CubeError;
+use crate::rubix_cube::RubixCube;
+
+// Standard notation on top of the turn_cube codes.
+// Faces: U = F1 (top), L = F2, F = F3 (front), R = F4, D = F5 (bottom), B = F6 (rear)
+//
+//   0 U'   1 E    2 D    3 U    4 E'   5 D'
+//   6 L'   7 M'   8 R    9 L   10 M   11 R'
+//  12 F'  13 S'  14 B   15 F   16 S   17 B'
+//
+// Every code c has its inverse 3 along (c + 3 or c - 3), the same rule undo_turn uses.
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum Layer {
+    U, D, L, R, F, B,
+    M, E, S,
+    Uw, Dw, Lw, Rw, Fw, Bw,
+    X, Y, Z,
+}
+
+impl Layer {
+    // Codes making up one clockwise quarter turn of this layer
+    pub fn codes(&self) -> &'static [u8] {
+        return match self {
+            Layer::U => &[3],
+            Layer::D => &[2],
+            Layer::L => &[9],
+            Layer::R => &[8],
+            Layer::F => &[15],
+            Layer::B => &[14],
+            Layer::M => &[10],
+            Layer::E => &[1],
+            Layer::S => &[16],
+            Layer::Uw => &[3, 4],
+            Layer::Dw => &[2, 1],
+            Layer::Lw => &[9, 10],
+            Layer::Rw => &[8, 7],
+            Layer::Fw => &[15, 16],
+            Layer::Bw => &[14, 13],
+            Layer::X => &[8, 7, 6],
+            Layer::Y => &[3, 4, 5],
+            Layer::Z => &[15, 16, 17],
+        };
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        return match self {
+            Layer::U => "U",
+            Layer::D => "D",
+            Layer::L => "L",
+            Layer::R => "R",
+            Layer::F => "F",
+            Layer::B => "B",
+            Layer::M => "M",
+            Layer::E => "E",
+            Layer::S => "S",
+            Layer::Uw => "u",
+            Layer::Dw => "d",
+            Layer::Lw => "l",
+            Layer::Rw => "r",
+            Layer::Fw => "f",
+            Layer::Bw => "b",
+            Layer::X => "x",
+            Layer::Y => "y",
+            Layer::Z => "z",
+        };
+    }
+
+    fn from_symbol(symbol: char) -> Option<Layer> {
+        return match symbol {
+            'U' => Some(Layer::U),
+            'D' => Some(Layer::D),
+            'L' => Some(Layer::L),
+            'R' => Some(Layer::R),
+            'F' => Some(Layer::F),
+            'B' => Some(Layer::B),
+            'M' => Some(Layer::M),
+            'E' => Some(Layer::E),
+            'S' => Some(Layer::S),
+            'u' => Some(Layer::Uw),
+            'd' => Some(Layer::Dw),
+            'l' => Some(Layer::Lw),
+            'r' => Some(Layer::Rw),
+            'f' => Some(Layer::Fw),
+            'b' => Some(Layer::Bw),
+            'x' => Some(Layer::X),
+            'y' => Some(Layer::Y),
+            'z' => Some(Layer::Z),
+            _ => None,
+        };
+    }
+
+    fn wide(&self) -> Option<Layer> {
+        return match self {
+            Layer::U => Some(Layer::Uw),
+            Layer::D => Some(Layer::Dw),
+            Layer::L => Some(Layer::Lw),
+            Layer::R => Some(Layer::Rw),
+            Layer::F => Some(Layer::Fw),
+            Layer::B => Some(Layer::Bw),
+            _ => None,
+        };
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct Move {
+    pub layer: Layer,
+    // Clockwise quarter turns: 1, 2 or 3 (written as ')
+    pub amount: u8,
+}
+
+impl Move {
+    pub fn new(layer: Layer, amount: u8) -> Move {
+        return Move { layer, amount: amount % 4 };
+    }
+
+    // The move turn_cube makes for code, an error for codes over 17 as try_turn gives
+    pub fn from_code(code: u8) -> Result<Move, CubeError> {
+        let (layer, amount) = match code {
+            0 => (Layer::U, 3),
+            1 => (Layer::E, 1),
+            2 => (Layer::D, 1),
+            3 => (Layer::U, 1),
+            4 => (Layer::E, 3),
+            5 => (Layer::D, 3),
+            6 => (Layer::L, 3),
+            7 => (Layer::M, 3),
+            8 => (Layer::R, 1),
+            9 => (Layer::L, 1),
+            10 => (Layer::M, 1),
+            11 => (Layer::R, 3),
+            12 => (Layer::F, 3),
+            13 => (Layer::S, 3),
+            14 => (Layer::B, 1),
+            15 => (Layer::F, 1),
+            16 => (Layer::S, 1),
+            17 => (Layer::B, 3),
+            _ => return Err(CubeError::InvalidTurn(code)),
+        };
+        return Ok(Move { layer, amount });
+    }
+
+    pub fn inverse(&self) -> Move {
+        return Move { layer: self.layer, amount: 4 - self.amount };
+    }
+
+    pub fn codes(&self) -> Vec<u8> {
+        let mut codes = Vec::new();
+        for _ in 0..self.amount {
+            codes.extend_from_slice(self.layer.codes());
+        }
+        return codes;
+    }
+
+    // This move as seen from the original orientation, when done after a y rotation
+    fn after_y(&self) -> Move {
+        let (layer, inverted) = match self.layer {
+            Layer::F => (Layer::R, false),
+            Layer::R => (Layer::B, false),
+            Layer::B => (Layer::L, false),
+            Layer::L => (Layer::F, false),
+            Layer::Fw => (Layer::Rw, false),
+            Layer::Rw => (Layer::Bw, false),
+            Layer::Bw => (Layer::Lw, false),
+            Layer::Lw => (Layer::Fw, false),
+            Layer::M => (Layer::S, false),
+            Layer::S => (Layer::M, true),
+            Layer::X => (Layer::Z, true),
+            Layer::Z => (Layer::X, false),
+            other => (other, false),
+        };
+        return Move { layer, amount: if inverted { 4 - self.amount } else { self.amount } };
+    }
+
+    pub fn is_rotation(&self) -> bool {
+        return matches!(self.layer, Layer::X | Layer::Y | Layer::Z);
+    }
+}
+
+// Whether a face turn search should try next after last: never the same face twice, and of
+// two opposite faces (which commute) only one order
+pub fn follows(last: Option<Move>, next: Move) -> bool {
+    let Some(last) = last else {
+        return true;
+    };
+    let (last_axis, last_rank) = axis(last.layer);
+    let (next_axis, next_rank) = axis(next.layer);
+    return last_axis != next_axis || next_rank > last_rank;
+}
+
+fn axis(layer: Layer) -> (u8, u8) {
+    return match layer {
+        Layer::U => (0, 0),
+        Layer::D => (0, 1),
+        Layer::L => (1, 0),
+        Layer::R => (1, 1),
+        Layer::F => (2, 0),
+        _ => (2, 1),
+    };
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.amount {
+            2 => write!(f, "{}2", self.layer.symbol()),
+            3 => write!(f, "{}'", self.layer.symbol()),
+            _ => write!(f, "{}", self.layer.symbol()),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Default, Hash)]
+pub struct Algorithm {
+    moves: Vec<Move>,
+}
+
+impl Algorithm {
+    pub fn new(moves: Vec<Move>) -> Algorithm {
+        return Algorithm { moves };
+    }
+
+    // Accepts the usual notation: R U R' U', R2, R2', Rw / r, M E S, x y z and brackets for grouping
+    pub fn parse(text: &str) -> Result<Algorithm, String> {
+        let mut moves = Vec::new();
+        for token in text.split(|c: char| c.is_whitespace() || c == '(' || c == ')' || c == '[' || c == ']') {
+            if token.is_empty() {
+                continue;
+            }
+            let mut chars = token.chars();
+            let first = chars.next().unwrap();
+            let mut layer = Layer::from_symbol(first).ok_or(format!("Unknown move '{}'", token))?;
+            let mut rest = chars.as_str();
+            if let Some(stripped) = rest.strip_prefix('w') {
+                layer = layer.wide().ok_or(format!("Unknown move '{}'", token))?;
+                rest = stripped;
+            }
+            let amount = match rest {
+                "" => 1,
+                "'" => 3,
+                "2" | "2'" | "'2" => 2,
+                _ => return Err(format!("Unknown move '{}'", token)),
+            };
+            moves.push(Move { layer, amount });
+        }
+        return Ok(Algorithm { moves });
+    }
+
+    // Turns a list of turn_cube codes into notation, joining repeated turns (3 3 -> U2)
+    pub fn from_codes(codes: &[u8]) -> Result<Algorithm, CubeError> {
+        let moves = codes.iter().map(|&code| Move::from_code(code)).collect::<Result<Vec<Move>, CubeError>>()?;
+        return Ok(Algorithm { moves }.simplified());
+    }
+
+    pub fn moves(&self) -> &[Move] {
+        return &self.moves;
+    }
+
+    pub fn len(&self) -> usize {
+        return self.moves.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.moves.is_empty();
+    }
+
+    pub fn push(&mut self, next: Move) {
+        self.moves.push(next);
+    }
+
+    pub fn codes(&self) -> Vec<u8> {
+        return self.moves.iter().flat_map(|m| m.codes()).collect();
+    }
+
+    pub fn inverse(&self) -> Algorithm {
+        return Algorithm { moves: self.moves.iter().rev().map(|m| m.inverse()).collect() };
+    }
+
+    // Same turns with neighbouring turns of one layer joined and cancelled, e.g. R R' U U -> U2
+    pub fn simplified(&self) -> Algorithm {
+        let mut moves: Vec<Move> = Vec::new();
+        for &next in &self.moves {
+            match moves.last_mut() {
+                Some(last) if last.layer == next.layer => {
+                    last.amount = (last.amount + next.amount) % 4;
+                    if last.amount == 0 {
+                        moves.pop();
+                    }
+                }
+                _ => moves.push(next),
+            }
+        }
+        return Algorithm { moves };
+    }
+
+    pub fn concat(&self, other: &Algorithm) -> Algorithm {
+        let mut moves = self.moves.clone();
+        moves.extend_from_slice(&other.moves);
+        return Algorithm { moves };
+    }
+
+    // length random face turns, never two on one face in a row or both orders of two opposite faces
+    pub fn random(length: usize, rng: &mut impl Rng) -> Algorithm {
+        const FACES: [Layer; 6] = [Layer::U, Layer::D, Layer::L, Layer::R, Layer::F, Layer::B];
+        let mut moves: Vec<Move> = Vec::new();
+        while moves.len() < length {
+            let next = Move::new(FACES[rng.gen_range(0..6)], rng.gen_range(1..4));
+            if follows(moves.last().copied(), next) {
+                moves.push(next);
+            }
+        }
+        return Algorithm { moves };
+    }
+
+    // The same turns written for a cube that has been turned with y `turns` times first,
+    // e.g. R U R' becomes F U F' after one y
+    pub fn after_y(&self, turns: u8) -> Algorithm {
+        let mut moves = self.moves.clone();
+        for _ in 0..turns % 4 {
+            for next in moves.iter_mut() {
+                *next = next.after_y();
+            }
+        }
+        return Algorithm { moves };
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, next) in self.moves.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", next)?;
+        }
+        Ok(())
+    }
+}
+
+impl RubixCube {
+    pub fn apply_algorithm(&mut self, algorithm: &Algorithm) {
+        for code in algorithm.codes() {
+            self.turn_cube(&code);
+        }
+    }
+
+    pub fn apply_move(&mut self, turn: Move) {
+        for code in turn.codes() {
+            self.turn_cube(&code);
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use std::f32::consts::FRAC_PI_2;
+use std::fmt::Write;
+use std::time::Duration;
+
+use crate::algorithm::{Algorithm, Layer, Move};
+use crate::diagram::{self, Diagram, Rgb, Scene, View};
+use crate::rubix_cube::RubixCube;
+
+// A solution played out on a cube, as an animated SVG or, with the gif feature, a GIF. Every
+// move gets a picture of the state after it, captioned with its number and notation; in the
+// isometric view turn_frames more pictures show the layer part way round.
+//
+// Part way through a turn the cube is drawn as its 26 pieces, slice by slice along the turning
+// axis from the back, so a turning layer covers and uncovers the rest properly.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Animation {
+    pub diagram: Diagram,
+    // Pictures while each layer turns, isometric view only; 0 jumps from state to state
+    pub turn_frames: usize,
+    // How long each state is shown; the last stays three times as long
+    pub pause: Duration,
+    // How long each picture part way through a turn is shown
+    pub turn_frame_time: Duration,
+    pub captions: bool,
+}
+
+impl Default for Animation {
+    fn default() -> Animation {
+        return Animation {
+            diagram: Diagram::default().with_view(View::Isometric),
+            turn_frames: 6,
+            pause: Duration::from_millis(700),
+            turn_frame_time: Duration::from_millis(40),
+            captions: true,
+        };
+    }
+}
+
+pub(crate) struct Frame {
+    pub(crate) scene: Scene,
+    pub(crate) time: Duration,
+}
+
+// Outward normals of the faces, in the order of faces
+const NORMALS: [[f32; 3]; 6] = [[0.0, 1.0, 0.0], [-1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, -1.0, 0.0], [0.0, 0.0, -1.0]];
+
+// The isometric view looks along this, from the U F R corner
+const TOWARDS_VIEWER: [f32; 3] = [1.0, 1.0, 1.0];
+
+// The axis a layer turns about (0 towards R, 1 towards U, 2 towards F), which of the three
+// slices along it turn, and the angle of a clockwise quarter turn, right handed
+fn turn_of(layer: Layer) -> (usize, [bool; 3], f32) {
+    let (axis, slices, clockwise_from_positive) = match layer {
+        Layer::R => (0, [false, false, true], true),
+        Layer::L => (0, [true, false, false], false),
+        Layer::M => (0, [false, true, false], false),
+        Layer::Rw => (0, [false, true, true], true),
+        Layer::Lw => (0, [true, true, false], false),
+        Layer::X => (0, [true, true, true], true),
+        Layer::U => (1, [false, false, true], true),
+        Layer::D => (1, [true, false, false], false),
+        Layer::E => (1, [false, true, false], false),
+        Layer::Uw => (1, [false, true, true], true),
+        Layer::Dw => (1, [true, true, false], false),
+        Layer::Y => (1, [true, true, true], true),
+        Layer::F => (2, [false, false, true], true),
+        Layer::B => (2, [true, false, false], false),
+        Layer::S => (2, [false, true, false], true),
+        Layer::Fw => (2, [false, true, true], true),
+        Layer::Bw => (2, [true, true, false], false),
+        Layer::Z => (2, [true, true, true], true),
+    };
+    return (axis, slices, if clockwise_from_positive { -FRAC_PI_2 } else { FRAC_PI_2 });
+}
+
+// Turns point by angle about the line through the cube's centre along axis
+fn rotate(point: [f32; 3], axis: usize, angle: f32) -> [f32; 3] {
+    let (a, b) = ((axis + 1) % 3, (axis + 2) % 3);
+    let (sin, cos) = angle.sin_cos();
+    let (u, v) = (point[a] - 1.5, point[b] - 1.5);
+    let mut turned = point;
+    turned[a] = 1.5 + u * cos - v * sin;
+    turned[b] = 1.5 + u * sin + v * cos;
+    return turned;
+}
+
+fn facing_viewer(normal: [f32; 3]) -> bool {
+    return normal.iter().zip(TOWARDS_VIEWER).map(|(n, v)| n * v).sum::<f32>() > 1e-3;
+}
+
+fn depth(point: [f32; 3]) -> f32 {
+    return point.iter().zip(TOWARDS_VIEWER).map(|(p, v)| p * v).sum();
+}
+
+impl Animation {
+    pub fn with_diagram(self, diagram: Diagram) -> Animation {
+        return Animation { diagram, ..self };
+    }
+
+    fn interpolates(&self) -> bool {
+        return self.diagram.view == View::Isometric && self.turn_frames > 0;
+    }
+
+    // The cube as pieces, with the layers move turns progress of the way round
+    fn pieces_scene(&self, cube: &RubixCube, turn: Option<(Move, f32)>) -> Scene {
+        let (axis, turning, angle) = match turn {
+            Some((turn, progress)) => {
+                let (axis, slices, quarter) = turn_of(turn.layer);
+                let quarters = if turn.amount == 3 { -1.0 } else { turn.amount as f32 };
+                (axis, slices, quarter * quarters * progress)
+            }
+            None => (1, [false; 3], 0.0),
+        };
+        let mut stickers: Vec<([usize; 3], usize, usize)> = Vec::new();
+        for (face, normal) in NORMALS.iter().enumerate() {
+            for index in 0..9 {
+                let centre = diagram::face_point(face, (index / 3) as f32 + 0.5, (index % 3) as f32 + 0.5);
+                let piece = [0, 1, 2].map(|k| (centre[k] - 0.5 * normal[k]).floor() as usize);
+                stickers.push((piece, face, index));
+            }
+        }
+        let inset = 0.06;
+        let mut polygons: Vec<(Vec<[f32; 3]>, Rgb)> = Vec::new();
+        for (slice, &turns) in turning.iter().enumerate() {
+            let place = |point: [f32; 3]| if turns { rotate(point, axis, angle) } else { point };
+            let turn_normal = |normal: [f32; 3]| if turns { rotate(normal.map(|n| n + 1.5), axis, angle).map(|n| n - 1.5) } else { normal };
+            let mut pieces: Vec<[usize; 3]> = Vec::new();
+            for a in 0..3 {
+                for b in 0..3 {
+                    let mut piece = [0; 3];
+                    piece[axis] = slice;
+                    piece[(axis + 1) % 3] = a;
+                    piece[(axis + 2) % 3] = b;
+                    if piece != [1, 1, 1] {
+                        pieces.push(piece);
+                    }
+                }
+            }
+            pieces.sort_by(|p, q| {
+                let centre = |piece: &[usize; 3]| place(piece.map(|k| k as f32 + 0.5));
+                return depth(centre(p)).total_cmp(&depth(centre(q)));
+            });
+            for piece in pieces {
+                let corner = piece.map(|k| k as f32);
+                for normal in NORMALS {
+                    if !facing_viewer(turn_normal(normal)) {
+                        continue;
+                    }
+                    let k = normal.iter().position(|&n| n != 0.0).unwrap_or(0);
+                    let (a, b) = ((k + 1) % 3, (k + 2) % 3);
+                    let mut base = corner;
+                    base[k] += normal[k].max(0.0);
+                    let square = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)].map(|(u, v)| {
+                        let mut point = base;
+                        point[a] += u;
+                        point[b] += v;
+                        place(point)
+                    });
+                    polygons.push((square.to_vec(), self.diagram.palette.body));
+                }
+                for &(_, face, index) in stickers.iter().filter(|(of, _, _)| *of == piece) {
+                    if !facing_viewer(turn_normal(NORMALS[face])) {
+                        continue;
+                    }
+                    let (row, column) = ((index / 3) as f32, (index % 3) as f32);
+                    let (near, far) = (inset, 1.0 - inset);
+                    let corners = [(near, near), (near, far), (far, far), (far, near)].map(|(down, across)| place(diagram::face_point(face, row + down, column + across)));
+                    polygons.push((corners.to_vec(), self.diagram.sticker_fill(cube, face, index)));
+                }
+            }
+        }
+        return diagram::flatten(&polygons, self.diagram.sticker_size as f32, true);
+    }
+
+    fn state_scene(&self, cube: &RubixCube) -> Scene {
+        if self.interpolates() {
+            return self.pieces_scene(cube, None);
+        }
+        return self.diagram.scene(cube);
+    }
+
+    fn caption(&self, scene: Scene, text: &str) -> Scene {
+        if !self.captions {
+            return scene;
+        }
+        return scene.with_caption(text, self.diagram.palette.body, self.diagram.sticker_size as f32);
+    }
+
+    pub(crate) fn frames(&self, cube: &RubixCube, algorithm: &Algorithm) -> Vec<Frame> {
+        let moves = algorithm.moves();
+        let mut cube = *cube;
+        let mut frames = vec![Frame { scene: self.caption(self.state_scene(&cube), &format!("0/{}", moves.len())), time: self.pause }];
+        for (number, &turn) in moves.iter().enumerate() {
+            let caption = format!("{}/{} {}", number + 1, moves.len(), turn);
+            if self.interpolates() {
+                for step in 1..=self.turn_frames {
+                    let progress = step as f32 / (self.turn_frames + 1) as f32;
+                    frames.push(Frame { scene: self.caption(self.pieces_scene(&cube, Some((turn, progress))), &caption), time: self.turn_frame_time });
+                }
+            }
+            cube.apply_move(turn);
+            frames.push(Frame { scene: self.caption(self.state_scene(&cube), &caption), time: self.pause });
+        }
+        if let Some(last) = frames.last_mut() {
+            last.time *= 3;
+        }
+        return frames;
+    }
+
+    // One SVG whose frames show in turn, looping, by SMIL animation
+    pub fn svg(&self, cube: &RubixCube, algorithm: &Algorithm) -> String {
+        let frames = self.frames(cube, algorithm);
+        let total: Duration = frames.iter().map(|frame| frame.time).sum();
+        let (width, height) = (frames[0].scene.width_px(), frames[0].scene.height_px());
+        let mut text = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n", width, height, width, height);
+        if let Some(background) = self.diagram.palette.background {
+            let _ = writeln!(text, "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>", diagram::hex(background));
+        }
+        let mut start = Duration::ZERO;
+        for (i, frame) in frames.iter().enumerate() {
+            let end = start + frame.time;
+            let (from, to) = (start.as_secs_f64() / total.as_secs_f64(), end.as_secs_f64() / total.as_secs_f64());
+            let _ = writeln!(text, "<g visibility=\"{}\">", if i == 0 { "visible" } else { "hidden" });
+            text.push_str(&frame.scene.svg_shapes());
+            let timing = match (i == 0, i + 1 == frames.len()) {
+                (true, true) => None,
+                (true, false) => Some((String::from("visible;hidden"), format!("0;{:.5}", to))),
+                (false, true) => Some((String::from("hidden;visible"), format!("0;{:.5}", from))),
+                (false, false) => Some((String::from("hidden;visible;hidden"), format!("0;{:.5};{:.5}", from, to))),
+            };
+            if let Some((values, times)) = timing {
+                let _ = writeln!(
+                    text,
+                    "<animate attributeName=\"visibility\" values=\"{}\" keyTimes=\"{}\" dur=\"{:.3}s\" calcMode=\"discrete\" repeatCount=\"indefinite\"/>",
+                    values,
+                    times,
+                    total.as_secs_f64()
+                );
+            }
+            text.push_str("</g>\n");
+            start = end;
+        }
+        text.push_str("</svg>\n");
+        return text;
+    }
+
+    // A looping GIF, on the palette's background or white
+    #[cfg(feature = "gif")]
+    pub fn gif(&self, cube: &RubixCube, algorithm: &Algorithm) -> Result<Vec<u8>, String> {
+        let background = self.diagram.palette.background.unwrap_or((255, 255, 255));
+        let frames = self.frames(cube, algorithm);
+        let width = u16::try_from(frames[0].scene.width_px()).map_err(|_| "the picture is too wide for a GIF")?;
+        let height = u16::try_from(frames[0].scene.height_px()).map_err(|_| "the picture is too tall for a GIF")?;
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = gif::Encoder::new(&mut bytes, width, height, &[]).map_err(|error| error.to_string())?;
+            encoder.set_repeat(gif::Repeat::Infinite).map_err(|error| error.to_string())?;
+            for frame in &frames {
+                let mut pixels = frame.scene.pixmap(Some(background))?.take();
+                let mut picture = gif::Frame::from_rgba_speed(width, height, &mut pixels, 10);
+                // In hundredths of a second
+                picture.delay = (frame.time.as_millis() / 10).min(u16::MAX as u128) as u16;
+                encoder.write_frame(&picture).map_err(|error| error.to_string())?;
+            }
+        }
+        return Ok(bytes);
+    }
+}
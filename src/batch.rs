@@ -0,0 +1,207 @@
+use std::fmt;
+use std::io;
+use std::time::{Duration, Instant};
+
+use rayon::prelude::*;
+
+use crate::algorithm::Algorithm;
+use crate::library::AlgorithmLibrary;
+use crate::rubix_cube::RubixCube;
+use crate::solution::SolveReport;
+use crate::solver::Solver;
+use crate::threads::Threads;
+
+// Solving a file of cases, one per line: either the 54 facelets letters of a state or a
+// scramble in notation made from solved (see parse_case). Blank lines and lines starting
+// with # are skipped. Cases are read and solved a chunk at a time across the threads, and
+// handed back in the order they were read, so a run over stdin streams its results.
+//
+// Each result can be written as a CSV row:
+//
+//   line,input,facelets,moves,solution,nodes,time_us,error
+//
+// where moves, solution, nodes and time_us are empty for a case that failed, and error says
+// why; facelets is empty if the line couldn't be read as a cube. With the serde feature it
+// can also be a JSON line, the caseResult of schema/rubix.schema.json:
+//
+//   {"line": 3, "input": "R U R'", "report": {"facelets": "...", "solution": {...}, ...}}
+//   {"line": 4, "input": "R Q", "error": "Unknown move 'Q'"}
+
+pub struct CaseResult {
+    // 1-based line number in the input
+    pub line: usize,
+    pub input: String,
+    pub cube: Option<RubixCube>,
+    pub outcome: Result<SolveReport, String>,
+}
+
+// A line saying what it is, "facelets: <54 letters>" or "scramble: <moves>", or either one
+// bare. A bare line is read as facelets if it is all colour letters and either 54 of them or
+// has some that aren't moves (W Y G O), and as a scramble otherwise. That only gets a
+// scramble wrong if it is 54 turns of R and B alone, which needs the prefix.
+pub fn parse_case(line: &str) -> Result<RubixCube, String> {
+    let line = line.trim();
+    if let Some(facelets) = line.strip_prefix("facelets:") {
+        return Ok(RubixCube::from_facelets(facelets)?);
+    }
+    if let Some(moves) = line.strip_prefix("scramble:") {
+        return scramble(moves);
+    }
+    let letters: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    let colours = letters.chars().all(|c| "WYRGOB".contains(c));
+    if colours && (letters.len() == 54 || letters.contains(['W', 'Y', 'G', 'O'])) {
+        return Ok(RubixCube::from_facelets(&letters)?);
+    }
+    return scramble(line);
+}
+
+fn scramble(moves: &str) -> Result<RubixCube, String> {
+    let mut cube = RubixCube::create_solved_rubix();
+    cube.apply_algorithm(&Algorithm::parse(moves)?);
+    return Ok(cube);
+}
+
+impl CaseResult {
+    pub const CSV_HEADER: &'static str = "line,input,facelets,moves,solution,nodes,time_us,error";
+
+    pub fn csv_row(&self) -> String {
+        let facelets = self.cube.map(|cube| cube.facelets()).unwrap_or_default();
+        let fields = match &self.outcome {
+            Ok(report) => [
+                report.solution.move_count().to_string(),
+                report.solution.algorithm().to_string(),
+                report.nodes.map(|nodes| nodes.to_string()).unwrap_or_default(),
+                report.time.as_micros().to_string(),
+                String::new(),
+            ],
+            Err(error) => [String::new(), String::new(), String::new(), String::new(), error.clone()],
+        };
+        let mut row = vec![self.line.to_string(), self.input.clone(), facelets];
+        row.extend(fields);
+        return row.iter().map(|field| csv_field(field)).collect::<Vec<_>>().join(",");
+    }
+
+    // The result as one line of JSON
+    #[cfg(feature = "serde")]
+    pub fn json_line(&self) -> String {
+        return serde_json::to_string(self).expect("a case result is plain JSON");
+    }
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        return format!("\"{}\"", field.replace('"', "\"\""));
+    }
+    return field.to_string();
+}
+
+// Totals over a batch
+#[derive(Clone, Debug, Default)]
+pub struct BatchStats {
+    pub cases: usize,
+    pub solved: usize,
+    pub failed: usize,
+    pub total_moves: usize,
+    pub fewest_moves: usize,
+    pub most_moves: usize,
+    pub nodes: u64,
+    // Time spent in the solvers, summed over cases
+    pub solve_time: Duration,
+    // Time from start to finish
+    pub wall_time: Duration,
+}
+
+impl BatchStats {
+    pub fn add(&mut self, result: &CaseResult) {
+        self.cases += 1;
+        let Ok(report) = &result.outcome else {
+            self.failed += 1;
+            return;
+        };
+        let moves = report.solution.move_count();
+        self.fewest_moves = if self.solved == 0 { moves } else { self.fewest_moves.min(moves) };
+        self.most_moves = self.most_moves.max(moves);
+        self.total_moves += moves;
+        self.nodes += report.nodes.unwrap_or(0);
+        self.solve_time += report.time;
+        self.solved += 1;
+    }
+
+    pub fn average_moves(&self) -> f64 {
+        if self.solved == 0 {
+            return 0.0;
+        }
+        return self.total_moves as f64 / self.solved as f64;
+    }
+
+    pub fn average_time(&self) -> Duration {
+        if self.solved == 0 {
+            return Duration::ZERO;
+        }
+        return self.solve_time / self.solved as u32;
+    }
+
+    pub fn cases_per_second(&self) -> f64 {
+        return self.cases as f64 / self.wall_time.as_secs_f64().max(1e-9);
+    }
+}
+
+impl fmt::Display for BatchStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cases {}  solved {}  failed {}  moves avg {:.1} min {} max {}  nodes {}  solve time avg {:?}  wall time {:?} ({:.1} cases/s)",
+            self.cases,
+            self.solved,
+            self.failed,
+            self.average_moves(),
+            self.fewest_moves,
+            self.most_moves,
+            self.nodes,
+            self.average_time(),
+            self.wall_time,
+            self.cases_per_second()
+        )
+    }
+}
+
+// Solves every case in lines with solver, passing each result to emit in input order
+pub fn solve_batch(
+    lines: impl Iterator<Item = io::Result<String>>,
+    solver: &Solver,
+    library: &AlgorithmLibrary,
+    threads: &Threads,
+    mut emit: impl FnMut(&CaseResult) -> io::Result<()>,
+) -> Result<BatchStats, String> {
+    let start = Instant::now();
+    let chunk_size = threads.thread_count() * 16;
+    let mut stats = BatchStats::default();
+    let mut chunk: Vec<(usize, String)> = Vec::new();
+    let mut lines = lines.enumerate().peekable();
+    while lines.peek().is_some() {
+        chunk.clear();
+        while chunk.len() < chunk_size {
+            let Some((index, line)) = lines.next() else {
+                break;
+            };
+            let line = line.map_err(|error| format!("cannot read line {}: {}", index + 1, error))?;
+            let case = line.trim();
+            if !case.is_empty() && !case.starts_with('#') {
+                chunk.push((index + 1, case.to_string()));
+            }
+        }
+        let solve = |(line, input): &(usize, String)| {
+            let cube = parse_case(input);
+            let outcome = cube.clone().and_then(|cube| solver.solve(&cube, library).map_err(String::from));
+            CaseResult { line: *line, input: input.clone(), cube: cube.ok(), outcome }
+        };
+        let results: Vec<CaseResult> =
+            if threads.is_single() { chunk.iter().map(solve).collect() } else { threads.install(|| chunk.par_iter().map(solve).collect()) };
+        for result in &results {
+            stats.add(result);
+            emit(result).map_err(|error| format!("cannot write results: {}", error))?;
+        }
+    }
+    stats.wall_time = start.elapsed();
+    return Ok(stats);
+}
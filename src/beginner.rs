@@ -0,0 +1,312 @@
+use crate::algorithm::Algorithm;
+use crate::cubies::{self, D, U};
+use crate::rubix_cube::RubixCube;
+use crate::solution::Solution;
+
+// Layer by layer, the way it is usually taught: D cross, D corners, middle edges, then the
+// last layer in four small steps. Every step is a fixed algorithm picked by looking at the
+// cube, so it runs in no time whatever the scramble.
+//
+// Pieces are always handled "in front": a frame of n means the cube as seen after n y turns,
+// and the moves played there are written back in the cube's real orientation.
+
+const CROSS_EDGE: usize = 5; // DF
+const FIRST_LAYER_CORNER: usize = 4; // DFR
+const SECOND_LAYER_EDGE: usize = 8; // FR
+
+// Upper bound on algorithm applications per step; only an impossible cube gets near it
+const STEP_LIMIT: usize = 12;
+
+struct Solver {
+    cube: RubixCube,
+    stage: Vec<u8>,
+    solution: Solution,
+}
+
+impl Solver {
+    fn view(&self, frame: u8) -> RubixCube {
+        let mut view = self.cube;
+        for _ in 0..frame % 4 {
+            view.apply_algorithm(&Algorithm::parse("y").unwrap());
+        }
+        return view;
+    }
+
+    fn play(&mut self, algorithm: &str, frame: u8) {
+        let algorithm = Algorithm::parse(algorithm).expect("built in algorithm").after_y(frame);
+        for code in algorithm.codes() {
+            self.cube.turn_cube(&code);
+            self.stage.push(code);
+        }
+    }
+
+    // U turns until test passes, None if no angle works
+    fn align_u(&mut self, frame: u8, test: impl Fn(&RubixCube) -> bool) -> Option<()> {
+        for _ in 0..4 {
+            if test(&self.view(frame)) {
+                return Some(());
+            }
+            self.play("U", frame);
+        }
+        return None;
+    }
+
+    fn finish_stage(&mut self, name: &str, explanation: &str) {
+        let algorithm = Algorithm::from_codes(&self.stage).expect("stage turn codes");
+        self.stage.clear();
+        self.solution.push_stage(name, explanation, algorithm);
+    }
+
+    fn colour_name(&self, face: usize) -> &'static str {
+        return self.cube.get_faces()[face][4].name();
+    }
+
+    fn cross(&mut self) -> Option<()> {
+        for frame in 0..4 {
+            let mut steps = 0;
+            loop {
+                steps += 1;
+                if steps > STEP_LIMIT {
+                    return None;
+                }
+                let (position, flip) = cubies::find_edge(&self.view(frame), CROSS_EDGE)?;
+                match (position, flip) {
+                    (5, 0) => break,
+                    (5, _) => self.play("F2", frame),
+                    (4, _) => self.play("R2", frame),
+                    (6, _) => self.play("L2", frame),
+                    (7, _) => self.play("B2", frame),
+                    (8, _) => self.play("R U R'", frame),
+                    (9, _) => self.play("L' U' L", frame),
+                    (10, _) => self.play("L U L'", frame),
+                    (11, _) => self.play("R' U' R", frame),
+                    _ => {
+                        self.align_u(frame, |view| cubies::find_edge(view, CROSS_EDGE).map(|(p, _)| p) == Some(1))?;
+                        if flip == 0 {
+                            self.play("F2", frame);
+                        } else {
+                            self.play("U' R' F R", frame);
+                        }
+                    }
+                }
+            }
+        }
+        let explanation = format!(
+            "Bring the four edges with the {} centre colour down around the {} centre, each under the centre matching its other colour.",
+            self.colour_name(D),
+            self.colour_name(D)
+        );
+        self.finish_stage("Cross", &explanation);
+        return Some(());
+    }
+
+    fn first_layer_corners(&mut self) -> Option<()> {
+        for frame in 0..4 {
+            let mut steps = 0;
+            loop {
+                steps += 1;
+                if steps > STEP_LIMIT {
+                    return None;
+                }
+                let (position, twist) = cubies::find_corner(&self.view(frame), FIRST_LAYER_CORNER)?;
+                match position {
+                    4 if twist == 0 => break,
+                    // In the D layer but wrong: lift it out from the front right of its own frame
+                    4 => self.play("R U R'", frame),
+                    7 => self.play("R U R'", frame + 1),
+                    6 => self.play("R U R'", frame + 2),
+                    5 => self.play("R U R'", frame + 3),
+                    _ => {
+                        self.align_u(frame, |view| cubies::find_corner(view, FIRST_LAYER_CORNER).map(|(p, _)| p) == Some(0))?;
+                        let mut repeats = 0;
+                        while !cubies::corner_solved(&self.view(frame), FIRST_LAYER_CORNER) {
+                            repeats += 1;
+                            if repeats > 6 {
+                                return None;
+                            }
+                            self.play("R U R' U'", frame);
+                        }
+                    }
+                }
+            }
+        }
+        let explanation = format!(
+            "Hold each {} corner above the slot it belongs in at the front right and repeat R U R' U' until it drops in solved.",
+            self.colour_name(D)
+        );
+        self.finish_stage("First layer corners", &explanation);
+        return Some(());
+    }
+
+    fn second_layer(&mut self) -> Option<()> {
+        for frame in 0..4 {
+            let mut steps = 0;
+            loop {
+                steps += 1;
+                if steps > STEP_LIMIT {
+                    return None;
+                }
+                let (position, flip) = cubies::find_edge(&self.view(frame), SECOND_LAYER_EDGE)?;
+                match position {
+                    8 if flip == 0 => break,
+                    // Stuck in a middle slot: insert any top edge there to push it out
+                    8 => self.play("U R U' R' U' F' U F", frame),
+                    11 => self.play("U R U' R' U' F' U F", frame + 1),
+                    10 => self.play("U R U' R' U' F' U F", frame + 2),
+                    9 => self.play("U R U' R' U' F' U F", frame + 3),
+                    _ if flip == 1 => {
+                        // Front colour on the side: line it up with the front centre and go right
+                        self.align_u(frame, |view| cubies::find_edge(view, SECOND_LAYER_EDGE).map(|(p, _)| p) == Some(1))?;
+                        self.play("U R U' R' U' F' U F", frame);
+                    }
+                    _ => {
+                        // Right colour on the side: line it up with the right centre and go left
+                        self.align_u(frame, |view| cubies::find_edge(view, SECOND_LAYER_EDGE).map(|(p, _)| p) == Some(0))?;
+                        self.play("U' F' U F U R U' R'", frame);
+                    }
+                }
+            }
+        }
+        self.finish_stage(
+            "Second layer",
+            "Match each middle edge in the top layer to its front centre, then insert it to the right with U R U' R' U' F' U F or from the right face with U' F' U F U R U' R'.",
+        );
+        return Some(());
+    }
+
+    fn last_layer_cross(&mut self) -> Option<()> {
+        for _ in 0..STEP_LIMIT {
+            let oriented = oriented_top_edges(&self.cube);
+            match oriented.iter().filter(|&&o| o).count() {
+                4 => {
+                    let explanation = format!(
+                        "Make a {} cross on top with F R U R' U' F', holding a line left to right and an L shape at the back left.",
+                        self.colour_name(U)
+                    );
+                    self.finish_stage("Last layer cross", &explanation);
+                    return Some(());
+                }
+                2 => {
+                    // Line: UL and UR. L shape: UL and UB.
+                    let line = oriented[0] == oriented[2];
+                    self.align_u(0, |view| {
+                        let edges = oriented_top_edges(view);
+                        if line { edges[0] && edges[2] } else { edges[2] && edges[3] }
+                    })?;
+                    self.play("F R U R' U' F'", 0);
+                }
+                _ => self.play("F R U R' U' F'", 0),
+            }
+        }
+        return None;
+    }
+
+    fn last_layer_edges(&mut self) -> Option<()> {
+        for _ in 0..STEP_LIMIT {
+            // Turn U to line up as many edges as possible
+            let best = (0..4).max_by_key(|&turns| {
+                let mut view = self.cube;
+                for _ in 0..turns {
+                    view.turn_cube(&3);
+                }
+                placed_top_edges(&view).iter().filter(|&&p| p).count()
+            })?;
+            for _ in 0..best {
+                self.play("U", 0);
+            }
+            let placed = placed_top_edges(&self.cube);
+            let count = placed.iter().filter(|&&p| p).count();
+            if count == 4 {
+                self.finish_stage(
+                    "Last layer edges",
+                    "Turn U to match two edges to their centres, hold them at the back and right, and swap the other two with R U R' U R U2 R' U.",
+                );
+                return Some(());
+            }
+            // Adjacent pair at the back and right (UB and UR)
+            let frame = (0..4).find(|&frame| {
+                let edges = placed_top_edges(&self.view(frame));
+                edges[3] && edges[0]
+            });
+            self.play("R U R' U R U2 R' U", frame.unwrap_or(0));
+        }
+        return None;
+    }
+
+    fn last_layer_corner_positions(&mut self) -> Option<()> {
+        for _ in 0..STEP_LIMIT {
+            let placed = placed_top_corners(&self.cube);
+            if placed.iter().all(|&p| p) {
+                self.finish_stage(
+                    "Position last layer corners",
+                    "Hold a corner that is already in its place at the front right and cycle the other three with U R U' L' U R' U' L until every corner sits between its three centres.",
+                );
+                return Some(());
+            }
+            // URF, UBR, ULB, UFL reach the front right after 0, 1, 2, 3 y turns
+            let frame = [0, 3, 2, 1].iter().position(|&corner| placed[corner]).unwrap_or(0) as u8;
+            self.play("U R U' L' U R' U' L", frame);
+        }
+        return None;
+    }
+
+    fn last_layer_corner_orientations(&mut self) -> Option<()> {
+        for _ in 0..4 {
+            let mut repeats = 0;
+            while cubies::corner_at(&self.cube, 0)?.1 != 0 {
+                repeats += 1;
+                if repeats > 2 {
+                    return None;
+                }
+                self.play("R' D' R D R' D' R D", 0);
+            }
+            self.play("U", 0);
+        }
+        let explanation = format!(
+            "With the cube held still, repeat R' D' R D in pairs on the front right top corner until its {} sticker faces up, then turn U to bring the next corner there. The first layers come back once every corner is done.",
+            self.colour_name(U)
+        );
+        self.finish_stage("Orient last layer corners", &explanation);
+        return Some(());
+    }
+}
+
+fn oriented_top_edges(cube: &RubixCube) -> [bool; 4] {
+    let faces = cube.get_faces();
+    let up = faces[U][4];
+    // UR, UF, UL, UB
+    return [faces[U][5] == up, faces[U][7] == up, faces[U][3] == up, faces[U][1] == up];
+}
+
+fn placed_top_edges(cube: &RubixCube) -> [bool; 4] {
+    let mut placed = [false; 4];
+    for (edge, slot) in placed.iter_mut().enumerate() {
+        *slot = cubies::edge_at(cube, edge).map(|(piece, _)| piece) == Some(edge);
+    }
+    return placed;
+}
+
+fn placed_top_corners(cube: &RubixCube) -> [bool; 4] {
+    let mut placed = [false; 4];
+    for (corner, slot) in placed.iter_mut().enumerate() {
+        *slot = cubies::corner_at(cube, corner).map(|(piece, _)| piece) == Some(corner);
+    }
+    return placed;
+}
+
+// Solves any valid cube layer by layer, with the first layer on D (F5) and the last on U (F1).
+// Fails only if the cube cannot be solved.
+pub fn solve_beginner(cube: &RubixCube) -> Result<Solution, String> {
+    let mut solver = Solver { cube: *cube, stage: Vec::new(), solution: Solution::new("Beginner") };
+    solver.cross().ok_or("cross could not be solved")?;
+    solver.first_layer_corners().ok_or("first layer corners could not be solved")?;
+    solver.second_layer().ok_or("second layer could not be solved")?;
+    solver.last_layer_cross().ok_or("last layer cross could not be made")?;
+    solver.last_layer_edges().ok_or("last layer edges could not be placed")?;
+    solver.last_layer_corner_positions().ok_or("last layer corners could not be placed")?;
+    solver.last_layer_corner_orientations().ok_or("last layer corners could not be twisted")?;
+    if !solver.cube.is_solved() {
+        return Err(String::from("cube could not be solved"));
+    }
+    return Ok(solver.solution);
+}
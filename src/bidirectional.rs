@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+use std::mem;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::algorithm::{follows, Algorithm, Layer, Move};
+use crate::cubies::{CubieCube, CubieMove};
+use crate::error::CubeError;
+use crate::rubix_cube::RubixCube;
+use crate::state_key::StateKey;
+use crate::symmetry::symmetries;
+use crate::threads::Threads;
+
+// Meet in the middle: breadth first from the scramble and from solved at once, a layer at a
+// time on whichever side has the smaller frontier, until a state turns up on both sides.
+// Each side keeps the StateKey of every state it has seen with the move that reached it, so a
+// path is read back by undoing moves from the meeting state. An n move solution costs two
+// searches of about n / 2 moves, where Iddfs pays for all n. The side from solved keeps one
+// entry for each set of up to 48 symmetric states, so its frontier stays small and it does
+// most of the growing.
+//
+// Only the 18 face turns are used and solutions are optimal in that metric. A state costs
+// roughly BYTES_PER_STATE, so the memory limit decides how deep it can get: the default 1 GB
+// reaches 12 moves, which is the default max_depth. Asking for more with the same budget ends
+// in the memory limit error rather than a deeper search.
+
+const BYTES_PER_STATE: usize = 80;
+
+// Marks the state a side started from
+const ROOT: u8 = u8::MAX;
+
+#[derive(Clone)]
+pub struct BidirectionalSearch {
+    // Longest solution looked for
+    pub max_depth: usize,
+    // Bytes the two sides may use between them before giving up
+    pub memory_limit: usize,
+    // Setting it gives up on the solve
+    pub cancel: Option<Arc<AtomicBool>>,
+    // Where the search runs. It takes one thread, so a pool only says which.
+    pub threads: Threads,
+}
+
+impl Default for BidirectionalSearch {
+    fn default() -> BidirectionalSearch {
+        return BidirectionalSearch { max_depth: 12, memory_limit: 1 << 30, cancel: None, threads: Threads::default() };
+    }
+}
+
+struct Side {
+    // Whether symmetric states share one entry. Only the side starting from solved can, as
+    // solved is its own image under every symmetry.
+    reduced: bool,
+    // Index of the move that reached each state, and for a reduced side the symmetry taking
+    // that state to its key
+    seen: HashMap<StateKey, (u8, u8)>,
+    frontier: Vec<CubieCube>,
+    depth: usize,
+}
+
+impl Side {
+    fn new(start: CubieCube, reduced: bool) -> Side {
+        let mut side = Side { reduced, seen: HashMap::new(), frontier: vec![start], depth: 0 };
+        let (key, symmetry) = side.key(&start);
+        side.seen.insert(key, (ROOT, symmetry as u8));
+        return side;
+    }
+
+    fn key(&self, state: &CubieCube) -> (StateKey, usize) {
+        if self.reduced {
+            return StateKey::reduced(state);
+        }
+        return (StateKey::from_cubies(state), 0);
+    }
+
+    // A state this side has seen that is state or, for the other side to match, one of its images
+    fn meets(&self, state: &CubieCube, other: &Side) -> Option<CubieCube> {
+        if other.reduced || !self.reduced {
+            return other.seen.contains_key(&other.key(state).0).then_some(*state);
+        }
+        return symmetries().iter().map(|symmetry| symmetry.apply(state)).find(|image| other.seen.contains_key(&other.key(image).0));
+    }
+
+    // Moves from the side's start to state, in order. On a reduced side the stored move
+    // belongs to whichever image was seen first, so it is turned through the symmetries
+    // between that image and state.
+    fn path_to(&self, state: &CubieCube, moves: &[(Move, CubieMove)]) -> Vec<Move> {
+        let mut path = Vec::new();
+        let mut state = *state;
+        loop {
+            let (key, symmetry) = self.key(&state);
+            let Some(&(last, stored)) = self.seen.get(&key) else {
+                break;
+            };
+            if last == ROOT {
+                break;
+            }
+            let symmetries = symmetries();
+            let turned = symmetries[symmetry].inverse().map_move(symmetries[stored as usize].map_move(moves[last as usize].0));
+            path.push(turned);
+            let (_, undo) = moves.iter().find(|(next, _)| *next == turned.inverse()).unwrap();
+            state = state.apply(undo);
+        }
+        path.reverse();
+        return path;
+    }
+}
+
+impl BidirectionalSearch {
+    pub fn new(max_depth: usize, memory_limit: usize) -> BidirectionalSearch {
+        return BidirectionalSearch { max_depth, memory_limit, cancel: None, threads: Threads::default() };
+    }
+
+    pub fn with_cancel(self, cancel: Arc<AtomicBool>) -> BidirectionalSearch {
+        return BidirectionalSearch { cancel: Some(cancel), ..self };
+    }
+
+    pub fn with_threads(self, threads: Threads) -> BidirectionalSearch {
+        return BidirectionalSearch { threads, ..self };
+    }
+
+    // A shortest face turn solution, or why none was found
+    pub fn solve(&self, cube: &RubixCube) -> Result<Algorithm, CubeError> {
+        return self.threads.install(|| self.search(cube));
+    }
+
+    fn search(&self, cube: &RubixCube) -> Result<Algorithm, CubeError> {
+        let start = CubieCube::from_rubix(cube).ok_or(CubeError::Unsolvable(String::from("some stickers don't make real pieces")))?;
+        let solved = CubieCube::solved();
+        if start == solved {
+            return Ok(Algorithm::default());
+        }
+        let mut moves = Vec::new();
+        for layer in [Layer::U, Layer::D, Layer::L, Layer::R, Layer::F, Layer::B] {
+            for amount in 1..4 {
+                let next = Move::new(layer, amount);
+                moves.push((next, CubieMove::from_move(next)));
+            }
+        }
+        let state_limit = self.memory_limit / BYTES_PER_STATE;
+
+        let mut forward = Side::new(start, false);
+        let mut backward = Side::new(solved, true);
+        while forward.depth + backward.depth < self.max_depth {
+            let searched = forward.depth + backward.depth;
+            let forward_turn = forward.frontier.len() <= backward.frontier.len();
+            let (growing, other) = if forward_turn { (&mut forward, &backward) } else { (&mut backward, &forward) };
+            let mut next_frontier = Vec::new();
+            let mut best: Option<(usize, CubieCube)> = None;
+            for state in mem::take(&mut growing.frontier) {
+                let (last, _) = growing.seen[&growing.key(&state).0];
+                let last = if last == ROOT { None } else { Some(moves[last as usize].0) };
+                for (m, (next, table)) in moves.iter().enumerate() {
+                    if !follows(last, *next) {
+                        continue;
+                    }
+                    let moved = state.apply(table);
+                    let (key, symmetry) = growing.key(&moved);
+                    if growing.seen.contains_key(&key) {
+                        continue;
+                    }
+                    growing.seen.insert(key, (m as u8, symmetry as u8));
+                    next_frontier.push(moved);
+                    if let Some(meeting) = growing.meets(&moved, other) {
+                        let length = growing.depth + 1 + other.path_to(&meeting, &moves).len();
+                        if best.is_none_or(|(shortest, _)| length < shortest) {
+                            best = Some((length, meeting));
+                        }
+                    }
+                }
+                if self.cancel.as_ref().is_some_and(|cancel| cancel.load(Ordering::Relaxed)) {
+                    return Err(CubeError::Cancelled);
+                }
+                if growing.seen.len() + other.seen.len() > state_limit {
+                    return Err(CubeError::LimitExceeded(format!(
+                        "memory limit of {} bytes reached with no solution up to {} moves",
+                        self.memory_limit, searched
+                    )));
+                }
+            }
+            growing.frontier = next_frontier;
+            growing.depth += 1;
+            if let Some((_, meeting)) = best {
+                let mut algorithm = Algorithm::default();
+                for next in forward.path_to(&meeting, &moves) {
+                    algorithm.push(next);
+                }
+                for next in backward.path_to(&meeting, &moves).into_iter().rev() {
+                    algorithm.push(next.inverse());
+                }
+                return Ok(algorithm);
+            }
+            if growing.frontier.is_empty() {
+                break;
+            }
+        }
+        return Err(CubeError::NoSolution(self.max_depth));
+    }
+}
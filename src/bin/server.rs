@@ -0,0 +1,437 @@
+#![allow(clippy::needless_return)]
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use rubix::{Algorithm, AlgorithmLibrary, CubeError, RubixCube, Solver, Threads};
+
+// The solvers behind a small JSON over HTTP API, for other services to call:
+//
+//   GET  /health                      {"status": "ok"}
+//   GET  /scramble?moves=20&seed=7    {"scramble": [moves], "facelets": "..."}
+//   POST /apply     {"facelets"?, "algorithm"}              {"facelets": "..."}
+//   POST /validate  {"facelets"}                            {"valid": bool, "reason"?}
+//   POST /solve     {"facelets", "solver"?, "max_depth"?, "timeout_ms"?}
+//                   a solve report as in schema/rubix.schema.json
+//
+// An algorithm may be given as notation ("R U R'") or a list of moves. Errors come back as
+// {"error": "..."} with 400 for a bad request, 422 for a cube that can't be solved or has no
+// solution in max_depth, 500 for a method that got stuck, 503 when every slot stays busy
+// until the timeout or a search runs out of memory, and 504 when the solve itself runs out
+// of time.
+//
+// Searches for the shortest solution can take minutes, and Roux, ZZ and Thistlethwaite take
+// seconds the first time while they build their tables, so only --max-searches searches and
+// --max-methods method solves run at once and the rest wait for a slot. A solve that times
+// out is cancelled, and its slot comes free as soon as it stops.
+//
+// Each connection gets a thread, so at most --max-connections are served at once and any more
+// are told 503 straight away.
+
+const USAGE: &str =
+    "usage: rubix-server [--address HOST:PORT] [--max-connections N] [--max-searches N] [--max-methods N] [--search-threads N] [--timeout-ms N]";
+
+// Largest request line and headers read, together
+const MAX_HEAD: usize = 1 << 13;
+
+// Largest request body read
+const MAX_BODY: usize = 1 << 16;
+
+// Deepest search asked for; every cube solves in 20 face turns
+const MAX_DEPTH: usize = 20;
+
+// Longest a response waits, in all, for the client to finish sending and hang up
+const LINGER: Duration = Duration::from_secs(1);
+
+// Connections over --max-connections told so at once; any more are simply closed
+const MAX_REJECTING: usize = 64;
+
+struct Config {
+    address: String,
+    max_connections: usize,
+    max_searches: usize,
+    max_methods: usize,
+    search_threads: Threads,
+    // Default and longest time a solve may take, waiting for a slot included
+    timeout: Duration,
+}
+
+impl Config {
+    fn parse(args: &[String]) -> Result<Config, String> {
+        let mut config = Config {
+            address: String::from("127.0.0.1:8080"),
+            max_connections: 64,
+            max_searches: 2,
+            max_methods: 4,
+            search_threads: Threads::Global,
+            timeout: Duration::from_secs(30),
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let value = args.next().ok_or(format!("{} needs a value", arg))?;
+            let number = || value.parse::<usize>().map_err(|_| format!("{} '{}' is not a number", arg, value));
+            match arg.as_str() {
+                "--address" => config.address = value.clone(),
+                "--max-connections" => config.max_connections = number()?.max(1),
+                "--max-searches" => config.max_searches = number()?.max(1),
+                "--max-methods" => config.max_methods = number()?.max(1),
+                "--search-threads" => config.search_threads = Threads::count(number()?)?,
+                "--timeout-ms" => config.timeout = Duration::from_millis(number()? as u64),
+                _ => return Err(format!("unknown option {}", arg)),
+            }
+        }
+        return Ok(config);
+    }
+}
+
+// Counts solves of one kind running against the limit
+struct Slots {
+    used: Mutex<usize>,
+    freed: Condvar,
+    limit: usize,
+}
+
+// A taken slot, given back when dropped
+struct Slot(Arc<Slots>);
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        *self.0.used.lock().unwrap() -= 1;
+        self.0.freed.notify_one();
+    }
+}
+
+impl Slots {
+    fn new(limit: usize) -> Arc<Slots> {
+        return Arc::new(Slots { used: Mutex::new(0), freed: Condvar::new(), limit });
+    }
+
+    // Waits until deadline for a slot
+    fn take(slots: &Arc<Slots>, deadline: Instant) -> Option<Slot> {
+        let mut used = slots.used.lock().unwrap();
+        while *used >= slots.limit {
+            let left = deadline.checked_duration_since(Instant::now())?;
+            used = slots.freed.wait_timeout(used, left).unwrap().0;
+        }
+        *used += 1;
+        return Some(Slot(Arc::clone(slots)));
+    }
+}
+
+struct Server {
+    config: Config,
+    library: Arc<AlgorithmLibrary>,
+    searches: Arc<Slots>,
+    methods: Arc<Slots>,
+    connections: AtomicUsize,
+    // Connections over the limit still being told so
+    rejecting: AtomicUsize,
+}
+
+// A connection being served, counted off when dropped
+struct Connection<'a>(&'a AtomicUsize);
+
+impl Drop for Connection<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+// What to send back: a status and a JSON body
+struct Response {
+    status: u16,
+    body: Value,
+}
+
+impl Response {
+    fn ok(body: Value) -> Response {
+        return Response { status: 200, body };
+    }
+
+    fn error(status: u16, message: impl ToString) -> Response {
+        return Response { status, body: json!({ "error": message.to_string() }) };
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let config = match Config::parse(&args) {
+        Ok(config) => config,
+        Err(message) => {
+            eprintln!("error: {}\n{}", message, USAGE);
+            std::process::exit(2);
+        }
+    };
+    let listener = match TcpListener::bind(&config.address) {
+        Ok(listener) => listener,
+        Err(error) => {
+            eprintln!("cannot listen on {}: {}", config.address, error);
+            std::process::exit(1);
+        }
+    };
+    let library = match AlgorithmLibrary::built_in() {
+        Ok(library) => library,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            std::process::exit(1);
+        }
+    };
+    match listener.local_addr() {
+        Ok(address) => eprintln!("listening on {}", address),
+        Err(_) => eprintln!("listening on {}", config.address),
+    }
+    let (searches, methods) = (Slots::new(config.max_searches), Slots::new(config.max_methods));
+    let server = Arc::new(Server { config, library: Arc::new(library), searches, methods, connections: AtomicUsize::new(0), rejecting: AtomicUsize::new(0) });
+    for stream in listener.incoming() {
+        let Ok(mut stream) = stream else {
+            continue;
+        };
+        if server.connections.fetch_add(1, Ordering::Relaxed) >= server.config.max_connections {
+            server.connections.fetch_sub(1, Ordering::Relaxed);
+            // Told on a thread of its own so a slow client can't hold up the accept loop
+            if server.rejecting.fetch_add(1, Ordering::Relaxed) >= MAX_REJECTING {
+                server.rejecting.fetch_sub(1, Ordering::Relaxed);
+                continue;
+            }
+            let server = Arc::clone(&server);
+            thread::spawn(move || {
+                let _rejecting = Connection(&server.rejecting);
+                let _ = stream.set_write_timeout(Some(LINGER));
+                respond(&mut stream, Response::error(503, "too many connections"));
+            });
+            continue;
+        }
+        let server = Arc::clone(&server);
+        thread::spawn(move || server.serve(stream));
+    }
+}
+
+impl Server {
+    fn serve(&self, mut stream: TcpStream) {
+        let _connection = Connection(&self.connections);
+        let _ = stream.set_read_timeout(Some(Duration::from_secs(10)));
+        let response = match read_request(&stream) {
+            Ok(request) => self.route(&request),
+            Err(message) => Response::error(400, message),
+        };
+        respond(&mut stream, response);
+    }
+
+    fn route(&self, request: &Request) -> Response {
+        let result = match (request.method.as_str(), request.path.as_str()) {
+            ("GET", "/health") => Ok(Response::ok(json!({ "status": "ok" }))),
+            ("GET", "/scramble") => scramble(request),
+            ("POST", "/apply") => apply(request),
+            ("POST", "/validate") => validate(request),
+            ("POST", "/solve") => self.solve(request),
+            (_, "/health" | "/scramble" | "/apply" | "/validate" | "/solve") => Ok(Response::error(405, "method not allowed")),
+            _ => Ok(Response::error(404, format!("no such endpoint {}", request.path))),
+        };
+        return result.unwrap_or_else(|message| Response::error(400, message));
+    }
+
+    fn solve(&self, request: &Request) -> Result<Response, String> {
+        #[derive(Deserialize)]
+        struct SolveRequest {
+            facelets: RubixCube,
+            solver: Option<String>,
+            max_depth: Option<usize>,
+            timeout_ms: Option<u64>,
+        }
+        let body: SolveRequest = parse_body(request)?;
+        let name = body.solver.as_deref().unwrap_or("optimal");
+        let mut solver = Solver::parse(name).ok_or(format!("unknown solver '{}'; one of {}", name, Solver::NAMES.join(", ")))?;
+        match &mut solver {
+            Solver::Optimal(search) => search.max_depth = body.max_depth.unwrap_or(search.max_depth).min(MAX_DEPTH),
+            Solver::Bidirectional(search) => search.max_depth = body.max_depth.unwrap_or(search.max_depth).min(MAX_DEPTH),
+            Solver::Method { .. } => {}
+        }
+        if solver.uses_threads() {
+            solver = solver.with_threads(self.config.search_threads.clone());
+        }
+        if let Err(error) = body.facelets.check_solvable() {
+            return Ok(Response::error(422, error));
+        }
+        let timeout = body.timeout_ms.map_or(self.config.timeout, Duration::from_millis).min(self.config.timeout);
+        let deadline = Instant::now() + timeout;
+        let (slots, kind) = if solver.is_search() { (&self.searches, "search") } else { (&self.methods, "method") };
+        let Some(slot) = Slots::take(slots, deadline) else {
+            return Ok(Response::error(503, format!("every {} slot stayed busy for {:?}", kind, timeout)));
+        };
+
+        // The solve runs on its own thread holding the slot, so a timed out solve keeps it
+        // until the cancel has actually stopped it
+        let cancel = Arc::new(AtomicBool::new(false));
+        let solver = solver.with_cancel(Arc::clone(&cancel));
+        let library = Arc::clone(&self.library);
+        let cube = body.facelets;
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let _slot = slot;
+            let _ = sender.send(solver.solve(&cube, &library));
+        });
+        let left = deadline.saturating_duration_since(Instant::now());
+        return match receiver.recv_timeout(left) {
+            Ok(Ok(report)) => Ok(Response::ok(serde_json::to_value(report).map_err(|error| error.to_string())?)),
+            Ok(Err(error @ (CubeError::Unsolvable(_) | CubeError::NoSolution(_)))) => Ok(Response::error(422, error)),
+            Ok(Err(error @ CubeError::LimitExceeded(_))) => Ok(Response::error(503, error)),
+            Ok(Err(error)) => Ok(Response::error(500, error)),
+            Err(_) => {
+                cancel.store(true, Ordering::Relaxed);
+                Ok(Response::error(504, format!("no solution within {:?}", timeout)))
+            }
+        };
+    }
+}
+
+fn respond(stream: &mut TcpStream, response: Response) {
+    let body = response.body.to_string();
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        422 => "Unprocessable Entity",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        _ => "Internal Server Error",
+    };
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        reason,
+        body.len(),
+        body
+    );
+    // Closing with some of the request unread resets the connection, which can lose the
+    // response on its way, so read what is left until the client hangs up or LINGER is up
+    let _ = stream.shutdown(Shutdown::Write);
+    let deadline = Instant::now() + LINGER;
+    let mut left = MAX_HEAD + MAX_BODY;
+    let mut buffer = [0; 4096];
+    while left > 0 {
+        let wait = deadline.saturating_duration_since(Instant::now());
+        if wait.is_zero() || stream.set_read_timeout(Some(wait)).is_err() {
+            break;
+        }
+        match stream.read(&mut buffer) {
+            Ok(0) | Err(_) => break,
+            Ok(read) => left = left.saturating_sub(read),
+        }
+    }
+}
+
+fn scramble(request: &Request) -> Result<Response, String> {
+    let number = |name: &str| request.query.get(name).map(|value| value.parse::<u64>().map_err(|_| format!("{} '{}' is not a number", name, value))).transpose();
+    let moves = number("moves")?.unwrap_or(20) as usize;
+    if moves > 1000 {
+        return Err(String::from("at most 1000 moves"));
+    }
+    let mut rng = match number("seed")? {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let scramble = Algorithm::random(moves, &mut rng);
+    let mut cube = RubixCube::create_solved_rubix();
+    cube.apply_algorithm(&scramble);
+    return Ok(Response::ok(json!({ "scramble": scramble, "facelets": cube })));
+}
+
+fn apply(request: &Request) -> Result<Response, String> {
+    #[derive(Deserialize)]
+    struct ApplyRequest {
+        facelets: Option<RubixCube>,
+        algorithm: Value,
+    }
+    let body: ApplyRequest = parse_body(request)?;
+    let mut cube = body.facelets.unwrap_or_else(RubixCube::create_solved_rubix);
+    cube.apply_algorithm(&algorithm(body.algorithm)?);
+    return Ok(Response::ok(json!({ "facelets": cube })));
+}
+
+fn validate(request: &Request) -> Result<Response, String> {
+    #[derive(Deserialize)]
+    struct ValidateRequest {
+        facelets: String,
+    }
+    let body: ValidateRequest = parse_body(request)?;
+    let checked = RubixCube::from_facelets(&body.facelets).and_then(|cube| cube.check_solvable());
+    return Ok(Response::ok(match checked {
+        Ok(()) => json!({ "valid": true }),
+        Err(error) => json!({ "valid": false, "reason": error.to_string() }),
+    }));
+}
+
+// Notation in a string, or a list of moves as the schema has it
+fn algorithm(value: Value) -> Result<Algorithm, String> {
+    return match value {
+        Value::String(text) => Algorithm::parse(&text),
+        list => serde_json::from_value(list).map_err(|error| format!("bad algorithm: {}", error)),
+    };
+}
+
+fn parse_body<'a, T: Deserialize<'a>>(request: &'a Request) -> Result<T, String> {
+    return serde_json::from_slice(&request.body).map_err(|error| format!("bad request body: {}", error));
+}
+
+fn read_request(stream: &TcpStream) -> Result<Request, String> {
+    let mut reader = BufReader::new(stream);
+    // The request line and headers come through head, which stops at MAX_HEAD bytes; a line
+    // cut short by it has no newline
+    let mut head = (&mut reader).take(MAX_HEAD as u64);
+    let mut read_line = |line: &mut String| {
+        let read = head.read_line(line).map_err(|error| error.to_string())?;
+        if read > 0 && !line.ends_with('\n') && head.limit() == 0 {
+            return Err(format!("request line and headers over {} bytes", MAX_HEAD));
+        }
+        return Ok(read);
+    };
+    let mut line = String::new();
+    read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(String::from("bad request line"));
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = query.split('&').filter_map(|pair| pair.split_once('=')).map(|(name, value)| (name.to_string(), value.to_string())).collect();
+    let mut length = 0;
+    loop {
+        let mut header = String::new();
+        if read_line(&mut header)? == 0 {
+            break;
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse().map_err(|_| String::from("bad Content-Length"))?;
+            }
+        }
+    }
+    if length > MAX_BODY {
+        return Err(format!("body over {} bytes", MAX_BODY));
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).map_err(|error| error.to_string())?;
+    return Ok(Request { method: method.to_string(), path: path.to_string(), query, body });
+}
@@ -0,0 +1,335 @@
+#![allow(clippy::needless_return)]
+
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{self, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{cursor, execute, queue};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use rubix::{Algorithm, AlgorithmLibrary, ColourScheme, Keymap, NetLayout, RubixCube, Simulator, Solver, TerminalNet};
+
+// A cube to play with in the terminal: the net in colour, turned from the keyboard.
+//
+//   letters          moves, by the keymap: notation (r R for R R') or speedcubing (i k for R R')
+//   Tab              the other keymap
+//   Ctrl-Z Ctrl-Y    undo and redo; Backspace undoes too
+//   Ctrl-S           scramble and start from there
+//   Ctrl-R           start again from solved, in the --scheme colours
+//   Ctrl-N           the next solver
+//   Enter            solve from here, in the background; Esc gives up
+//   Right Left       step through the solution; Space plays it
+//   Ctrl-C Ctrl-Q    quit
+//
+// The solution stays up while the moves made follow it, so it can be stepped through, undone
+// and redone freely; any other move drops it.
+
+const USAGE: &str = "usage: rubix-tui [--keymap notation|speedcubing] [--solver NAME] [--scheme NAME] [--colours W=#ffffff,...] [--state FACELETS] [--scramble MOVES]";
+
+const HELP: [&str; 4] = [
+    "Tab keymap   Ctrl-Z/Ctrl-Y undo/redo   Ctrl-S scramble   Ctrl-R reset",
+    "Enter solve   Ctrl-N solver   Right/Left step   Space play   Esc stop",
+    "Ctrl-C quit",
+    "",
+];
+
+// Time between moves when playing a solution
+const PLAY_STEP: Duration = Duration::from_millis(400);
+
+struct Solving {
+    from: RubixCube,
+    solver: &'static str,
+    cancel: Arc<AtomicBool>,
+    result: mpsc::Receiver<Result<Algorithm, String>>,
+    started: Instant,
+}
+
+struct App {
+    simulator: Simulator,
+    keymap: Keymap,
+    // Index into Solver::NAMES
+    solver: usize,
+    library: Arc<AlgorithmLibrary>,
+    net: TerminalNet,
+    solving: Option<Solving>,
+    playing: bool,
+    last_step: Instant,
+    message: String,
+    rng: StdRng,
+}
+
+fn parse_args(args: &[String]) -> Result<App, String> {
+    let mut scheme = None;
+    let mut colours = "";
+    let mut state = None;
+    let mut scramble = None;
+    let mut keymap = Keymap::Notation;
+    let mut solver = Solver::NAMES.iter().position(|&name| name == "cfop").unwrap_or(0);
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or(format!("{} needs a value", arg))?;
+        match arg.as_str() {
+            "--scheme" => scheme = Some(ColourScheme::parse(value)?),
+            "--colours" => colours = value,
+            "--keymap" => keymap = Keymap::parse(value).ok_or(format!("unknown keymap '{}'", value))?,
+            "--solver" => {
+                solver = Solver::NAMES.iter().position(|&name| name == value.to_lowercase()).ok_or(format!("unknown solver '{}'", value))?;
+            }
+            "--state" => state = Some(value),
+            "--scramble" => scramble = Some(Algorithm::parse(value)?),
+            _ => return Err(format!("unknown option {}", arg)),
+        }
+    }
+    // The state is checked against the scheme only if one was asked for, as the CLI does
+    let checked = scheme.is_some();
+    let scheme = scheme.unwrap_or_default().with_shades(colours)?;
+    let mut cube = match state {
+        Some(facelets) if checked => scheme.from_facelets(facelets)?,
+        Some(facelets) => RubixCube::from_facelets(facelets)?,
+        None => scheme.solved(),
+    };
+    if let Some(scramble) = scramble {
+        cube.apply_algorithm(&scramble);
+    }
+    return Ok(App {
+        simulator: Simulator::new(cube).with_scheme(scheme),
+        keymap,
+        solver,
+        library: Arc::new(AlgorithmLibrary::built_in()?),
+        net: TerminalNet::for_stdout().with_layout(NetLayout::Cross).with_labels(true).with_scheme(&scheme),
+        solving: None,
+        playing: false,
+        last_step: Instant::now(),
+        message: String::new(),
+        rng: StdRng::from_entropy(),
+    });
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut app = match parse_args(&args) {
+        Ok(app) => app,
+        Err(message) => {
+            eprintln!("error: {}\n{}", message, USAGE);
+            std::process::exit(2);
+        }
+    };
+    let mut out = io::stdout();
+    let result = terminal::enable_raw_mode()
+        .and_then(|()| execute!(out, EnterAlternateScreen, cursor::Hide))
+        .and_then(|()| app.run(&mut out));
+    let _ = execute!(out, LeaveAlternateScreen, cursor::Show);
+    let _ = terminal::disable_raw_mode();
+    if let Err(error) = result {
+        eprintln!("error: {}", error);
+        std::process::exit(1);
+    }
+}
+
+impl App {
+    fn run(&mut self, out: &mut impl Write) -> io::Result<()> {
+        loop {
+            self.draw(out)?;
+            if event::poll(Duration::from_millis(100))? {
+                let carry_on = match event::read()? {
+                    Event::Key(key) if key.kind != KeyEventKind::Release => self.key(key),
+                    Event::Resize(_, _) => {
+                        queue!(out, terminal::Clear(ClearType::All))?;
+                        true
+                    }
+                    _ => true,
+                };
+                if !carry_on {
+                    return Ok(());
+                }
+            }
+            self.check_solving();
+            self.play();
+        }
+    }
+
+    // Handles a key, false to quit
+    fn key(&mut self, key: KeyEvent) -> bool {
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            match key.code {
+                KeyCode::Char('c') | KeyCode::Char('q') => return false,
+                KeyCode::Char('z') => self.undo(),
+                KeyCode::Char('y') => self.redo(),
+                KeyCode::Char('s') => {
+                    let scramble = self.simulator.scramble(20, &mut self.rng);
+                    self.playing = false;
+                    self.message = format!("scrambled: {}", scramble);
+                }
+                KeyCode::Char('r') => {
+                    self.simulator.reset(self.simulator.scheme().solved());
+                    self.playing = false;
+                    self.message = String::from("reset to solved");
+                }
+                KeyCode::Char('n') => {
+                    self.solver = (self.solver + 1) % Solver::NAMES.len();
+                    self.message = format!("solver: {}", Solver::NAMES[self.solver]);
+                }
+                _ => {}
+            }
+            return true;
+        }
+        match key.code {
+            KeyCode::Esc => {
+                if let Some(solving) = self.solving.take() {
+                    solving.cancel.store(true, Ordering::Relaxed);
+                    self.message = format!("{} cancelled", solving.solver);
+                }
+                self.playing = false;
+            }
+            KeyCode::Tab => {
+                self.keymap = self.keymap.next();
+                self.message = format!("keymap: {}", self.keymap.name());
+            }
+            KeyCode::Backspace => self.undo(),
+            KeyCode::Enter => self.solve(),
+            KeyCode::Right => {
+                self.playing = false;
+                if self.simulator.step_forward().is_none() {
+                    self.message = match self.simulator.playback() {
+                        Some(_) => String::from("that's the end of the solution"),
+                        None => String::from("no solution to step through; Enter solves"),
+                    };
+                }
+            }
+            KeyCode::Left => {
+                self.playing = false;
+                self.simulator.step_back();
+            }
+            KeyCode::Char(' ') => {
+                self.playing = !self.playing && self.simulator.playback().is_some_and(|playback| !playback.finished());
+                self.last_step = Instant::now();
+            }
+            KeyCode::Char(key) => match self.keymap.move_for(key) {
+                Some(turn) => {
+                    self.playing = false;
+                    self.simulator.turn(turn);
+                    self.message.clear();
+                }
+                None => self.message = format!("'{}' isn't a move in the {} keymap", key, self.keymap.name()),
+            },
+            _ => {}
+        }
+        return true;
+    }
+
+    fn undo(&mut self) {
+        self.playing = false;
+        if self.simulator.undo().is_none() {
+            self.message = String::from("nothing to undo");
+        }
+    }
+
+    fn redo(&mut self) {
+        self.playing = false;
+        if self.simulator.redo().is_none() {
+            self.message = String::from("nothing to redo");
+        }
+    }
+
+    fn solve(&mut self) {
+        if self.solving.is_some() {
+            return;
+        }
+        let name = Solver::NAMES[self.solver];
+        let Some(solver) = Solver::parse(name) else {
+            return;
+        };
+        let cancel = Arc::new(AtomicBool::new(false));
+        let solver = solver.with_cancel(Arc::clone(&cancel));
+        let (sender, result) = mpsc::channel();
+        let cube = *self.simulator.cube();
+        let library = Arc::clone(&self.library);
+        thread::spawn(move || {
+            let _ = sender.send(solver.solve(&cube, &library).map(|report| report.solution.algorithm()).map_err(String::from));
+        });
+        self.solving = Some(Solving { from: cube, solver: name, cancel, result, started: Instant::now() });
+        self.message = format!("solving with {}; Esc gives up", name);
+    }
+
+    fn check_solving(&mut self) {
+        let Some(solving) = &self.solving else {
+            return;
+        };
+        let outcome = match solving.result.try_recv() {
+            Ok(outcome) => outcome,
+            Err(mpsc::TryRecvError::Empty) => return,
+            Err(mpsc::TryRecvError::Disconnected) => Err(String::from("the solver stopped")),
+        };
+        let solver = solving.solver;
+        let from = solving.from;
+        let time = solving.started.elapsed();
+        self.solving = None;
+        self.message = match outcome {
+            Ok(_) if *self.simulator.cube() != from => String::from("the cube was turned while solving; Enter solves again"),
+            Ok(solution) => {
+                let message = format!("{}: {} moves in {:.2?}; Right steps, Space plays", solver, solution.moves().len(), time);
+                self.simulator.show_solution(solver, solution);
+                message
+            }
+            Err(error) => format!("{}: {}", solver, error),
+        };
+    }
+
+    fn play(&mut self) {
+        if !self.playing || self.last_step.elapsed() < PLAY_STEP {
+            return;
+        }
+        self.last_step = Instant::now();
+        if self.simulator.step_forward().is_none() || self.simulator.playback().is_none_or(|playback| playback.finished()) {
+            self.playing = false;
+        }
+    }
+
+    fn lines(&self) -> Vec<String> {
+        let mut lines = vec![format!("rubix-tui   keymap: {} (Tab)   solver: {} (Ctrl-N)", self.keymap.name(), Solver::NAMES[self.solver]), String::new()];
+        lines.extend(self.net.render(self.simulator.cube()).lines().map(String::from));
+        lines.push(String::new());
+
+        let history = self.simulator.history();
+        let shown = &history[history.len().saturating_sub(16)..];
+        let earlier = if shown.len() < history.len() { "... " } else { "" };
+        lines.push(format!("moves ({}): {}{}", history.len(), earlier, Algorithm::new(shown.to_vec())));
+
+        lines.push(match (&self.solving, self.simulator.playback()) {
+            (Some(solving), _) => format!("solving with {}... {:.1?}", solving.solver, solving.started.elapsed()),
+            (None, Some(playback)) => {
+                let moves: Vec<String> = playback
+                    .solution
+                    .moves()
+                    .iter()
+                    .enumerate()
+                    .map(|(i, turn)| if i == playback.position { format!("[{}]", turn) } else { turn.to_string() })
+                    .collect();
+                let state = if self.playing { "playing" } else { "paused" };
+                format!("solution ({}, {}/{}, {}): {}", playback.solver, playback.position, moves.len(), state, moves.join(" "))
+            }
+            (None, None) => String::from("solution: none; Enter solves from here"),
+        });
+        let solved = if self.simulator.cube().is_solved() { "solved" } else { "" };
+        lines.push(format!("{}{}{}", solved, if solved.is_empty() || self.message.is_empty() { "" } else { "   " }, self.message));
+        lines.push(String::new());
+        lines.extend(HELP.iter().map(|line| line.to_string()));
+        return lines;
+    }
+
+    fn draw(&self, out: &mut impl Write) -> io::Result<()> {
+        queue!(out, cursor::MoveTo(0, 0))?;
+        for line in self.lines() {
+            write!(out, "{}", line)?;
+            queue!(out, terminal::Clear(ClearType::UntilNewLine))?;
+            write!(out, "\r\n")?;
+        }
+        queue!(out, terminal::Clear(ClearType::FromCursorDown))?;
+        return out.flush();
+    }
+}
@@ -0,0 +1,115 @@
+use std::sync::atomic::AtomicBool;
+
+use crate::algorithm::{Algorithm, Layer};
+use crate::cubies::{self, D};
+use crate::lazy_table::LazyTable;
+use crate::library::{AlgorithmLibrary, Goal, LibraryMatch};
+use crate::method::{match_cost, StageSolver};
+use crate::piece_search::PieceSearch;
+use crate::rubix_cube::RubixCube;
+use crate::solution::Solution;
+
+// Cross, F2L, OLL, PLL. The cross is searched for and always optimal; everything after it
+// comes out of the algorithm library, so a library missing a case makes the solve fail
+// rather than fall back to something slow.
+//
+// The front right slot of frames 0 to 3 is the real FR, BR, BL and FL slot.
+
+const SLOT_NAMES: [&str; 4] = ["FR", "BR", "BL", "FL"];
+
+// Pair insertions and extractions allowed before giving up on F2L
+const F2L_STEP_LIMIT: usize = 12;
+
+fn cross_search(cancel: Option<&AtomicBool>) -> Option<&'static PieceSearch> {
+    static SEARCH: LazyTable<PieceSearch> = LazyTable::new();
+    return SEARCH.get(cancel, || {
+        let moves = PieceSearch::turns_of(&[Layer::U, Layer::D, Layer::L, Layer::R, Layer::F, Layer::B]);
+        PieceSearch::build(&[], &[4, 5, 6, 7], false, &moves, cancel)
+    });
+}
+
+fn cross(solver: &mut StageSolver) -> Option<()> {
+    let algorithm = cross_search(solver.cancel)?.solve(&solver.cube)?;
+    let explanation = format!(
+        "Solve the {} cross on D in {} moves, the fewest it can be done in.",
+        solver.colour_name(D),
+        algorithm.len()
+    );
+    solver.push_stage("Cross", &explanation, algorithm);
+    return Some(());
+}
+
+fn slot_solved(solver: &StageSolver, frame: u8) -> bool {
+    let view = solver.view(frame);
+    return cubies::corner_solved(&view, 4) && cubies::edge_solved(&view, 8);
+}
+
+// Frame of a slot holding a piece of some unsolved pair, the slot to lift pieces out of
+fn stuck_slot(solver: &StageSolver, unsolved: &[u8]) -> Option<u8> {
+    for &frame in unsolved {
+        let view = solver.view(frame);
+        let (corner, _) = cubies::find_corner(&view, 4)?;
+        let (edge, _) = cubies::find_edge(&view, 8)?;
+        // D corners DFR, DLF, DBL, DRB and middle edges FR, FL, BL, BR, as frames from this one
+        let offset = match (corner, edge) {
+            (4..=7, _) => [0, 3, 2, 1][corner - 4],
+            (_, 8..=11) => [0, 3, 2, 1][edge - 8],
+            _ => continue,
+        };
+        return Some((frame + offset) % 4);
+    }
+    return None;
+}
+
+// Pairs go in cheapest first. When no unsolved slot has a library case, a piece is stuck
+// in the wrong slot and R U R' in that slot's frame brings it up.
+fn f2l(solver: &mut StageSolver) -> Option<()> {
+    let mut extraction = Algorithm::default();
+    let mut pair = 0;
+    for _ in 0..F2L_STEP_LIMIT {
+        let unsolved: Vec<u8> = (0..4).filter(|&frame| !slot_solved(solver, frame)).collect();
+        if unsolved.is_empty() {
+            return Some(());
+        }
+        let library = solver.library;
+        let mut best: Option<(u8, LibraryMatch)> = None;
+        for &frame in &unsolved {
+            for found in library.algorithms_for_goal(&solver.view(frame), Goal::F2l) {
+                if best.as_ref().is_none_or(|(_, current)| match_cost(&found) < match_cost(current)) {
+                    best = Some((frame, found));
+                }
+            }
+        }
+        match best {
+            Some((frame, found)) => {
+                pair += 1;
+                let explanation = if extraction.is_empty() {
+                    format!("Insert {} into the {} slot.", found.entry.case, SLOT_NAMES[frame as usize])
+                } else {
+                    format!("Lift the stuck pieces out with {}, then insert {} into the {} slot.", extraction, found.entry.case, SLOT_NAMES[frame as usize])
+                };
+                let algorithm = extraction.concat(&solver.play_match(&found, frame));
+                extraction = Algorithm::default();
+                solver.record(&format!("F2L pair {}", pair), &explanation, algorithm);
+            }
+            None => {
+                let frame = stuck_slot(solver, &unsolved)?;
+                let lift = Algorithm::parse("R U R'").unwrap().after_y(frame);
+                solver.cube.apply_algorithm(&lift);
+                extraction = extraction.concat(&lift);
+            }
+        }
+    }
+    return None;
+}
+
+// Solves any valid cube by CFOP with the cross on D (F5), taking F2L, OLL and PLL algorithms
+// from library. Fails if the cube can't be solved or the library lacks a case that comes up.
+pub fn solve_cfop(cube: &RubixCube, library: &AlgorithmLibrary, cancel: Option<&AtomicBool>) -> Result<Solution, String> {
+    let mut solver = StageSolver::new(cube, library, "CFOP", cancel);
+    cross(&mut solver).ok_or("cross could not be solved")?;
+    f2l(&mut solver).ok_or("F2L could not be solved with the library's F2L algorithms")?;
+    solver.oll().ok_or("no OLL algorithm in the library for this case")?;
+    solver.pll().ok_or("no PLL algorithm in the library for this case")?;
+    return solver.finish();
+}
@@ -0,0 +1,92 @@
+use std::fs;
+use std::path::Path;
+
+use crate::rubix_cube::RubixCube;
+
+// How far a ParallelSearch has got, enough to carry on from there later. Tasks are numbered
+// in the order the split walk finds them, which is the same every time for the same cube,
+// bound and split depth, so finished subtrees can be named by number.
+//
+// Saved as plain text, one field a line:
+//
+//   rubix-search-checkpoint 1
+//   cube WWWWWWWWWRRRRRRRRR...
+//   split_depth 3
+//   max_depth 20
+//   bound 11
+//   next_bound 12
+//   nodes 1840211
+//   done 0 1 2 5 6
+
+const HEADER: &str = "rubix-search-checkpoint 1";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub cube: RubixCube,
+    pub split_depth: usize,
+    pub max_depth: usize,
+    // Bound of the iteration under way
+    pub bound: u8,
+    // Smallest estimate over the bound seen so far in it
+    pub next_bound: u8,
+    // Nodes visited before this point
+    pub nodes: u64,
+    // Tasks of this iteration searched to the end, in increasing order
+    pub done: Vec<usize>,
+}
+
+impl Checkpoint {
+    pub fn to_text(&self) -> String {
+        let done: Vec<String> = self.done.iter().map(|task| task.to_string()).collect();
+        return format!(
+            "{}\ncube {}\nsplit_depth {}\nmax_depth {}\nbound {}\nnext_bound {}\nnodes {}\ndone {}\n",
+            HEADER,
+            self.cube.facelets(),
+            self.split_depth,
+            self.max_depth,
+            self.bound,
+            self.next_bound,
+            self.nodes,
+            done.join(" ")
+        );
+    }
+
+    pub fn parse(text: &str) -> Result<Checkpoint, String> {
+        let mut lines = text.lines();
+        if lines.next().map(str::trim) != Some(HEADER) {
+            return Err(String::from("not a search checkpoint"));
+        }
+        let mut field = |name: &str| {
+            let line = lines.next().ok_or(format!("checkpoint ends before {}", name))?;
+            let value = line.strip_prefix(name).ok_or(format!("expected {} but found '{}'", name, line))?;
+            Ok::<String, String>(value.trim().to_string())
+        };
+        let number = |name: &str, value: String| value.parse::<u64>().map_err(|_| format!("{} '{}' is not a number", name, value));
+        let cube = RubixCube::from_facelets(&field("cube")?)?;
+        let split_depth = number("split_depth", field("split_depth")?)? as usize;
+        let max_depth = number("max_depth", field("max_depth")?)? as usize;
+        let bound = number("bound", field("bound")?)?.min(u8::MAX as u64) as u8;
+        let next_bound = number("next_bound", field("next_bound")?)?.min(u8::MAX as u64) as u8;
+        let nodes = number("nodes", field("nodes")?)?;
+        let mut done = Vec::new();
+        for task in field("done")?.split_whitespace() {
+            done.push(number("done", task.to_string())? as usize);
+        }
+        done.sort_unstable();
+        return Ok(Checkpoint { cube, split_depth, max_depth, bound, next_bound, nodes, done });
+    }
+
+    // Writes to a file beside path first and renames it over path, so a crash while saving
+    // leaves the previous checkpoint whole
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let mut partial = path.as_os_str().to_owned();
+        partial.push(".partial");
+        fs::write(&partial, self.to_text()).map_err(|error| format!("cannot write {}: {}", Path::new(&partial).display(), error))?;
+        return fs::rename(&partial, path).map_err(|error| format!("cannot replace {}: {}", path.display(), error));
+    }
+
+    pub fn load(path: &Path) -> Result<Checkpoint, String> {
+        let text = fs::read_to_string(path).map_err(|error| format!("cannot read {}: {}", path.display(), error))?;
+        return Checkpoint::parse(&text);
+    }
+}
@@ -0,0 +1,472 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use rubix::{
+    compare_methods, solve_batch, Algorithm, AlgorithmLibrary, Animation, CaseResult, ColourScheme, CubeError, Diagram, Method, NetLayout, Palette, RubixCube, Solver, StickerMask, TerminalNet,
+    Threads, View,
+};
+
+// The command line: a subcommand and its options, e.g.
+//
+//   RubixCube scramble --moves 20 --seed 7
+//   RubixCube solve --scramble "R U R' U'" --solver cfop
+//   RubixCube verify --state <54 letters> --solution "U R2 F"
+//
+// A cube is given as --state (the facelets letters) and/or --scramble (moves turned on it,
+// or on a solved cube). Results go to stdout, one per line, and anything else to stderr.
+
+pub const SUCCESS: i32 = 0;
+// The command ran but the answer is no: no solution found, a state that can't be solved, a
+// solution that doesn't solve
+pub const FAILURE: i32 = 1;
+// The command line or its input couldn't be read
+pub const USAGE: i32 = 2;
+
+const HELP: &str = "usage: RubixCube <command> [options]
+
+commands:
+  solve     find a solution          [cube] [--solver NAME] [--max-depth N] [--threads N] [--split-depth N] [--library FILE]
+  scramble  print a random scramble  [--moves N] [--seed N] [--scheme NAME]; the moves, then the facelets
+  apply     turn a cube              <moves> [cube]; prints the facelets
+  verify    check a state            [cube] [--solution MOVES]; fails if it can't be solved or isn't
+  show      print the net            [cube] [--colour auto|always|never] [--layout cross|column] [--labels on|off]
+            [--colours W=#ffffff,...]
+  draw      save a picture           [cube] [--output FILE.svg|FILE.png] [--view net|column|3d] [--mask all|last-layer|oll]
+            [--size N] [--colours W=#ffffff,...]; SVG to stdout without --output, PNG needs the png feature
+  animate   save a solution playing  [cube] [--solution MOVES | --solver NAME] [--output FILE.svg|FILE.gif] [--turn-frames N]
+            [--pause-ms N] [--captions on|off] and draw's options; GIF needs the gif feature
+  bench     time a solver            [--solver NAME|all] [--count N] [--moves N] [--seed N] [--library FILE]
+  batch     solve a file of cases    [--input FILE] [--output FILE] [--format jsonl|csv] [--solver NAME] [--threads N]
+            one state (facelets) or scramble a line, either bare or after facelets: or scramble:; stdin and
+            stdout by default; totals go to stderr; JSON lines, the default, need the serde feature
+  help      print this
+
+cube: --state FACELETS and/or --scramble MOVES, solved if neither is given; with --scheme, the state's
+      centres must be the scheme's and a solved cube is in its colours
+schemes: rubix (default), western, japanese, or six centre letters in the order U L F R D B, e.g. WOGRYB
+solvers: optimal (default), bidirectional, beginner, cfop, roux, zz, thistlethwaite
+
+exit codes: 0 success, 1 no solution or check failed, 2 bad command line or input";
+
+// What went wrong, and so which exit code to give
+enum Failure {
+    Usage(String),
+    Failed(String),
+}
+
+impl From<String> for Failure {
+    fn from(message: String) -> Failure {
+        return Failure::Usage(message);
+    }
+}
+
+// A cube that reads fine but can't be solved is an answer, not bad input, whichever command
+// finds it, and so is a solver coming back without a solution
+impl From<CubeError> for Failure {
+    fn from(error: CubeError) -> Failure {
+        return match error {
+            CubeError::InvalidTurn(_) | CubeError::NothingToUndo | CubeError::Facelets(_) | CubeError::Scheme(_) => Failure::Usage(error.to_string()),
+            _ => Failure::Failed(error.to_string()),
+        };
+    }
+}
+
+impl From<&str> for Failure {
+    fn from(message: &str) -> Failure {
+        return Failure::Usage(String::from(message));
+    }
+}
+
+// Options after the subcommand: --name value pairs and everything else in order
+struct Options {
+    named: HashMap<String, String>,
+    positional: Vec<String>,
+}
+
+impl Options {
+    fn parse(args: &[String]) -> Result<Options, String> {
+        let mut named = HashMap::new();
+        let mut positional = Vec::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(name) => {
+                    let value = args.next().ok_or(format!("--{} needs a value", name))?;
+                    named.insert(name.to_string(), value.clone());
+                }
+                None => positional.push(arg.clone()),
+            }
+        }
+        return Ok(Options { named, positional });
+    }
+
+    // Fails on any option not in known, so a typo isn't silently ignored
+    fn allow(&self, known: &[&str], positional: usize) -> Result<(), String> {
+        if let Some(name) = self.named.keys().find(|name| !known.contains(&name.as_str())) {
+            return Err(format!("unknown option --{}", name));
+        }
+        if self.positional.len() > positional {
+            return Err(format!("unexpected argument '{}'", self.positional[positional]));
+        }
+        return Ok(());
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        return self.named.get(name).map(String::as_str);
+    }
+
+    fn number(&self, name: &str, default: usize) -> Result<usize, String> {
+        return match self.get(name) {
+            Some(value) => value.parse().map_err(|_| format!("--{} '{}' is not a number", name, value)),
+            None => Ok(default),
+        };
+    }
+
+    // --scheme, the default if not given, with the shades in --colours
+    fn scheme(&self) -> Result<ColourScheme, String> {
+        let scheme = match self.get("scheme") {
+            Some(scheme) => ColourScheme::parse(scheme)?,
+            None => ColourScheme::default(),
+        };
+        return Ok(scheme.with_shades(self.get("colours").unwrap_or(""))?);
+    }
+
+    // A --state must have the centres of --scheme if one is given, or any six otherwise
+    fn cube(&self) -> Result<RubixCube, String> {
+        let scheme = self.scheme()?;
+        let mut cube = match (self.get("state"), self.get("scheme")) {
+            (Some(facelets), Some(_)) => scheme.from_facelets(facelets)?,
+            (Some(facelets), None) => RubixCube::from_facelets(facelets)?,
+            (None, _) => scheme.solved(),
+        };
+        if let Some(scramble) = self.get("scramble") {
+            cube.apply_algorithm(&Algorithm::parse(scramble)?);
+        }
+        return Ok(cube);
+    }
+
+    // The solver named by --solver with the search settings given. --threads goes to the
+    // solver, unless it can't spread over them and the command spreads its own work instead, as
+    // batch does.
+    fn solver(&self, command_threads: bool) -> Result<Solver, String> {
+        let name = self.get("solver").unwrap_or("optimal");
+        let mut solver = Solver::parse(name).ok_or(format!("unknown solver '{}'", name))?;
+        match &mut solver {
+            Solver::Optimal(search) => {
+                search.split_depth = self.number("split-depth", search.split_depth)?;
+                search.max_depth = self.number("max-depth", search.max_depth)?;
+            }
+            Solver::Bidirectional(search) => search.max_depth = self.number("max-depth", search.max_depth)?,
+            Solver::Method { .. } => {}
+        }
+        if solver.uses_threads() || (self.get("threads").is_some() && !command_threads) {
+            solver = solver.with_threads(self.threads()?);
+        }
+        return Ok(solver);
+    }
+
+    fn threads(&self) -> Result<Threads, String> {
+        return match self.get("threads") {
+            Some(threads) => Threads::count(threads.parse().map_err(|_| format!("--threads '{}' is not a number", threads))?),
+            None => Ok(Threads::Global),
+        };
+    }
+
+    fn library(&self) -> Result<AlgorithmLibrary, String> {
+        let mut library = AlgorithmLibrary::built_in()?;
+        if let Some(path) = self.get("library") {
+            library.load_file(Path::new(path))?;
+        }
+        return Ok(library);
+    }
+
+    fn rng(&self) -> Result<StdRng, String> {
+        return match self.get("seed") {
+            Some(seed) => Ok(StdRng::seed_from_u64(seed.parse().map_err(|_| format!("--seed '{}' is not a number", seed))?)),
+            None => Ok(StdRng::from_entropy()),
+        };
+    }
+
+    // --view, --mask, --size and --colours
+    fn diagram(&self, view: View) -> Result<Diagram, String> {
+        let view = match self.get("view") {
+            None => view,
+            Some("net") => View::Net(NetLayout::Cross),
+            Some("column") => View::Net(NetLayout::Column),
+            Some("3d") => View::Isometric,
+            Some(other) => return Err(format!("unknown --view '{}': net, column or 3d", other)),
+        };
+        let mask = self.get("mask").unwrap_or("all");
+        let mask = StickerMask::parse(mask).ok_or(format!("unknown --mask '{}': all, last-layer or oll", mask))?;
+        let palette = Palette::for_scheme(&self.scheme()?);
+        return Ok(Diagram { view, palette, mask, sticker_size: self.number("size", 30)? as u32 });
+    }
+}
+
+// Runs the command in args (without the program name) and returns the exit code
+pub fn run(args: &[String]) -> i32 {
+    let Some((command, rest)) = args.split_first() else {
+        eprintln!("{}", HELP);
+        return USAGE;
+    };
+    let result = Options::parse(rest).map_err(Failure::Usage).and_then(|options| match command.as_str() {
+        "solve" => solve(&options),
+        "scramble" => scramble(&options),
+        "apply" => apply(&options),
+        "verify" => verify(&options),
+        "show" => show(&options),
+        "draw" => draw(&options),
+        "animate" => animate(&options),
+        "bench" => bench(&options),
+        "batch" => batch(&options),
+        "help" | "--help" | "-h" => {
+            println!("{}", HELP);
+            Ok(())
+        }
+        other => Err(Failure::Usage(format!("unknown command '{}'; try help", other))),
+    });
+    return match result {
+        Ok(()) => SUCCESS,
+        Err(Failure::Failed(message)) => {
+            eprintln!("{}", message);
+            FAILURE
+        }
+        Err(Failure::Usage(message)) => {
+            eprintln!("error: {}", message);
+            USAGE
+        }
+    };
+}
+
+fn solve(options: &Options) -> Result<(), Failure> {
+    options.allow(&["state", "scramble", "scheme", "solver", "max-depth", "threads", "split-depth", "library"], 0)?;
+    let cube = options.cube()?;
+    cube.check_solvable()?;
+    let solver = options.solver(false)?;
+    let report = solver.solve(&cube, &options.library()?)?;
+    println!("{}", report.solution.algorithm());
+    if solver.is_search() {
+        match report.nodes {
+            Some(nodes) => eprintln!("{} moves, {} nodes", report.solution.move_count(), nodes),
+            None => eprintln!("{} moves", report.solution.move_count()),
+        }
+    } else {
+        eprint!("{}", report.solution);
+    }
+    eprintln!("solved in {:?}", report.time);
+    return Ok(());
+}
+
+fn scramble(options: &Options) -> Result<(), Failure> {
+    options.allow(&["moves", "seed", "scheme"], 0)?;
+    let scramble = Algorithm::random(options.number("moves", 20)?, &mut options.rng()?);
+    let mut cube = options.scheme()?.solved();
+    cube.apply_algorithm(&scramble);
+    println!("{}", scramble);
+    println!("{}", cube.facelets());
+    return Ok(());
+}
+
+fn apply(options: &Options) -> Result<(), Failure> {
+    options.allow(&["state", "scramble", "scheme"], 1)?;
+    let moves = options.positional.first().ok_or("apply needs the moves to make")?;
+    let mut cube = options.cube()?;
+    cube.apply_algorithm(&Algorithm::parse(moves)?);
+    println!("{}", cube.facelets());
+    return Ok(());
+}
+
+fn verify(options: &Options) -> Result<(), Failure> {
+    options.allow(&["state", "scramble", "scheme", "solution"], 0)?;
+    let mut cube = options.cube()?;
+    let solution = options.get("solution").map(Algorithm::parse).transpose()?;
+    cube.check_solvable().map_err(|reason| Failure::Failed(format!("invalid: {}", reason)))?;
+    let Some(solution) = solution else {
+        println!("valid");
+        return Ok(());
+    };
+    cube.apply_algorithm(&solution);
+    if !cube.is_solved() {
+        return Err(Failure::Failed(String::from("not solved")));
+    }
+    println!("solved");
+    return Ok(());
+}
+
+fn show(options: &Options) -> Result<(), Failure> {
+    options.allow(&["state", "scramble", "scheme", "colours", "colour", "layout", "labels"], 0)?;
+    let mut net = TerminalNet::for_stdout().with_scheme(&options.scheme()?);
+    net.colour = match options.get("colour").unwrap_or("auto") {
+        "auto" => net.colour,
+        "always" => true,
+        "never" => false,
+        other => return Err(Failure::from(format!("unknown --colour '{}': auto, always or never", other))),
+    };
+    net.layout = match options.get("layout").unwrap_or("cross") {
+        "cross" => NetLayout::Cross,
+        "column" => NetLayout::Column,
+        other => return Err(Failure::from(format!("unknown --layout '{}': cross or column", other))),
+    };
+    net.labels = match options.get("labels").unwrap_or("off") {
+        "on" => true,
+        "off" => false,
+        other => return Err(Failure::from(format!("unknown --labels '{}': on or off", other))),
+    };
+    net.print(&options.cube()?);
+    return Ok(());
+}
+
+fn draw(options: &Options) -> Result<(), Failure> {
+    options.allow(&["state", "scramble", "scheme", "output", "view", "mask", "size", "colours"], 0)?;
+    let diagram = options.diagram(View::Net(NetLayout::Cross))?;
+    let cube = options.cube()?;
+    let Some(path) = options.get("output") else {
+        print!("{}", diagram.svg(&cube));
+        return Ok(());
+    };
+    let bytes = match Path::new(path).extension().and_then(|extension| extension.to_str()) {
+        Some("svg") => diagram.svg(&cube).into_bytes(),
+        #[cfg(feature = "png")]
+        Some("png") => diagram.png(&cube).map_err(Failure::Failed)?,
+        #[cfg(not(feature = "png"))]
+        Some("png") => return Err(Failure::from("PNG output needs the png feature")),
+        _ => return Err(Failure::from(format!("cannot tell the format of {}: .svg or .png", path))),
+    };
+    return write_file(path, &bytes);
+}
+
+fn animate(options: &Options) -> Result<(), Failure> {
+    options.allow(
+        &[
+            "state", "scramble", "scheme", "solution", "solver", "max-depth", "threads", "split-depth", "library", "output", "view", "mask", "size", "colours", "turn-frames",
+            "pause-ms", "captions",
+        ],
+        0,
+    )?;
+    let cube = options.cube()?;
+    let solution = match options.get("solution") {
+        Some(moves) => Algorithm::parse(moves)?,
+        None => options.solver(false)?.solve(&cube, &options.library()?)?.solution.algorithm(),
+    };
+    let defaults = Animation::default();
+    let animation = Animation {
+        diagram: options.diagram(View::Isometric)?,
+        turn_frames: options.number("turn-frames", defaults.turn_frames)?,
+        pause: Duration::from_millis(options.number("pause-ms", defaults.pause.as_millis() as usize)? as u64),
+        captions: match options.get("captions").unwrap_or("on") {
+            "on" => true,
+            "off" => false,
+            other => return Err(Failure::from(format!("unknown --captions '{}': on or off", other))),
+        },
+        ..defaults
+    };
+    let Some(path) = options.get("output") else {
+        print!("{}", animation.svg(&cube, &solution));
+        return Ok(());
+    };
+    let bytes = match Path::new(path).extension().and_then(|extension| extension.to_str()) {
+        Some("svg") => animation.svg(&cube, &solution).into_bytes(),
+        #[cfg(feature = "gif")]
+        Some("gif") => animation.gif(&cube, &solution).map_err(Failure::Failed)?,
+        #[cfg(not(feature = "gif"))]
+        Some("gif") => return Err(Failure::from("GIF output needs the gif feature")),
+        _ => return Err(Failure::from(format!("cannot tell the format of {}: .svg or .gif", path))),
+    };
+    return write_file(path, &bytes);
+}
+
+fn write_file(path: &str, bytes: &[u8]) -> Result<(), Failure> {
+    return std::fs::write(path, bytes).map_err(|error| Failure::Failed(format!("cannot write {}: {}", path, error)));
+}
+
+fn bench(options: &Options) -> Result<(), Failure> {
+    options.allow(&["solver", "count", "moves", "seed", "max-depth", "threads", "split-depth", "library"], 0)?;
+    let count = options.number("count", 100)?;
+    let length = options.number("moves", 20)?;
+    let mut rng = options.rng()?;
+    let scrambles: Vec<RubixCube> = (0..count)
+        .map(|_| {
+            let mut cube = RubixCube::create_solved_rubix();
+            cube.apply_algorithm(&Algorithm::random(length, &mut rng));
+            cube
+        })
+        .collect();
+    let library = options.library()?;
+    let methods: Vec<Method> = match options.get("solver") {
+        Some("all") => Method::ALL.to_vec(),
+        _ => match options.solver(false)? {
+            Solver::Method { method, .. } => vec![method],
+            solver => return bench_search(solver, &scrambles, &library),
+        },
+    };
+    for report in compare_methods(&methods, &scrambles, &library) {
+        println!("{}", report);
+    }
+    return Ok(());
+}
+
+fn bench_search(solver: Solver, scrambles: &[RubixCube], library: &AlgorithmLibrary) -> Result<(), Failure> {
+    let (mut solved, mut failed, mut moves, mut nodes) = (0, 0, 0, 0);
+    let start = Instant::now();
+    for scramble in scrambles {
+        match solver.solve(scramble, library) {
+            Ok(report) => {
+                solved += 1;
+                moves += report.solution.move_count();
+                nodes += report.nodes.unwrap_or(0);
+            }
+            Err(_) => failed += 1,
+        }
+    }
+    let average = if solved == 0 { 0.0 } else { moves as f64 / solved as f64 };
+    println!("{}: solved {}  failed {}  moves avg {:.1}  nodes {}  time {:?}", solver.name(), solved, failed, average, nodes, start.elapsed());
+    return Ok(());
+}
+
+fn batch(options: &Options) -> Result<(), Failure> {
+    options.allow(&["input", "output", "format", "solver", "max-depth", "threads", "split-depth", "library"], 0)?;
+    let csv = match options.get("format").unwrap_or(if cfg!(feature = "serde") { "jsonl" } else { "csv" }) {
+        #[cfg(feature = "serde")]
+        "jsonl" => false,
+        #[cfg(not(feature = "serde"))]
+        "jsonl" => return Err(Failure::from("JSON lines need the serde feature")),
+        "csv" => true,
+        other => return Err(Failure::Usage(format!("unknown format '{}'; jsonl or csv", other))),
+    };
+    let input: Box<dyn BufRead> = match options.get("input") {
+        None | Some("-") => Box::new(io::stdin().lock()),
+        Some(path) => Box::new(BufReader::new(File::open(path).map_err(|error| format!("cannot open {}: {}", path, error))?)),
+    };
+    let mut output: Box<dyn Write> = match options.get("output") {
+        None | Some("-") => Box::new(BufWriter::new(io::stdout().lock())),
+        Some(path) => Box::new(BufWriter::new(File::create(path).map_err(|error| format!("cannot create {}: {}", path, error))?)),
+    };
+    let solver = options.solver(true)?;
+    let library = options.library()?;
+    if csv {
+        writeln!(output, "{}", CaseResult::CSV_HEADER).map_err(|error| error.to_string())?;
+    }
+    let row = |result: &CaseResult| {
+        #[cfg(feature = "serde")]
+        if !csv {
+            return result.json_line();
+        }
+        return result.csv_row();
+    };
+    let stats = solve_batch(input.lines(), &solver, &library, &options.threads()?, |result| {
+        writeln!(output, "{}", row(result))?;
+        return output.flush();
+    })
+    .map_err(Failure::Failed)?;
+    eprintln!("{}: {}", solver.name(), stats);
+    if stats.failed > 0 {
+        return Err(Failure::Failed(format!("{} of {} cases failed", stats.failed, stats.cases)));
+    }
+    return Ok(());
+}
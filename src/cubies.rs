@@ -0,0 +1,241 @@
+use crate::algorithm::{Algorithm, Move};
+use crate::error::CubeError;
+use crate::rubix_cube::{Colour, RubixCube};
+
+// Face indices into RubixCube::faces
+pub const U: usize = 0;
+pub const L: usize = 1;
+pub const F: usize = 2;
+pub const R: usize = 3;
+pub const D: usize = 4;
+pub const B: usize = 5;
+
+// Corner positions in the usual order, stickers listed U/D first then clockwise
+pub const CORNER_NAMES: [&str; 8] = ["URF", "UFL", "ULB", "UBR", "DFR", "DLF", "DBL", "DRB"];
+pub const CORNER_FACELETS: [[(usize, usize); 3]; 8] = [
+    [(U, 8), (R, 0), (F, 2)],
+    [(U, 6), (F, 0), (L, 2)],
+    [(U, 0), (L, 0), (B, 6)],
+    [(U, 2), (B, 8), (R, 2)],
+    [(D, 2), (F, 8), (R, 6)],
+    [(D, 0), (L, 8), (F, 6)],
+    [(D, 6), (B, 0), (L, 6)],
+    [(D, 8), (R, 8), (B, 2)],
+];
+
+pub const EDGE_NAMES: [&str; 12] = ["UR", "UF", "UL", "UB", "DR", "DF", "DL", "DB", "FR", "FL", "BL", "BR"];
+pub const EDGE_FACELETS: [[(usize, usize); 2]; 12] = [
+    [(U, 5), (R, 1)],
+    [(U, 7), (F, 1)],
+    [(U, 3), (L, 1)],
+    [(U, 1), (B, 7)],
+    [(D, 5), (R, 7)],
+    [(D, 1), (F, 7)],
+    [(D, 3), (L, 7)],
+    [(D, 7), (B, 1)],
+    [(F, 5), (R, 3)],
+    [(F, 3), (L, 5)],
+    [(B, 3), (L, 3)],
+    [(B, 5), (R, 5)],
+];
+
+// Which face each sticker belongs to, judged by the centre colours, so any colour scheme works
+pub fn face_of(faces: &[[Colour; 9]; 6], colour: Colour) -> Option<usize> {
+    return (0..6).find(|&face| faces[face][4] == colour);
+}
+
+// (piece, twist) of the corner sitting at position. The piece is its home position and
+// twist says which of the position's stickers holds the piece's U/D sticker.
+pub fn corner_at(cube: &RubixCube, position: usize) -> Option<(usize, u8)> {
+    let faces = cube.get_faces();
+    return corner_in(&faces, &faces.map(|face| face[4]), position);
+}
+
+// (piece, flip) of the edge sitting at position. flip is 1 when the piece's first sticker
+// is not on the position's first sticker.
+pub fn edge_at(cube: &RubixCube, position: usize) -> Option<(usize, u8)> {
+    let faces = cube.get_faces();
+    return edge_in(&faces, &faces.map(|face| face[4]), position);
+}
+
+// As corner_at, with the colour of each face given by scheme instead of the centres
+pub fn corner_in(faces: &[[Colour; 9]; 6], scheme: &[Colour; 6], position: usize) -> Option<(usize, u8)> {
+    let mut sticker_faces = [0; 3];
+    for (i, &(face, index)) in CORNER_FACELETS[position].iter().enumerate() {
+        sticker_faces[i] = scheme.iter().position(|&colour| colour == faces[face][index])?;
+    }
+    let twist = sticker_faces.iter().position(|&face| face == U || face == D)?;
+    for (piece, home) in CORNER_FACELETS.iter().enumerate() {
+        if (0..3).all(|i| home[i].0 == sticker_faces[(twist + i) % 3]) {
+            return Some((piece, twist as u8));
+        }
+    }
+    return None;
+}
+
+pub fn edge_in(faces: &[[Colour; 9]; 6], scheme: &[Colour; 6], position: usize) -> Option<(usize, u8)> {
+    let [(f0, i0), (f1, i1)] = EDGE_FACELETS[position];
+    let a = scheme.iter().position(|&colour| colour == faces[f0][i0])?;
+    let b = scheme.iter().position(|&colour| colour == faces[f1][i1])?;
+    for (piece, home) in EDGE_FACELETS.iter().enumerate() {
+        if home[0].0 == a && home[1].0 == b {
+            return Some((piece, 0));
+        }
+        if home[0].0 == b && home[1].0 == a {
+            return Some((piece, 1));
+        }
+    }
+    return None;
+}
+
+// Where a given piece currently is: (position, twist)
+pub fn find_corner(cube: &RubixCube, piece: usize) -> Option<(usize, u8)> {
+    for position in 0..8 {
+        if let Some((found, twist)) = corner_at(cube, position) {
+            if found == piece {
+                return Some((position, twist));
+            }
+        }
+    }
+    return None;
+}
+
+pub fn find_edge(cube: &RubixCube, piece: usize) -> Option<(usize, u8)> {
+    for position in 0..12 {
+        if let Some((found, flip)) = edge_at(cube, position) {
+            if found == piece {
+                return Some((position, flip));
+            }
+        }
+    }
+    return None;
+}
+
+pub fn corner_solved(cube: &RubixCube, position: usize) -> bool {
+    return corner_at(cube, position) == Some((position, 0));
+}
+
+pub fn edge_solved(cube: &RubixCube, position: usize) -> bool {
+    return edge_at(cube, position) == Some((position, 0));
+}
+
+// Where each piece goes under a move: piece at position i moves to .0, adding .1 to its
+// twist (mod 3) or flip (mod 2). Read off the sticker model, so any layer or rotation works;
+// positions are fixed in space rather than taken from the centres.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CubieMove {
+    pub corners: [(u8, u8); 8],
+    pub edges: [(u8, u8); 12],
+}
+
+impl CubieMove {
+    pub fn from_algorithm(algorithm: &Algorithm) -> CubieMove {
+        let solved = RubixCube::create_solved_rubix();
+        let scheme = solved.get_faces().map(|face| face[4]);
+        let mut cube = solved;
+        cube.apply_algorithm(algorithm);
+        let faces = cube.get_faces();
+        let mut corners = [(0, 0); 8];
+        for position in 0..8 {
+            let (piece, twist) = corner_in(&faces, &scheme, position).expect("solved cube pieces");
+            corners[piece] = (position as u8, twist);
+        }
+        let mut edges = [(0, 0); 12];
+        for position in 0..12 {
+            let (piece, flip) = edge_in(&faces, &scheme, position).expect("solved cube pieces");
+            edges[piece] = (position as u8, flip);
+        }
+        return CubieMove { corners, edges };
+    }
+
+    pub fn from_move(next: Move) -> CubieMove {
+        return CubieMove::from_algorithm(&Algorithm::new(vec![next]));
+    }
+}
+
+// The whole cube as pieces: corners[position] is (piece, twist) and edges[position] is
+// (piece, flip), as corner_at and edge_at read them
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CubieCube {
+    pub corners: [(u8, u8); 8],
+    pub edges: [(u8, u8); 12],
+}
+
+impl CubieCube {
+    pub fn solved() -> CubieCube {
+        return CubieCube {
+            corners: std::array::from_fn(|position| (position as u8, 0)),
+            edges: std::array::from_fn(|position| (position as u8, 0)),
+        };
+    }
+
+    // None if some sticker combination is not a real piece
+    pub fn from_rubix(cube: &RubixCube) -> Option<CubieCube> {
+        let mut cubies = CubieCube::solved();
+        for position in 0..8 {
+            let (piece, twist) = corner_at(cube, position)?;
+            cubies.corners[position] = (piece as u8, twist);
+        }
+        for position in 0..12 {
+            let (piece, flip) = edge_at(cube, position)?;
+            cubies.edges[position] = (piece as u8, flip);
+        }
+        return Some(cubies);
+    }
+
+    // Whether turning the faces could ever bring these pieces back to solved: every piece
+    // once, twists a multiple of 3, flips even and the corners and edges swapped an equal
+    // number of times
+    pub fn check_solvable(&self) -> Result<(), String> {
+        let corners: Vec<u8> = self.corners.iter().map(|&(piece, _)| piece).collect();
+        let edges: Vec<u8> = self.edges.iter().map(|&(piece, _)| piece).collect();
+        if (0..8).any(|piece| !corners.contains(&piece)) {
+            return Err(String::from("a corner appears twice"));
+        }
+        if (0..12).any(|piece| !edges.contains(&piece)) {
+            return Err(String::from("an edge appears twice"));
+        }
+        if !self.corners.iter().map(|&(_, twist)| twist as u32).sum::<u32>().is_multiple_of(3) {
+            return Err(String::from("a corner is twisted"));
+        }
+        if !self.edges.iter().map(|&(_, flip)| flip as u32).sum::<u32>().is_multiple_of(2) {
+            return Err(String::from("an edge is flipped"));
+        }
+        if parity(&corners) != parity(&edges) {
+            return Err(String::from("two pieces are swapped"));
+        }
+        return Ok(());
+    }
+
+    pub fn apply(&self, next: &CubieMove) -> CubieCube {
+        let mut moved = *self;
+        for (position, &(piece, twist)) in self.corners.iter().enumerate() {
+            let (to, change) = next.corners[position];
+            moved.corners[to as usize] = (piece, (twist + change) % 3);
+        }
+        for (position, &(piece, flip)) in self.edges.iter().enumerate() {
+            let (to, change) = next.edges[position];
+            moved.edges[to as usize] = (piece, (flip + change) % 2);
+        }
+        return moved;
+    }
+}
+
+// Whether a permutation takes an odd number of swaps
+fn parity(pieces: &[u8]) -> bool {
+    let mut odd = false;
+    for i in 0..pieces.len() {
+        for j in i + 1..pieces.len() {
+            odd ^= pieces[i] > pieces[j];
+        }
+    }
+    return odd;
+}
+
+impl RubixCube {
+    // Whether some sequence of turns solves the cube, and if not why not
+    pub fn check_solvable(&self) -> Result<(), CubeError> {
+        let cubies = CubieCube::from_rubix(self).ok_or(CubeError::Unsolvable(String::from("some stickers don't make real pieces")))?;
+        return cubies.check_solvable().map_err(CubeError::Unsolvable);
+    }
+}
@@ -0,0 +1,409 @@
+use std::fmt::Write;
+
+use crate::font;
+use crate::rubix_cube::{Colour, RubixCube};
+use crate::scheme::ColourScheme;
+use crate::terminal::NetLayout;
+
+// Pictures of a cube for documents and web pages: the unfolded net, or an isometric view of
+// the U, F and R faces, as SVG text or, with the png feature, PNG bytes. Both are drawn from
+// the same flat shapes, so they match.
+//
+// A mask greys out the stickers that don't matter, as in the usual last layer diagrams:
+// LastLayer keeps the U face and the top row of each side, for PLL, and Oll keeps only the
+// last layer stickers of the U centre's colour.
+
+pub type Rgb = (u8, u8, u8);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Palette {
+    // Indexed by Colour as usize
+    pub stickers: [Rgb; 6],
+    // Stickers the mask hides
+    pub hidden: Rgb,
+    // The plastic between the stickers
+    pub body: Rgb,
+    // Transparent if None
+    pub background: Option<Rgb>,
+}
+
+impl Default for Palette {
+    fn default() -> Palette {
+        return Palette { stickers: Colour::ALL.map(|colour| colour.rgb()), hidden: (150, 150, 150), body: (20, 20, 20), background: None };
+    }
+}
+
+impl Palette {
+    pub fn rgb(&self, colour: Colour) -> Rgb {
+        return self.stickers[colour as usize];
+    }
+
+    pub fn with(mut self, colour: Colour, rgb: Rgb) -> Palette {
+        self.stickers[colour as usize] = rgb;
+        return self;
+    }
+
+    // The default with some stickers changed, e.g. "Y=#ffff00,O=#ff8000"
+    pub fn parse(text: &str) -> Result<Palette, String> {
+        let palette = Palette::default();
+        return Ok(Palette { stickers: parse_shades(text, palette.stickers)?, ..palette });
+    }
+
+    // The default with the scheme's shades
+    pub fn for_scheme(scheme: &ColourScheme) -> Palette {
+        return Palette { stickers: scheme.shades, ..Palette::default() };
+    }
+}
+
+// shades with those in text changed, as Palette::parse reads them
+pub(crate) fn parse_shades(text: &str, mut shades: [Rgb; 6]) -> Result<[Rgb; 6], String> {
+    for entry in text.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+        let (letter, hex) = entry.split_once('=').ok_or(format!("'{}' is not LETTER=#rrggbb", entry))?;
+        let mut letters = letter.trim().chars();
+        let colour = match (letters.next(), letters.next()) {
+            (Some(letter), None) => Colour::from_letter(letter),
+            _ => None,
+        };
+        let colour = colour.ok_or(format!("'{}' is not a colour letter (W Y R G O B)", letter.trim()))?;
+        shades[colour as usize] = parse_hex(hex.trim())?;
+    }
+    return Ok(shades);
+}
+
+// #rrggbb, the # optional
+pub fn parse_hex(text: &str) -> Result<Rgb, String> {
+    let digits = text.strip_prefix('#').unwrap_or(text);
+    let channel = |at: usize| digits.get(at..at + 2).and_then(|pair| u8::from_str_radix(pair, 16).ok());
+    return match (digits.len(), channel(0), channel(2), channel(4)) {
+        (6, Some(red), Some(green), Some(blue)) => Ok((red, green, blue)),
+        _ => Err(format!("'{}' is not a colour like #ff8000", text)),
+    };
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StickerMask {
+    All,
+    LastLayer,
+    Oll,
+    // Shown stickers, indexed like faces
+    Custom([[bool; 9]; 6]),
+}
+
+impl StickerMask {
+    // all, last-layer (or pll) or oll
+    pub fn parse(name: &str) -> Option<StickerMask> {
+        return match name.to_lowercase().as_str() {
+            "all" => Some(StickerMask::All),
+            "last-layer" | "pll" => Some(StickerMask::LastLayer),
+            "oll" => Some(StickerMask::Oll),
+            _ => None,
+        };
+    }
+
+    pub fn shows(&self, cube: &RubixCube, face: usize, index: usize) -> bool {
+        let faces = cube.get_faces();
+        return match self {
+            StickerMask::All => true,
+            StickerMask::LastLayer => in_last_layer(face, index),
+            StickerMask::Oll => in_last_layer(face, index) && faces[face][index] == faces[0][4],
+            StickerMask::Custom(shown) => shown[face][index],
+        };
+    }
+}
+
+// The U face and the row of each side next to it; B is stored upside down, so its row is last
+fn in_last_layer(face: usize, index: usize) -> bool {
+    return match face {
+        0 => true,
+        1..=3 => index < 3,
+        5 => index >= 6,
+        _ => false,
+    };
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum View {
+    Net(NetLayout),
+    Isometric,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Diagram {
+    pub view: View,
+    pub palette: Palette,
+    pub mask: StickerMask,
+    // Width of a sticker in pixels, the plastic round it included
+    pub sticker_size: u32,
+}
+
+impl Default for Diagram {
+    fn default() -> Diagram {
+        return Diagram { view: View::Net(NetLayout::Cross), palette: Palette::default(), mask: StickerMask::All, sticker_size: 30 };
+    }
+}
+
+// A polygon filled with one colour
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Shape {
+    pub(crate) points: Vec<(f32, f32)>,
+    pub(crate) fill: Rgb,
+}
+
+// Shapes to draw in order over the background, in pixels from the top left, and a line of
+// text under them
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Scene {
+    pub(crate) width: f32,
+    pub(crate) height: f32,
+    pub(crate) shapes: Vec<Shape>,
+    pub(crate) caption: Option<Caption>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Caption {
+    pub(crate) text: String,
+    pub(crate) ink: Rgb,
+    // Size of a font pixel
+    pub(crate) scale: f32,
+    // Where the text's top sits
+    pub(crate) top: f32,
+}
+
+// Where each face sits in space, the cube filling 0..3 on each axis with y up and z towards
+// the front: the corner of sticker 0 and the directions its rows and columns run in
+pub(crate) const FACE_FRAMES: [([f32; 3], [f32; 3], [f32; 3]); 6] = [
+    ([0.0, 3.0, 0.0], [0.0, 0.0, 1.0], [1.0, 0.0, 0.0]),
+    ([0.0, 3.0, 0.0], [0.0, -1.0, 0.0], [0.0, 0.0, 1.0]),
+    ([0.0, 3.0, 3.0], [0.0, -1.0, 0.0], [1.0, 0.0, 0.0]),
+    ([3.0, 3.0, 3.0], [0.0, -1.0, 0.0], [0.0, 0.0, -1.0]),
+    ([0.0, 0.0, 3.0], [0.0, 0.0, -1.0], [1.0, 0.0, 0.0]),
+    ([0.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0, 0.0, 0.0]),
+];
+
+// Share of a sticker's width left as plastic on each side
+const INSET: f32 = 0.06;
+
+// A point on face at row and column, counted in stickers, which may be fractional
+pub(crate) fn face_point(face: usize, row: f32, column: f32) -> [f32; 3] {
+    let (corner, down, across) = FACE_FRAMES[face];
+    return [0, 1, 2].map(|axis| corner[axis] + row * down[axis] + column * across[axis]);
+}
+
+// Looking down at the corner between U, F and R, y up on the screen
+pub(crate) fn isometric(point: [f32; 3]) -> (f32, f32) {
+    let [x, y, z] = point;
+    return ((x - z) * 0.866_025_4, (x + z) * 0.5 - y);
+}
+
+impl Diagram {
+    pub fn with_view(self, view: View) -> Diagram {
+        return Diagram { view, ..self };
+    }
+
+    pub fn with_palette(self, palette: Palette) -> Diagram {
+        return Diagram { palette, ..self };
+    }
+
+    pub fn with_mask(self, mask: StickerMask) -> Diagram {
+        return Diagram { mask, ..self };
+    }
+
+    pub fn svg(&self, cube: &RubixCube) -> String {
+        return self.scene(cube).svg(self.palette.background);
+    }
+
+    #[cfg(feature = "png")]
+    pub fn png(&self, cube: &RubixCube) -> Result<Vec<u8>, String> {
+        return self.scene(cube).pixmap(self.palette.background)?.encode_png().map_err(|error| error.to_string());
+    }
+
+    pub(crate) fn sticker_fill(&self, cube: &RubixCube, face: usize, index: usize) -> Rgb {
+        if !self.mask.shows(cube, face, index) {
+            return self.palette.hidden;
+        }
+        return self.palette.rgb(cube.get_faces()[face][index]);
+    }
+
+    pub(crate) fn scene(&self, cube: &RubixCube) -> Scene {
+        return match self.view {
+            View::Net(layout) => self.net_scene(cube, layout),
+            View::Isometric => self.isometric_scene(cube),
+        };
+    }
+
+    fn net_scene(&self, cube: &RubixCube, layout: NetLayout) -> Scene {
+        let size = self.sticker_size as f32;
+        let margin = size / 2.0;
+        let spacing = size / 5.0;
+        let face_size = 3.0 * size;
+        let bands = layout.bands();
+        let columns = bands.iter().flatten().map(|&(_, column)| column + 1).max().unwrap_or(1) as f32;
+        let rows = bands.len() as f32;
+        let mut shapes = Vec::new();
+        for (row, band) in bands.iter().enumerate() {
+            for &(face, column) in band {
+                let left = margin + column as f32 * (face_size + spacing);
+                let top = margin + row as f32 * (face_size + spacing);
+                shapes.push(Shape { points: rectangle(left, top, face_size, face_size), fill: self.palette.body });
+                for index in 0..9 {
+                    let (down, across) = ((index / 3) as f32, (index % 3) as f32);
+                    let fill = self.sticker_fill(cube, face, layout.sticker(face, index / 3, index % 3));
+                    let points = rectangle(left + (across + INSET) * size, top + (down + INSET) * size, size * (1.0 - 2.0 * INSET), size * (1.0 - 2.0 * INSET));
+                    shapes.push(Shape { points, fill });
+                }
+            }
+        }
+        let width = 2.0 * margin + columns * face_size + (columns - 1.0) * spacing;
+        let height = 2.0 * margin + rows * face_size + (rows - 1.0) * spacing;
+        return Scene { width, height, shapes, caption: None };
+    }
+
+    fn isometric_scene(&self, cube: &RubixCube) -> Scene {
+        let size = self.sticker_size as f32;
+        let mut polygons = Vec::new();
+        // U, F and R face the viewer
+        for face in [0, 2, 3] {
+            let body = [(0.0, 0.0), (0.0, 3.0), (3.0, 3.0), (3.0, 0.0)].map(|(row, column)| face_point(face, row, column));
+            polygons.push((body.to_vec(), self.palette.body));
+            for index in 0..9 {
+                let (row, column) = ((index / 3) as f32, (index % 3) as f32);
+                let (near, far) = (INSET, 1.0 - INSET);
+                let corners = [(near, near), (near, far), (far, far), (far, near)].map(|(down, across)| face_point(face, row + down, column + across));
+                polygons.push((corners.to_vec(), self.sticker_fill(cube, face, index)));
+            }
+        }
+        return flatten(&polygons, size, false);
+    }
+}
+
+// Projects polygons in space, scaled to size pixels a sticker, onto a picture big enough for
+// the whole cube, or for any layer of it part way through a turn if turning
+pub(crate) fn flatten(polygons: &[(Vec<[f32; 3]>, Rgb)], size: f32, turning: bool) -> Scene {
+    let margin = size / 2.0;
+    let corners = [0.0, 3.0].map(|x| [0.0, 3.0].map(|y| [0.0, 3.0].map(|z| isometric([x, y, z]))));
+    let corners = corners.iter().flatten().flatten();
+    let (mut left, mut top, mut right, mut bottom) = (
+        corners.clone().map(|point| point.0).fold(f32::MAX, f32::min),
+        corners.clone().map(|point| point.1).fold(f32::MAX, f32::min),
+        corners.clone().map(|point| point.0).fold(f32::MIN, f32::max),
+        corners.map(|point| point.1).fold(f32::MIN, f32::max),
+    );
+    if turning {
+        // The sphere through the corners; the projection stretches everything by sqrt(1.5)
+        let (x, y) = isometric([1.5, 1.5, 1.5]);
+        let radius = 1.5 * 3.0_f32.sqrt() * 1.5_f32.sqrt();
+        (left, top, right, bottom) = (x - radius, y - radius, x + radius, y + radius);
+    }
+    let shapes = polygons
+        .iter()
+        .map(|(points, fill)| Shape {
+            points: points.iter().map(|&point| isometric(point)).map(|(x, y)| (margin + (x - left) * size, margin + (y - top) * size)).collect(),
+            fill: *fill,
+        })
+        .collect();
+    return Scene { width: 2.0 * margin + (right - left) * size, height: 2.0 * margin + (bottom - top) * size, shapes, caption: None };
+}
+
+fn rectangle(left: f32, top: f32, width: f32, height: f32) -> Vec<(f32, f32)> {
+    return vec![(left, top), (left + width, top), (left + width, top + height), (left, top + height)];
+}
+
+pub(crate) fn hex(rgb: Rgb) -> String {
+    return format!("#{:02x}{:02x}{:02x}", rgb.0, rgb.1, rgb.2);
+}
+
+impl Scene {
+    // Adds text centred in a strip along the bottom, sized for stickers of size pixels
+    pub(crate) fn with_caption(mut self, text: &str, ink: Rgb, size: f32) -> Scene {
+        let scale = (size / 10.0).max(1.0);
+        self.caption = Some(Caption { text: text.to_string(), ink, scale, top: self.height });
+        self.height += (font::HEIGHT as f32 + 3.0) * scale;
+        return self;
+    }
+
+    pub(crate) fn width_px(&self) -> u32 {
+        return self.width.ceil() as u32;
+    }
+
+    pub(crate) fn height_px(&self) -> u32 {
+        return self.height.ceil() as u32;
+    }
+
+    // The shapes as SVG polygons, one a line
+    pub(crate) fn svg_shapes(&self) -> String {
+        let mut text = String::new();
+        for shape in &self.shapes {
+            let points: Vec<String> = shape.points.iter().map(|(x, y)| format!("{:.1},{:.1}", x, y)).collect();
+            let _ = writeln!(text, "<polygon points=\"{}\" fill=\"{}\"/>", points.join(" "), hex(shape.fill));
+        }
+        if let Some(caption) = &self.caption {
+            let escaped = caption.text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+            let _ = writeln!(
+                text,
+                "<text x=\"{:.1}\" y=\"{:.1}\" font-family=\"monospace\" font-size=\"{:.1}\" text-anchor=\"middle\" fill=\"{}\">{}</text>",
+                self.width / 2.0,
+                caption.top + font::HEIGHT as f32 * caption.scale,
+                font::HEIGHT as f32 * caption.scale * 1.3,
+                hex(caption.ink),
+                escaped
+            );
+        }
+        return text;
+    }
+
+    pub(crate) fn svg(&self, background: Option<Rgb>) -> String {
+        let mut text = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+            self.width_px(),
+            self.height_px(),
+            self.width_px(),
+            self.height_px()
+        );
+        if let Some(background) = background {
+            let _ = writeln!(text, "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>", hex(background));
+        }
+        text.push_str(&self.svg_shapes());
+        text.push_str("</svg>\n");
+        return text;
+    }
+
+    #[cfg(feature = "png")]
+    pub(crate) fn pixmap(&self, background: Option<Rgb>) -> Result<tiny_skia::Pixmap, String> {
+        use tiny_skia::{Color, FillRule, Paint, PathBuilder, Pixmap, Transform};
+
+        let mut pixmap = Pixmap::new(self.width_px(), self.height_px()).ok_or("the picture has no area")?;
+        if let Some((red, green, blue)) = background {
+            pixmap.fill(Color::from_rgba8(red, green, blue, 255));
+        }
+        let mut paint = Paint { anti_alias: true, ..Paint::default() };
+        for shape in &self.shapes {
+            let mut path = PathBuilder::new();
+            for (i, &(x, y)) in shape.points.iter().enumerate() {
+                if i == 0 {
+                    path.move_to(x, y);
+                } else {
+                    path.line_to(x, y);
+                }
+            }
+            path.close();
+            let Some(path) = path.finish() else {
+                continue;
+            };
+            paint.set_color_rgba8(shape.fill.0, shape.fill.1, shape.fill.2, 255);
+            pixmap.fill_path(&path, &paint, FillRule::Winding, Transform::identity(), None);
+        }
+        if let Some(caption) = &self.caption {
+            paint.anti_alias = false;
+            paint.set_color_rgba8(caption.ink.0, caption.ink.1, caption.ink.2, 255);
+            let left = (self.width - font::width(&caption.text) as f32 * caption.scale) / 2.0;
+            for (column, row) in font::pixels(&caption.text) {
+                let x = (left + column as f32 * caption.scale).round();
+                let y = (caption.top + row as f32 * caption.scale).round();
+                if let Some(pixel) = tiny_skia::Rect::from_xywh(x, y, caption.scale.round(), caption.scale.round()) {
+                    pixmap.fill_rect(pixel, &paint, Transform::identity(), None);
+                }
+            }
+        }
+        return Ok(pixmap);
+    }
+}
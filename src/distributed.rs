@@ -0,0 +1,363 @@
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::algorithm::{Algorithm, Move};
+use crate::cubies::CubieCube;
+use crate::parallel_search::{first_bound, search_subtree, split_tree};
+use crate::rubix_cube::RubixCube;
+
+// The IDA* of ParallelSearch spread over worker processes, on this machine or others. The
+// coordinator cuts each iteration into subtrees the same way and hands them out over TCP one
+// at a time, so a fast worker simply asks for more. While it searches, a worker says it is
+// still there a few times every task_timeout. One that drops its connection, or goes quiet for
+// task_timeout in the middle of a subtree, is told to stop and has the subtree put back at the
+// front of the queue for someone else.
+//
+// The protocol is one line of text per message:
+//
+//   worker       coordinator
+//   hello    ->
+//            <-  job <facelets> <ms>                the cube to solve, and how often to
+//                                                   send progress while searching
+//            <-  task <id> <bound> <all 0/1> <moves> a subtree: the moves leading to it
+//   progress <id>  ->                               still searching it
+//   solution <moves>  ->                            one per solution found in it
+//   result <id> <next bound> <nodes>  ->            the subtree is finished
+//            <-  stop                               give up on the current subtree
+//            <-  done                               nothing more to do, disconnect
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Collect {
+    // Stop at the first shortest solution any worker finds
+    First,
+    // Finish the iteration and return every shortest solution
+    All,
+}
+
+pub struct Coordinator {
+    pub split_depth: usize,
+    pub max_depth: usize,
+    pub collect: Collect,
+    // How long work may wait with no worker connected before the solve gives up
+    pub idle_timeout: Duration,
+    // How long a worker may go without a message while it has a subtree before it counts as
+    // gone. Workers send progress four times as often, however long the subtree takes.
+    pub task_timeout: Duration,
+}
+
+impl Default for Coordinator {
+    fn default() -> Coordinator {
+        return Coordinator {
+            split_depth: 3,
+            max_depth: 20,
+            collect: Collect::First,
+            idle_timeout: Duration::from_secs(60),
+            task_timeout: Duration::from_secs(600),
+        };
+    }
+}
+
+// How long a new connection has to say hello
+const HELLO_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Progress lines a searching worker sends per task_timeout
+const PROGRESS_PER_TIMEOUT: u32 = 4;
+
+// One solve, as the connection threads see it
+struct Job {
+    facelets: String,
+    bound: u8,
+    all: bool,
+    tasks: Vec<Vec<Move>>,
+    queue: VecDeque<usize>,
+    // Subtrees handed out and not yet answered, with a way to write to the worker on each
+    running: Vec<(usize, TcpStream)>,
+    next_bound: u8,
+    nodes: u64,
+    solutions: Vec<Vec<Move>>,
+    workers: usize,
+    finished: bool,
+}
+
+struct Shared {
+    job: Mutex<Job>,
+    changed: Condvar,
+}
+
+impl Coordinator {
+    // Solves cube with whichever workers connect to listener, returning the first shortest
+    // solution or all of them
+    pub fn solve(&self, listener: TcpListener, cube: &RubixCube) -> Result<Vec<Algorithm>, String> {
+        return self.solve_counting(listener, cube).map(|(solutions, _)| solutions);
+    }
+
+    // As solve, also giving the nodes the workers reported over all iterations. Subtrees a
+    // worker dropped are counted once, when someone finishes them.
+    pub fn solve_counting(&self, listener: TcpListener, cube: &RubixCube) -> Result<(Vec<Algorithm>, u64), String> {
+        let start = CubieCube::from_rubix(cube).ok_or("cube has stickers that don't make real pieces")?;
+        let all = self.collect == Collect::All;
+        let shared = Arc::new(Shared {
+            job: Mutex::new(Job {
+                facelets: cube.facelets(),
+                bound: 0,
+                all,
+                tasks: Vec::new(),
+                queue: VecDeque::new(),
+                running: Vec::new(),
+                next_bound: u8::MAX,
+                nodes: 0,
+                solutions: Vec::new(),
+                workers: 0,
+                finished: false,
+            }),
+            changed: Condvar::new(),
+        });
+        listener.set_nonblocking(true).map_err(|error| error.to_string())?;
+        let accepting = Arc::clone(&shared);
+        let task_timeout = self.task_timeout;
+        thread::spawn(move || accept_workers(listener, accepting, task_timeout));
+
+        let result = self.iterate(&shared, &start, all);
+        let mut job = shared.job.lock().unwrap();
+        job.finished = true;
+        for (_, worker) in job.running.iter_mut() {
+            let _ = writeln!(worker, "stop");
+        }
+        shared.changed.notify_all();
+        return result;
+    }
+
+    fn iterate(&self, shared: &Shared, start: &CubieCube, all: bool) -> Result<(Vec<Algorithm>, u64), String> {
+        let mut bound = first_bound(start);
+        while bound as usize <= self.max_depth {
+            let (tasks, found) = split_tree(start, bound, self.split_depth, all);
+            let mut job = shared.job.lock().unwrap();
+            job.bound = bound;
+            job.queue = (0..tasks.len()).collect();
+            job.tasks = tasks;
+            job.next_bound = found.next_bound;
+            job.nodes += found.nodes;
+            job.solutions = found.solutions;
+            shared.changed.notify_all();
+            let mut idle_since = None;
+            loop {
+                if (!all && !job.solutions.is_empty()) || (job.queue.is_empty() && job.running.is_empty()) {
+                    break;
+                }
+                if job.workers == 0 {
+                    let since = *idle_since.get_or_insert_with(Instant::now);
+                    if since.elapsed() >= self.idle_timeout {
+                        return Err(format!("no workers connected for {:?} with {} subtrees left", self.idle_timeout, job.queue.len()));
+                    }
+                } else {
+                    idle_since = None;
+                }
+                job = shared.changed.wait_timeout(job, Duration::from_millis(100)).unwrap().0;
+            }
+            if !job.solutions.is_empty() {
+                return Ok((job.solutions.iter().map(|path| Algorithm::new(path.clone())).collect(), job.nodes));
+            }
+            bound = job.next_bound;
+        }
+        return Err(format!("no solution of {} moves or fewer", self.max_depth));
+    }
+}
+
+fn accept_workers(listener: TcpListener, shared: Arc<Shared>, task_timeout: Duration) {
+    while !shared.job.lock().unwrap().finished {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let serving = Arc::clone(&shared);
+                thread::spawn(move || serve_worker(stream, serving, task_timeout));
+            }
+            Err(_) => thread::sleep(Duration::from_millis(20)),
+        }
+    }
+    // Workers that connected while the last subtrees were searched are told there is
+    // nothing left rather than cut off
+    while let Ok((stream, _)) = listener.accept() {
+        let serving = Arc::clone(&shared);
+        thread::spawn(move || serve_worker(stream, serving, task_timeout));
+    }
+}
+
+fn serve_worker(stream: TcpStream, shared: Arc<Shared>, task_timeout: Duration) {
+    // Messages are single short lines, timed against task_timeout, so none may wait to be
+    // sent with the next
+    if stream.set_nonblocking(false).is_err() || stream.set_nodelay(true).is_err() || stream.set_read_timeout(Some(HELLO_TIMEOUT)).is_err() {
+        return;
+    }
+    let Ok(reading) = stream.try_clone() else {
+        return;
+    };
+    let mut reader = BufReader::new(reading);
+    let mut writer = stream;
+    let mut line = String::new();
+    if reader.read_line(&mut line).is_err() || line.trim() != "hello" {
+        return;
+    }
+    let facelets = shared.job.lock().unwrap().facelets.clone();
+    let every = (task_timeout / PROGRESS_PER_TIMEOUT).as_millis().max(1);
+    if writeln!(writer, "job {} {}", facelets, every).is_err() || writer.set_read_timeout(Some(task_timeout)).is_err() {
+        return;
+    }
+    shared.job.lock().unwrap().workers += 1;
+    shared.changed.notify_all();
+    loop {
+        let (index, message) = {
+            let mut job = shared.job.lock().unwrap();
+            loop {
+                if job.finished {
+                    job.workers -= 1;
+                    drop(job);
+                    let _ = writeln!(writer, "done");
+                    return;
+                }
+                if let Some(index) = job.queue.pop_front() {
+                    let Ok(handle) = writer.try_clone() else {
+                        job.queue.push_front(index);
+                        job.workers -= 1;
+                        return;
+                    };
+                    job.running.push((index, handle));
+                    let message = format!("task {} {} {} {}", index, job.bound, job.all as u8, Algorithm::new(job.tasks[index].clone()));
+                    break (index, message);
+                }
+                job = shared.changed.wait(job).unwrap();
+            }
+        };
+        let outcome = writeln!(writer, "{}", message).map_err(|error| error.to_string()).and_then(|_| read_result(&mut reader, index));
+        let mut job = shared.job.lock().unwrap();
+        job.running.retain(|(running, _)| *running != index);
+        match outcome {
+            Ok((solutions, next_bound, nodes)) => {
+                job.next_bound = job.next_bound.min(next_bound);
+                job.nodes += nodes;
+                job.solutions.extend(solutions);
+                shared.changed.notify_all();
+            }
+            Err(_) => {
+                // The worker is gone, confused or silent; someone else gets its subtree and
+                // the connection is dropped, with a stop in case it is still searching
+                if !job.finished {
+                    job.queue.push_front(index);
+                }
+                job.workers -= 1;
+                shared.changed.notify_all();
+                drop(job);
+                let _ = writeln!(writer, "stop");
+                return;
+            }
+        }
+    }
+}
+
+// A worker's answer for one subtree: its solutions, next bound and nodes. Each line read,
+// progress included, starts the read timeout again.
+fn read_result(reader: &mut impl BufRead, index: usize) -> Result<(Vec<Vec<Move>>, u8, u64), String> {
+    let mut solutions = Vec::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).map_err(|error| error.to_string())? == 0 {
+            return Err(String::from("worker disconnected"));
+        }
+        let line = line.trim_end();
+        if let Some(moves) = line.strip_prefix("solution") {
+            solutions.push(Algorithm::parse(moves)?.moves().to_vec());
+            continue;
+        }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if let ["progress", id] = fields[..] {
+            if id.parse() != Ok(index) {
+                return Err(format!("progress on subtree {} while waiting for {}", id, index));
+            }
+            continue;
+        }
+        if let ["result", id, next_bound, nodes] = fields[..] {
+            if id.parse() != Ok(index) {
+                return Err(format!("result for subtree {} while waiting for {}", id, index));
+            }
+            let next_bound = next_bound.parse().map_err(|_| format!("bad next bound '{}'", next_bound))?;
+            let nodes = nodes.parse().map_err(|_| format!("bad node count '{}'", nodes))?;
+            return Ok((solutions, next_bound, nodes));
+        }
+        return Err(format!("unexpected message '{}'", line));
+    }
+}
+
+// Connects to a coordinator and searches the subtrees it sends until it says it is done,
+// returning how many were searched
+pub fn run_worker(address: impl ToSocketAddrs) -> Result<usize, String> {
+    let stream = TcpStream::connect(address).map_err(|error| format!("cannot connect: {}", error))?;
+    // Progress lines are tiny and mustn't wait on an acknowledgement to be sent
+    stream.set_nodelay(true).map_err(|error| error.to_string())?;
+    let mut writer = stream.try_clone().map_err(|error| error.to_string())?;
+    writeln!(writer, "hello").map_err(|error| error.to_string())?;
+    // Shared with the thread sending progress, so lines never interleave
+    let writer = Mutex::new(writer);
+
+    // A reader thread so a stop can arrive while a subtree is being searched
+    let stop = Arc::new(AtomicBool::new(false));
+    let (messages, received) = mpsc::channel();
+    let stopping = Arc::clone(&stop);
+    thread::spawn(move || {
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if line.trim() == "stop" {
+                stopping.store(true, Ordering::Relaxed);
+            } else if messages.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut start = None;
+    let mut every = Duration::MAX;
+    let mut searched = 0;
+    loop {
+        let line = received.recv().map_err(|_| String::from("coordinator disconnected"))?;
+        let fields: Vec<&str> = line.splitn(5, ' ').collect();
+        match fields[..] {
+            ["job", facelets, ms] => {
+                let cube = RubixCube::from_facelets(facelets)?;
+                start = Some(CubieCube::from_rubix(&cube).ok_or("coordinator sent a cube that can't be read as pieces")?);
+                every = Duration::from_millis(ms.parse().map_err(|_| format!("bad progress interval '{}'", ms))?);
+            }
+            ["task", id, bound, all, ref moves @ ..] => {
+                let start = start.as_ref().ok_or("task before job")?;
+                let bound = bound.parse().map_err(|_| format!("bad bound '{}'", bound))?;
+                let prefix = Algorithm::parse(moves.first().copied().unwrap_or(""))?;
+                stop.store(false, Ordering::Relaxed);
+                let (finished, finishing) = mpsc::channel::<()>();
+                let subtree = thread::scope(|scope| {
+                    let writer = &writer;
+                    scope.spawn(move || {
+                        while finishing.recv_timeout(every) == Err(mpsc::RecvTimeoutError::Timeout) {
+                            if writeln!(writer.lock().unwrap(), "progress {}", id).is_err() {
+                                break;
+                            }
+                        }
+                    });
+                    let subtree = search_subtree(start, prefix.moves(), bound, all == "1", &stop);
+                    drop(finished);
+                    subtree
+                });
+                let mut writer = writer.lock().unwrap();
+                for solution in subtree.solutions {
+                    writeln!(writer, "solution {}", Algorithm::new(solution)).map_err(|error| error.to_string())?;
+                }
+                writeln!(writer, "result {} {} {}", id, subtree.next_bound, subtree.nodes).map_err(|error| error.to_string())?;
+                searched += 1;
+            }
+            ["done"] => return Ok(searched),
+            _ => return Err(format!("unexpected message '{}'", line)),
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use std::fmt;
+
+// What the fallible cube operations can go wrong with. Converts into the String errors the
+// solvers use, so ? works in either direction of the crate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CubeError {
+    // A turn code outside 0..18
+    InvalidTurn(u8),
+    // undo with an empty history
+    NothingToUndo,
+    // Facelets text that doesn't describe 54 stickers, nine of each colour
+    Facelets(String),
+    // Stickers no sequence of turns could solve, and why
+    Unsolvable(String),
+    // A search ran out of depth without solving: nothing within this many moves
+    NoSolution(usize),
+    // A search that hit a limit other than its depth, such as memory, and why
+    LimitExceeded(String),
+    // A search given up on through its cancel flag
+    Cancelled,
+    // A checkpoint file that couldn't be read or written
+    Checkpoint(String),
+    // A method stage that got stuck on a cube that can be solved
+    MethodFailed(String),
+    // A colour scheme with a colour missing or used twice, or a cube whose centres don't fit one
+    Scheme(String),
+}
+
+impl fmt::Display for CubeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CubeError::InvalidTurn(code) => write!(f, "turn code {} is not one of 0 to 17", code),
+            CubeError::NothingToUndo => write!(f, "no turns to undo"),
+            CubeError::Facelets(reason) => write!(f, "bad facelets: {}", reason),
+            CubeError::Unsolvable(reason) => write!(f, "cube can't be solved: {}", reason),
+            CubeError::NoSolution(depth) => write!(f, "no solution of {} moves or fewer", depth),
+            CubeError::LimitExceeded(reason) => write!(f, "{}", reason),
+            CubeError::Cancelled => write!(f, "search cancelled"),
+            CubeError::Checkpoint(reason) => write!(f, "checkpoint: {}", reason),
+            CubeError::MethodFailed(reason) => write!(f, "{}", reason),
+            CubeError::Scheme(reason) => write!(f, "colour scheme: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for CubeError {}
+
+impl From<CubeError> for String {
+    fn from(error: CubeError) -> String {
+        return error.to_string();
+    }
+}
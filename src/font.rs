@@ -0,0 +1,73 @@
+// A 5x7 pixel font with just what move captions need: digits, the letters of the notation,
+// ' / and space. Each glyph is seven rows, top first, the leftmost pixel in bit 4; other
+// characters draw as a box so they are at least seen to be missing. Only rasterising needs
+// the glyphs; SVG captions are text, sized to match.
+
+#[cfg(feature = "png")]
+pub(crate) const WIDTH: usize = 5;
+pub(crate) const HEIGHT: usize = 7;
+
+#[cfg(feature = "png")]
+const UNKNOWN: [u8; 7] = [0b11111, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b11111];
+
+#[cfg(feature = "png")]
+pub(crate) fn glyph(c: char) -> [u8; 7] {
+    return match c {
+        ' ' => [0; 7],
+        '\'' => [0b00100, 0b00100, 0b01000, 0, 0, 0, 0],
+        '/' => [0b00001, 0b00010, 0b00010, 0b00100, 0b01000, 0b01000, 0b10000],
+        '0' => [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110],
+        '1' => [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        '2' => [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111],
+        '3' => [0b11110, 0b00001, 0b00001, 0b01110, 0b00001, 0b00001, 0b11110],
+        '4' => [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010],
+        '5' => [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110],
+        '6' => [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110],
+        '7' => [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000],
+        '8' => [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110],
+        '9' => [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100],
+        'B' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110],
+        'D' => [0b11100, 0b10010, 0b10001, 0b10001, 0b10001, 0b10010, 0b11100],
+        'E' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111],
+        'F' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000],
+        'L' => [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111],
+        'M' => [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001],
+        'R' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001],
+        'S' => [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110],
+        'U' => [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+        'b' => [0b10000, 0b10000, 0b10110, 0b11001, 0b10001, 0b10001, 0b11110],
+        'd' => [0b00001, 0b00001, 0b01101, 0b10011, 0b10001, 0b10001, 0b01111],
+        'f' => [0b00110, 0b01001, 0b01000, 0b11100, 0b01000, 0b01000, 0b01000],
+        'l' => [0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        'r' => [0, 0, 0b10110, 0b11001, 0b10000, 0b10000, 0b10000],
+        'u' => [0, 0, 0b10001, 0b10001, 0b10001, 0b10011, 0b01101],
+        'w' => [0, 0, 0b10001, 0b10001, 0b10101, 0b10101, 0b01010],
+        'x' => [0, 0, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001],
+        'y' => [0, 0, 0b10001, 0b10001, 0b01111, 0b00001, 0b01110],
+        'z' => [0, 0, 0b11111, 0b00010, 0b00100, 0b01000, 0b11111],
+        _ => UNKNOWN,
+    };
+}
+
+// The lit pixels of text, as column and row from its top left, a pixel's gap between letters
+#[cfg(feature = "png")]
+pub(crate) fn pixels(text: &str) -> Vec<(usize, usize)> {
+    let mut lit = Vec::new();
+    for (position, c) in text.chars().enumerate() {
+        let rows = glyph(c);
+        for (row, bits) in rows.iter().enumerate() {
+            for column in 0..WIDTH {
+                if bits & (1 << (WIDTH - 1 - column)) != 0 {
+                    lit.push((position * (WIDTH + 1) + column, row));
+                }
+            }
+        }
+    }
+    return lit;
+}
+
+// Width of text in font pixels
+#[cfg(feature = "png")]
+pub(crate) fn width(text: &str) -> usize {
+    return (text.chars().count() * (WIDTH + 1)).saturating_sub(1);
+}
@@ -0,0 +1,44 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+// A table the method solvers build the first time a solve needs it and share from then on.
+// Some take seconds to build, so one solve builds while the rest wait, and a solve cancelled
+// part way through stops building and leaves the table for the next solve to start again.
+
+pub(crate) struct LazyTable<T> {
+    table: OnceLock<T>,
+    building: Mutex<()>,
+}
+
+impl<T> LazyTable<T> {
+    pub const fn new() -> LazyTable<T> {
+        return LazyTable { table: OnceLock::new(), building: Mutex::new(()) };
+    }
+
+    // The table, built with build if no one has yet. None if cancel is set first, or if
+    // build gives up because of it.
+    pub fn get(&self, cancel: Option<&AtomicBool>, build: impl FnOnce() -> Option<T>) -> Option<&T> {
+        loop {
+            if let Some(table) = self.table.get() {
+                return Some(table);
+            }
+            if cancelled(cancel) {
+                return None;
+            }
+            if let Ok(_building) = self.building.try_lock() {
+                if let Some(table) = self.table.get() {
+                    return Some(table);
+                }
+                let table = build()?;
+                return Some(self.table.get_or_init(|| table));
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+}
+
+pub(crate) fn cancelled(cancel: Option<&AtomicBool>) -> bool {
+    return cancel.is_some_and(|cancel| cancel.load(Ordering::Relaxed));
+}
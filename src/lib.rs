@@ -0,0 +1,75 @@
+#![allow(clippy::needless_return)]
+
+// A 3x3 cube as stickers, the usual move notation on top of it, and solvers from a
+// beginner's layer by layer method to shortest-solution searches across threads or machines.
+//
+//   let mut cube = RubixCube::create_solved_rubix();
+//   cube.apply_algorithm(&Algorithm::parse("R U R' U'")?);
+//   let solution = ParallelSearch::default().solve(&cube)?;
+//
+// Everything a user needs is re-exported here; the modules behind it are private.
+
+mod algorithm;
+mod animation;
+mod batch;
+mod beginner;
+mod bidirectional;
+mod cfop;
+mod checkpoint;
+mod cubies;
+mod diagram;
+mod distributed;
+mod error;
+mod font;
+mod lazy_table;
+mod library;
+mod method;
+mod parallel_search;
+mod piece_search;
+mod recognition;
+mod roux;
+mod rubix_cube;
+mod scheme;
+#[cfg(feature = "serde")]
+mod serialization;
+mod simulator;
+mod solution;
+mod solver;
+mod state_key;
+mod symmetry;
+mod terminal;
+mod thistlethwaite;
+mod threads;
+mod zz;
+
+// The cube and its moves
+pub use algorithm::{Algorithm, Layer, Move};
+pub use error::CubeError;
+pub use rubix_cube::{Colour, History, Iddfs, RubixCube};
+pub use scheme::ColourScheme;
+
+// Drawing it
+pub use animation::Animation;
+pub use diagram::{parse_hex, Diagram, Palette, Rgb, StickerMask, View};
+pub use terminal::{NetLayout, TerminalNet};
+
+// Solving by method, stage by stage
+pub use library::{AlgorithmLibrary, AlgorithmSet, Goal, LibraryEntry, LibraryMatch};
+pub use method::{compare_methods, Method, MethodReport};
+pub use recognition::{recognise_f2l, recognise_last_layer, recognise_oll, recognise_pll, CaseSet, Recognition, Slot, OLL_CASES, PLL_CASES};
+pub use solution::{SolveReport, Solution, Stage};
+pub use thistlethwaite::subgroup;
+
+// Searching for the shortest solution
+pub use bidirectional::BidirectionalSearch;
+pub use checkpoint::Checkpoint;
+pub use distributed::{run_worker, Collect, Coordinator};
+pub use parallel_search::{scaling_benchmark, ParallelSearch, ScalingReport};
+pub use state_key::{StateKey, TranspositionTable};
+pub use symmetry::{symmetries, Symmetry};
+pub use threads::Threads;
+
+// Any of them, picked by name, over a file of cases or by hand
+pub use batch::{parse_case, solve_batch, BatchStats, CaseResult};
+pub use simulator::{Keymap, Playback, Simulator};
+pub use solver::Solver;
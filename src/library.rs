@@ -0,0 +1,347 @@
+use std::fs;
+use std::path::Path;
+
+use crate::algorithm::{Algorithm, Layer, Move};
+use crate::cubies;
+use crate::recognition::{self, Slot};
+use crate::rubix_cube::RubixCube;
+
+// Algorithm sets are plain text:
+//
+//   # comment
+//   set: OLL
+//   goal: oll
+//   OLL 27: R U R' U R U2 R'
+//
+// "set" starts (or extends) a set, "goal" says what its algorithms achieve and every other line
+// is "case: algorithm". Each algorithm is checked when it is loaded: undoing it from a solved cube
+// has to give a state its goal starts from, and for F2L, OLL and PLL that state has to be
+// recognised as the case it is listed under. COLL, ZBLL and LL cases have no standard names, so
+// their sets name them: every algorithm under a name has to solve the same case, and no two
+// names the same one.
+
+const BUILT_IN: [(&str, &str); 5] = [
+    ("f2l.txt", include_str!("../algorithms/f2l.txt")),
+    ("oll.txt", include_str!("../algorithms/oll.txt")),
+    ("pll.txt", include_str!("../algorithms/pll.txt")),
+    ("coll.txt", include_str!("../algorithms/coll.txt")),
+    ("zbll.txt", include_str!("../algorithms/zbll.txt")),
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Goal {
+    // Inserts the front right pair over a solved cross and other slots
+    F2l,
+    // Orients the last layer
+    Oll,
+    // Solves the last layer corners, keeping the edges oriented
+    Coll,
+    // Permutes an oriented last layer
+    Pll,
+    // Solves a last layer whose edges are oriented
+    Zbll,
+    // Solves any last layer
+    Ll,
+}
+
+impl Goal {
+    pub fn parse(text: &str) -> Option<Goal> {
+        return match text {
+            "f2l" => Some(Goal::F2l),
+            "oll" => Some(Goal::Oll),
+            "coll" => Some(Goal::Coll),
+            "pll" => Some(Goal::Pll),
+            "zbll" => Some(Goal::Zbll),
+            "ll" => Some(Goal::Ll),
+            _ => None,
+        };
+    }
+
+    // Whether a case state is one this goal starts from
+    fn accepts(&self, cube: &RubixCube) -> bool {
+        return match self {
+            Goal::F2l => {
+                recognition::cross_solved(cube)
+                    && (5..8).all(|corner| cubies::corner_solved(cube, corner))
+                    && (9..12).all(|edge| cubies::edge_solved(cube, edge))
+            }
+            Goal::Oll | Goal::Ll => recognition::f2l_solved(cube),
+            Goal::Coll | Goal::Zbll => recognition::f2l_solved(cube) && last_layer_edges_oriented(cube),
+            Goal::Pll => recognition::f2l_solved(cube) && recognition::last_layer_oriented(cube),
+        };
+    }
+
+    // Whether a state is worth trying this goal's algorithms on
+    fn applies_to(&self, cube: &RubixCube) -> bool {
+        return match self {
+            Goal::F2l => recognition::cross_solved(cube),
+            _ => self.accepts(cube),
+        };
+    }
+
+    // Post AUF needed once the goal is reached, None if it isn't
+    fn reached(&self, cube: &RubixCube) -> Option<u8> {
+        return match self {
+            Goal::F2l => {
+                if recognition::cross_solved(cube) && cubies::corner_solved(cube, 4) && cubies::edge_solved(cube, 8) {
+                    Some(0)
+                } else {
+                    None
+                }
+            }
+            Goal::Oll => {
+                if recognition::f2l_solved(cube) && recognition::last_layer_oriented(cube) {
+                    Some(0)
+                } else {
+                    None
+                }
+            }
+            Goal::Coll => {
+                if !recognition::f2l_solved(cube) || !last_layer_edges_oriented(cube) {
+                    return None;
+                }
+                aligning_auf(cube, |aligned| (0..4).all(|corner| cubies::corner_solved(aligned, corner)))
+            }
+            Goal::Pll | Goal::Zbll | Goal::Ll => {
+                if !recognition::f2l_solved(cube) {
+                    return None;
+                }
+                aligning_auf(cube, recognition::last_layer_solved)
+            }
+        };
+    }
+}
+
+fn last_layer_edges_oriented(cube: &RubixCube) -> bool {
+    let faces = cube.get_faces();
+    return [1, 3, 5, 7].iter().all(|&i| faces[cubies::U][i] == faces[cubies::U][4]);
+}
+
+// U turns after which solved holds, if any
+pub fn aligning_auf(cube: &RubixCube, solved: impl Fn(&RubixCube) -> bool) -> Option<u8> {
+    let mut aligned = *cube;
+    for post_auf in 0..4 {
+        if solved(&aligned) {
+            return Some(post_auf);
+        }
+        aligned.turn_cube(&3);
+    }
+    return None;
+}
+
+#[derive(Clone, Debug)]
+pub struct LibraryEntry {
+    pub set: String,
+    pub case: String,
+    pub algorithm: Algorithm,
+}
+
+#[derive(Clone, Debug)]
+pub struct AlgorithmSet {
+    pub name: String,
+    pub goal: Goal,
+    pub entries: Vec<LibraryEntry>,
+}
+
+// An algorithm that works on a given state, after pre_auf U turns and followed by post_auf U turns
+#[derive(Clone, Debug)]
+pub struct LibraryMatch<'a> {
+    pub entry: &'a LibraryEntry,
+    pub pre_auf: u8,
+    pub post_auf: u8,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct AlgorithmLibrary {
+    sets: Vec<AlgorithmSet>,
+}
+
+impl AlgorithmLibrary {
+    pub fn new() -> AlgorithmLibrary {
+        return AlgorithmLibrary { sets: Vec::new() };
+    }
+
+    // F2L, OLL, PLL, and the Sune subsets of COLL and ZBLL
+    pub fn built_in() -> Result<AlgorithmLibrary, String> {
+        let mut library = AlgorithmLibrary::new();
+        for (name, text) in BUILT_IN {
+            library.load_str(text).map_err(|message| format!("built in algorithms {}: {}", name, message))?;
+        }
+        return Ok(library);
+    }
+
+    pub fn load_file(&mut self, path: &Path) -> Result<(), String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        return self.load_str(&text).map_err(|e| format!("{}: {}", path.display(), e));
+    }
+
+    // Adds the sets in text, appending to sets already loaded under the same name
+    pub fn load_str(&mut self, text: &str) -> Result<(), String> {
+        let mut current: Option<usize> = None;
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once(':')
+                .map(|(key, value)| (key.trim(), value.trim()))
+                .ok_or(format!("line {}: expected 'case: algorithm'", number + 1))?;
+            match key {
+                "set" => {
+                    current = Some(match self.sets.iter().position(|set| set.name == value) {
+                        Some(index) => index,
+                        None => {
+                            self.sets.push(AlgorithmSet { name: String::from(value), goal: Goal::Ll, entries: Vec::new() });
+                            self.sets.len() - 1
+                        }
+                    });
+                }
+                "goal" => {
+                    let index = current.ok_or(format!("line {}: goal before set", number + 1))?;
+                    let goal = Goal::parse(value).ok_or(format!("line {}: unknown goal '{}'", number + 1, value))?;
+                    let set = &mut self.sets[index];
+                    if !set.entries.is_empty() && set.goal != goal {
+                        return Err(format!("line {}: set {} already has a different goal", number + 1, set.name));
+                    }
+                    set.goal = goal;
+                }
+                case => {
+                    let index = current.ok_or(format!("line {}: algorithm before set", number + 1))?;
+                    let algorithm = Algorithm::parse(value).map_err(|e| format!("line {}: {}", number + 1, e))?;
+                    let set = &mut self.sets[index];
+                    verify(set, case, &algorithm).map_err(|e| format!("line {}: {}", number + 1, e))?;
+                    set.entries.push(LibraryEntry { set: set.name.clone(), case: String::from(case), algorithm });
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    pub fn sets(&self) -> &[AlgorithmSet] {
+        return &self.sets;
+    }
+
+    pub fn set(&self, name: &str) -> Option<&AlgorithmSet> {
+        return self.sets.iter().find(|set| set.name == name);
+    }
+
+    pub fn algorithms_for_case(&self, set: &str, case: &str) -> Vec<&LibraryEntry> {
+        return match self.set(set) {
+            Some(set) => set.entries.iter().filter(|entry| entry.case == case).collect(),
+            None => Vec::new(),
+        };
+    }
+
+    // Every algorithm in the library that reaches its goal from this state
+    pub fn algorithms_for_state(&self, cube: &RubixCube) -> Vec<LibraryMatch<'_>> {
+        return self.matches(cube, |_| true);
+    }
+
+    // As algorithms_for_state, only from sets with the given goal
+    pub fn algorithms_for_goal(&self, cube: &RubixCube, goal: Goal) -> Vec<LibraryMatch<'_>> {
+        return self.matches(cube, |set| set.goal == goal);
+    }
+
+    // Algorithms from sets with the given goal put to another use: those that take this state
+    // to one where reached gives the post AUF. Only the algorithms' effect on the last layer
+    // is relied on, so the cube need not be one the goal starts from.
+    pub fn algorithms_reaching(
+        &self,
+        goal: Goal,
+        cube: &RubixCube,
+        reached: impl Fn(&RubixCube) -> Option<u8>,
+    ) -> Vec<LibraryMatch<'_>> {
+        let mut matches = Vec::new();
+        for set in self.sets.iter().filter(|set| set.goal == goal) {
+            for entry in &set.entries {
+                if let Some((pre_auf, post_auf)) = try_entry(cube, &entry.algorithm, &reached) {
+                    matches.push(LibraryMatch { entry, pre_auf, post_auf });
+                }
+            }
+        }
+        return matches;
+    }
+
+    fn matches(&self, cube: &RubixCube, wanted: impl Fn(&AlgorithmSet) -> bool) -> Vec<LibraryMatch<'_>> {
+        let mut matches = Vec::new();
+        for set in &self.sets {
+            if !wanted(set) || !set.goal.applies_to(cube) {
+                continue;
+            }
+            for entry in &set.entries {
+                if let Some((pre_auf, post_auf)) = try_entry(cube, &entry.algorithm, |attempt| set.goal.reached(attempt)) {
+                    matches.push(LibraryMatch { entry, pre_auf, post_auf });
+                }
+            }
+        }
+        return matches;
+    }
+}
+
+fn try_entry(cube: &RubixCube, algorithm: &Algorithm, reached: impl Fn(&RubixCube) -> Option<u8>) -> Option<(u8, u8)> {
+    let mut start = *cube;
+    for pre_auf in 0..4 {
+        let mut attempt = start;
+        attempt.apply_algorithm(algorithm);
+        if let Some(post_auf) = reached(&attempt) {
+            return Some((pre_auf, post_auf));
+        }
+        start.turn_cube(&3);
+    }
+    return None;
+}
+
+// The stickers of the case algorithm solves, with every AUF before and after it tried and the
+// smallest kept, so algorithms for the same case give the same key. COLL leaves out the side
+// stickers of the last layer edges, which its algorithms are free to move.
+fn case_key(goal: Goal, algorithm: &Algorithm) -> String {
+    let mut keys = Vec::new();
+    for post_auf in 0..4 {
+        for pre_auf in 0..4 {
+            let mut state = RubixCube::create_solved_rubix();
+            state.apply_algorithm(&Algorithm::new(vec![Move::new(Layer::U, post_auf)]).concat(&algorithm.inverse()));
+            state.apply_move(Move::new(Layer::U, pre_auf));
+            let mut faces = state.get_faces();
+            if goal == Goal::Coll {
+                for (face, index) in [(cubies::L, 1), (cubies::F, 1), (cubies::R, 1), (cubies::B, 7)] {
+                    faces[face][index] = faces[cubies::U][4];
+                }
+            }
+            keys.push(faces.iter().flatten().map(|colour| colour.to_string()).collect::<String>());
+        }
+    }
+    return keys.into_iter().min().unwrap_or_default();
+}
+
+fn verify(set: &AlgorithmSet, case: &str, algorithm: &Algorithm) -> Result<(), String> {
+    let goal = set.goal;
+    let mut state = RubixCube::create_solved_rubix();
+    state.apply_algorithm(&algorithm.inverse());
+    if !goal.accepts(&state) {
+        return Err(format!("{} does not fit a {:?} case", algorithm, goal));
+    }
+    let recognised = match goal {
+        Goal::F2l => recognition::recognise_f2l(&state, Slot::FR),
+        Goal::Oll => recognition::recognise_oll(&state),
+        Goal::Pll => recognition::recognise_pll(&state),
+        Goal::Coll | Goal::Zbll | Goal::Ll => {
+            let key = case_key(goal, algorithm);
+            for entry in &set.entries {
+                let same_case = case_key(goal, &entry.algorithm) == key;
+                if entry.case == case && !same_case {
+                    return Err(format!("{} solves a different case from {}, also listed as {}", algorithm, entry.algorithm, case));
+                }
+                if entry.case != case && same_case {
+                    return Err(format!("{} solves {}, not {}", algorithm, entry.case, case));
+                }
+            }
+            return Ok(());
+        }
+    };
+    return match recognised {
+        Some(recognition) if recognition.name == case => Ok(()),
+        Some(recognition) => Err(format!("{} solves {}, not {}", algorithm, recognition.name, case)),
+        None => Err(format!("{} does not solve a recognisable case", algorithm)),
+    };
+}
@@ -0,0 +1,222 @@
+use std::fmt;
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration, Instant};
+
+use crate::algorithm::{Algorithm, Layer, Move};
+use crate::beginner;
+use crate::cfop;
+use crate::error::CubeError;
+use crate::lazy_table::cancelled;
+use crate::library::{AlgorithmLibrary, Goal, LibraryMatch};
+use crate::recognition;
+use crate::roux;
+use crate::rubix_cube::RubixCube;
+use crate::solution::Solution;
+use crate::thistlethwaite;
+use crate::zz;
+
+// What the method solvers share: the cube being solved, the library its algorithms come
+// from, the stages found so far and the flag that stops a stage building its table. A frame
+// of n is the cube seen after n y turns.
+pub struct StageSolver<'a> {
+    pub cube: RubixCube,
+    pub library: &'a AlgorithmLibrary,
+    pub solution: Solution,
+    pub cancel: Option<&'a AtomicBool>,
+}
+
+impl<'a> StageSolver<'a> {
+    pub fn new(cube: &RubixCube, library: &'a AlgorithmLibrary, method: &str, cancel: Option<&'a AtomicBool>) -> StageSolver<'a> {
+        return StageSolver { cube: *cube, library, solution: Solution::new(method), cancel };
+    }
+
+    pub fn view(&self, frame: u8) -> RubixCube {
+        let mut view = self.cube;
+        for _ in 0..frame % 4 {
+            view.apply_algorithm(&Algorithm::parse("y").unwrap());
+        }
+        return view;
+    }
+
+    pub fn colour_name(&self, face: usize) -> &'static str {
+        return self.cube.get_faces()[face][4].name();
+    }
+
+    // Plays a stage found some other way and records it
+    pub fn push_stage(&mut self, name: &str, explanation: &str, algorithm: Algorithm) {
+        self.cube.apply_algorithm(&algorithm);
+        self.record(name, explanation, algorithm);
+    }
+
+    // Records a stage already played, joining turns left next to each other by its parts
+    pub fn record(&mut self, name: &str, explanation: &str, algorithm: Algorithm) {
+        self.solution.push_stage(name, explanation, algorithm.simplified());
+    }
+
+    // A library match played in a frame: pre AUF, the algorithm, then post AUF
+    pub fn play_match(&mut self, found: &LibraryMatch, frame: u8) -> Algorithm {
+        let mut algorithm = Algorithm::default();
+        if found.pre_auf > 0 {
+            algorithm.push(Move::new(Layer::U, found.pre_auf));
+        }
+        algorithm = algorithm.concat(&found.entry.algorithm.after_y(frame));
+        if found.post_auf > 0 {
+            algorithm.push(Move::new(Layer::U, found.post_auf));
+        }
+        self.cube.apply_algorithm(&algorithm);
+        return algorithm;
+    }
+
+    // The shortest library algorithm for goal on the cube as it is
+    pub fn best_match(&self, goal: Goal) -> Option<LibraryMatch<'a>> {
+        let library = self.library;
+        return library.algorithms_for_goal(&self.cube, goal).into_iter().min_by_key(match_cost);
+    }
+
+    pub fn oll(&mut self) -> Option<()> {
+        if recognition::last_layer_oriented(&self.cube) {
+            self.record("OLL", "OLL skip: the last layer is already oriented.", Algorithm::default());
+            return Some(());
+        }
+        let found = self.best_match(Goal::Oll)?;
+        let algorithm = self.play_match(&found, 0);
+        self.record("OLL", &format!("Orient the last layer: {}.", found.entry.case), algorithm);
+        return Some(());
+    }
+
+    pub fn pll(&mut self) -> Option<()> {
+        let skip = recognition::recognise_pll(&self.cube)?;
+        if skip.is_skip() {
+            let mut algorithm = Algorithm::default();
+            if skip.post_auf > 0 {
+                algorithm.push(Move::new(Layer::U, skip.post_auf));
+            }
+            self.push_stage("PLL", "PLL skip: only the last layer needs lining up.", algorithm);
+            return Some(());
+        }
+        let found = self.best_match(Goal::Pll)?;
+        let algorithm = self.play_match(&found, 0);
+        self.record("PLL", &format!("Permute the last layer: {} perm.", found.entry.case), algorithm);
+        return Some(());
+    }
+
+    pub fn finish(self) -> Result<Solution, String> {
+        if !self.cube.is_solved() {
+            return Err(String::from("cube could not be solved"));
+        }
+        return Ok(self.solution);
+    }
+}
+
+// Moves a match costs, counting each AUF as one
+pub fn match_cost(found: &LibraryMatch) -> usize {
+    return found.entry.algorithm.len() + (found.pre_auf > 0) as usize + (found.post_auf > 0) as usize;
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Method {
+    Beginner,
+    Cfop,
+    Roux,
+    Zz,
+    Thistlethwaite,
+}
+
+impl Method {
+    pub const ALL: [Method; 5] = [Method::Beginner, Method::Cfop, Method::Roux, Method::Zz, Method::Thistlethwaite];
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            Method::Beginner => "Beginner",
+            Method::Cfop => "CFOP",
+            Method::Roux => "Roux",
+            Method::Zz => "ZZ",
+            Method::Thistlethwaite => "Thistlethwaite",
+        };
+    }
+
+    // Fails straight away on a state no turns could solve, rather than at whichever stage gets stuck
+    pub fn solve(&self, cube: &RubixCube, library: &AlgorithmLibrary) -> Result<Solution, CubeError> {
+        return self.solve_with_cancel(cube, library, None);
+    }
+
+    // As solve, giving up once cancel is set. Only building a stage's table takes long enough
+    // for that to matter: seconds for Roux, ZZ and Thistlethwaite, the first time each is used.
+    pub fn solve_with_cancel(&self, cube: &RubixCube, library: &AlgorithmLibrary, cancel: Option<&AtomicBool>) -> Result<Solution, CubeError> {
+        cube.check_solvable()?;
+        let solved = match self {
+            Method::Beginner => beginner::solve_beginner(cube),
+            Method::Cfop => cfop::solve_cfop(cube, library, cancel),
+            Method::Roux => roux::solve_roux(cube, library, cancel),
+            Method::Zz => zz::solve_zz(cube, library, cancel),
+            Method::Thistlethwaite => thistlethwaite::solve_thistlethwaite(cube, cancel),
+        };
+        return solved.map_err(|reason| if cancelled(cancel) { CubeError::Cancelled } else { CubeError::MethodFailed(reason) });
+    }
+}
+
+// How one method did over a set of scrambles
+#[derive(Clone, Debug)]
+pub struct MethodReport {
+    pub method: Method,
+    pub solved: usize,
+    pub failed: usize,
+    pub total_moves: usize,
+    pub fewest_moves: usize,
+    pub most_moves: usize,
+    pub time: Duration,
+}
+
+impl MethodReport {
+    pub fn average_moves(&self) -> f64 {
+        if self.solved == 0 {
+            return 0.0;
+        }
+        return self.total_moves as f64 / self.solved as f64;
+    }
+}
+
+impl fmt::Display for MethodReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<14} solved {:>5}  failed {:>3}  moves avg {:>6.1} min {:>3} max {:>3}  time {:?}",
+            self.method.name(),
+            self.solved,
+            self.failed,
+            self.average_moves(),
+            self.fewest_moves,
+            self.most_moves,
+            self.time
+        )
+    }
+}
+
+// Runs every method over the same scrambles. Table based stages build their tables on first use,
+// so each method solves the first scramble once untimed.
+pub fn compare_methods(methods: &[Method], scrambles: &[RubixCube], library: &AlgorithmLibrary) -> Vec<MethodReport> {
+    let mut reports = Vec::new();
+    for &method in methods {
+        if let Some(first) = scrambles.first() {
+            let _ = method.solve(first, library);
+        }
+        let mut report =
+            MethodReport { method, solved: 0, failed: 0, total_moves: 0, fewest_moves: 0, most_moves: 0, time: Duration::ZERO };
+        let start = Instant::now();
+        for scramble in scrambles {
+            match method.solve(scramble, library) {
+                Ok(solution) => {
+                    let moves = solution.move_count();
+                    report.fewest_moves = if report.solved == 0 { moves } else { report.fewest_moves.min(moves) };
+                    report.most_moves = report.most_moves.max(moves);
+                    report.total_moves += moves;
+                    report.solved += 1;
+                }
+                Err(_) => report.failed += 1,
+            }
+        }
+        report.time = start.elapsed();
+        reports.push(report);
+    }
+    return reports;
+}
@@ -0,0 +1,460 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use rayon::prelude::*;
+
+use crate::algorithm::{follows, Algorithm, Layer, Move};
+use crate::checkpoint::Checkpoint;
+use crate::cubies::{CubieCube, CubieMove};
+use crate::error::CubeError;
+use crate::piece_search::PieceSearch;
+use crate::rubix_cube::RubixCube;
+use crate::symmetry::{symmetries, Symmetry};
+use crate::threads::Threads;
+
+// IDA* over the 18 face turns, spread over every core. Each iteration walks the tree
+// split_depth moves deep on one thread, keeping every node still within the bound as a task,
+// and rayon hands the tasks out to whichever worker is free, so a slow branch never holds up
+// the rest. Workers share the next iteration's bound as they find it, and the first one to
+// reach solved stops them all: every solution an iteration can find is the same length, the
+// shortest there is.
+//
+// Progress can be saved to a Checkpoint as tasks finish and when the search is cancelled, and
+// picked up again with resume.
+//
+// A cancel flag set from another thread stops the search within a few milliseconds.
+//
+// With Threads::Single the tasks are searched in order on the calling thread, so the same
+// scramble always gives the same solution.
+//
+// The estimate is the most moves any group of four corners or four edges needs on its own,
+// read from tables built on first use. The D face's groups are the U face's turned upside
+// down, so they share its tables through a symmetry, and each table keeps one distance per
+// symmetry class of its group.
+
+const BOUND_CORNERS: [[usize; 4]; 2] = [[0, 1, 2, 3], [4, 5, 6, 7]];
+const BOUND_EDGES: [[usize; 4]; 3] = [[0, 1, 2, 3], [4, 5, 6, 7], [8, 9, 10, 11]];
+
+#[derive(Clone)]
+pub struct ParallelSearch {
+    // Moves made before the tree is cut into tasks; 3 gives a few thousand
+    pub split_depth: usize,
+    // Longest solution looked for
+    pub max_depth: usize,
+    pub threads: Threads,
+    // Setting it gives up on the solve
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+impl Default for ParallelSearch {
+    fn default() -> ParallelSearch {
+        return ParallelSearch { split_depth: 3, max_depth: 20, threads: Threads::Global, cancel: None };
+    }
+}
+
+struct Tables {
+    moves: Vec<(Move, CubieMove)>,
+    bounds: Vec<PieceSearch>,
+    // Each group as the table it is read from, and the symmetry turning its pieces into that
+    // table's, if they aren't already
+    groups: Vec<(usize, Option<&'static Symmetry>)>,
+}
+
+fn tables() -> &'static Tables {
+    static TABLES: OnceLock<Tables> = OnceLock::new();
+    return TABLES.get_or_init(|| {
+        let moves = PieceSearch::turns_of(&[Layer::U, Layer::D, Layer::L, Layer::R, Layer::F, Layer::B]);
+        let mut pieces: Vec<(Vec<usize>, Vec<usize>)> = Vec::new();
+        let mut bounds = Vec::new();
+        let mut groups = Vec::new();
+        let all = BOUND_CORNERS.iter().map(|corners| (corners.to_vec(), Vec::new())).chain(BOUND_EDGES.iter().map(|edges| (Vec::new(), edges.to_vec())));
+        for (corners, edges) in all {
+            let turned = |symmetry: &Symmetry, table: &(Vec<usize>, Vec<usize>)| {
+                let mut turned_corners: Vec<usize> = corners.iter().map(|&piece| symmetry.map_corner(piece, 0, 0).0).collect();
+                let mut turned_edges: Vec<usize> = edges.iter().map(|&piece| symmetry.map_edge(piece, 0, 0).0).collect();
+                turned_corners.sort();
+                turned_edges.sort();
+                (turned_corners, turned_edges) == *table
+            };
+            let shared = pieces.iter().enumerate().find_map(|(index, table)| symmetries().iter().find(|symmetry| turned(symmetry, table)).map(|symmetry| (index, symmetry)));
+            match shared {
+                Some((index, symmetry)) => groups.push((index, Some(symmetry))),
+                None => {
+                    groups.push((bounds.len(), None));
+                    bounds.push(PieceSearch::symmetric(&corners, &edges));
+                    pieces.push((corners, edges));
+                }
+            }
+        }
+        Tables { moves: moves.iter().map(|&next| (next, CubieMove::from_move(next))).collect(), bounds, groups }
+    });
+}
+
+fn estimate(cube: &CubieCube) -> u8 {
+    let tables = tables();
+    return tables
+        .groups
+        .iter()
+        .map(|&(table, symmetry)| match symmetry {
+            Some(symmetry) => tables.bounds[table].cubie_distance(&symmetry.apply(cube)),
+            None => tables.bounds[table].cubie_distance(cube),
+        })
+        .max()
+        .unwrap();
+}
+
+// A node the walk to split_depth stopped at, for one worker to search below
+struct Task {
+    cube: CubieCube,
+    path: Vec<Move>,
+}
+
+// What the workers of one iteration share
+struct Iteration<'a> {
+    bound: u8,
+    // Smallest estimate seen over the bound, the bound for the next iteration
+    next_bound: AtomicU8,
+    stop: &'a AtomicBool,
+    // The caller's flag, which unlike stop is never set by the search itself
+    cancel: Option<&'a AtomicBool>,
+    nodes: AtomicU64,
+    // When set, every solution goes in here and the search carries on to find the rest
+    all: Option<Mutex<Vec<Vec<Move>>>>,
+}
+
+impl<'a> Iteration<'a> {
+    fn new(bound: u8, next_bound: u8, stop: &'a AtomicBool, all: bool) -> Iteration<'a> {
+        return Iteration {
+            bound,
+            next_bound: AtomicU8::new(next_bound),
+            stop,
+            cancel: None,
+            nodes: AtomicU64::new(0),
+            all: if all { Some(Mutex::new(Vec::new())) } else { None },
+        };
+    }
+
+    fn stopped(&self) -> bool {
+        return self.stop.load(Ordering::Relaxed) || self.cancel.is_some_and(|cancel| cancel.load(Ordering::Relaxed));
+    }
+
+    // Whether path reaching cube ends the search, noting it if all solutions are wanted
+    fn solution(&self, cube: &CubieCube, path: &[Move]) -> bool {
+        if *cube != CubieCube::solved() {
+            return false;
+        }
+        let Some(all) = &self.all else {
+            return true;
+        };
+        all.lock().unwrap().push(path.to_vec());
+        return false;
+    }
+
+    // Depth first below cube, leaving path holding the solution if one is found
+    fn search(&self, cube: &CubieCube, path: &mut Vec<Move>, nodes: &mut u64) -> bool {
+        if self.stopped() {
+            return false;
+        }
+        *nodes += 1;
+        let estimate = estimate(cube);
+        let total = path.len() as u8 + estimate;
+        if total > self.bound {
+            self.next_bound.fetch_min(total, Ordering::Relaxed);
+            return false;
+        }
+        if estimate == 0 && self.solution(cube, path) {
+            return true;
+        }
+        for (next, table) in &tables().moves {
+            if !follows(path.last().copied(), *next) {
+                continue;
+            }
+            path.push(*next);
+            if self.search(&cube.apply(table), path, nodes) {
+                return true;
+            }
+            path.pop();
+        }
+        return false;
+    }
+
+    // Walks split_depth moves deep, returning a solution found on the way or leaving the
+    // nodes still within the bound in tasks
+    fn split(&self, cube: &CubieCube, path: &mut Vec<Move>, split_depth: usize, tasks: &mut Vec<Task>) -> Option<Vec<Move>> {
+        let total = path.len() as u8 + estimate(cube);
+        if total > self.bound {
+            self.next_bound.fetch_min(total, Ordering::Relaxed);
+            return None;
+        }
+        if self.solution(cube, path) {
+            return Some(path.clone());
+        }
+        if path.len() == split_depth {
+            tasks.push(Task { cube: *cube, path: path.clone() });
+            return None;
+        }
+        self.nodes.fetch_add(1, Ordering::Relaxed);
+        for (next, table) in &tables().moves {
+            if !follows(path.last().copied(), *next) {
+                continue;
+            }
+            path.push(*next);
+            let found = self.split(&cube.apply(table), path, split_depth, tasks);
+            path.pop();
+            if found.is_some() {
+                return found;
+            }
+        }
+        return None;
+    }
+
+    fn finish(self, solutions: Vec<Vec<Move>>) -> Subtree {
+        let mut solutions = solutions;
+        if let Some(all) = self.all {
+            solutions.extend(all.into_inner().unwrap());
+        }
+        return Subtree { solutions, next_bound: self.next_bound.into_inner(), nodes: self.nodes.into_inner() };
+    }
+}
+
+// What searching part of an iteration found, for a distributed coordinator to combine
+pub(crate) struct Subtree {
+    pub solutions: Vec<Vec<Move>>,
+    pub next_bound: u8,
+    pub nodes: u64,
+}
+
+pub(crate) fn first_bound(start: &CubieCube) -> u8 {
+    return estimate(start);
+}
+
+// The task prefixes of one iteration, and what the walk out to them found on its own
+pub(crate) fn split_tree(start: &CubieCube, bound: u8, split_depth: usize, all: bool) -> (Vec<Vec<Move>>, Subtree) {
+    let stop = AtomicBool::new(false);
+    let iteration = Iteration::new(bound, u8::MAX, &stop, all);
+    let mut tasks = Vec::new();
+    let found = iteration.split(start, &mut Vec::new(), split_depth, &mut tasks);
+    return (tasks.into_iter().map(|task| task.path).collect(), iteration.finish(found.into_iter().collect()));
+}
+
+// Searches below prefix within bound; stop may be set from another thread to give up early
+pub(crate) fn search_subtree(start: &CubieCube, prefix: &[Move], bound: u8, all: bool, stop: &AtomicBool) -> Subtree {
+    let iteration = Iteration::new(bound, u8::MAX, stop, all);
+    let mut cube = *start;
+    for next in prefix {
+        let (_, table) = tables().moves.iter().find(|(known, _)| known == next).expect("face turn");
+        cube = cube.apply(table);
+    }
+    let mut path = prefix.to_vec();
+    let mut nodes = 0;
+    let found = iteration.search(&cube, &mut path, &mut nodes);
+    iteration.nodes.fetch_add(nodes, Ordering::Relaxed);
+    return iteration.finish(if found { vec![path] } else { Vec::new() });
+}
+
+impl ParallelSearch {
+    pub fn new(split_depth: usize, max_depth: usize) -> ParallelSearch {
+        return ParallelSearch { split_depth, max_depth, threads: Threads::Global, cancel: None };
+    }
+
+    pub fn with_threads(self, threads: Threads) -> ParallelSearch {
+        return ParallelSearch { threads, ..self };
+    }
+
+    pub fn with_cancel(self, cancel: Arc<AtomicBool>) -> ParallelSearch {
+        return ParallelSearch { cancel: Some(cancel), ..self };
+    }
+
+    // Distances the estimate tables keep, and how many they would with one for every
+    // arrangement of every group. Builds the tables if no search has yet.
+    pub fn table_entries() -> (usize, usize) {
+        let tables = tables();
+        let kept = tables.bounds.iter().map(|bound| bound.entries()).sum();
+        return (kept, tables.groups.len() * 24usize.pow(4));
+    }
+
+    fn cancelled(&self) -> bool {
+        return self.cancel.as_ref().is_some_and(|cancel| cancel.load(Ordering::Relaxed));
+    }
+
+    // A shortest face turn solution, or why none was found
+    pub fn solve(&self, cube: &RubixCube) -> Result<Algorithm, CubeError> {
+        return self.solve_counting(cube).map(|(algorithm, _)| algorithm);
+    }
+
+    // As solve, also giving the number of nodes visited over all iterations
+    pub fn solve_counting(&self, cube: &RubixCube) -> Result<(Algorithm, u64), CubeError> {
+        return self.run(cube, None, None);
+    }
+
+    // As solve, saving progress to path at most every interval (and at each new bound) so
+    // an interrupted run can be picked up with resume
+    pub fn solve_saving(&self, cube: &RubixCube, path: &Path, interval: Duration) -> Result<Algorithm, CubeError> {
+        return self.run(cube, None, Some((path, interval))).map(|(algorithm, _)| algorithm);
+    }
+
+    // Carries on the search saved at path, saving to it again as it goes. The split and
+    // maximum depths come from the checkpoint; the threads and cancel flag are this search's.
+    pub fn resume(&self, path: &Path, interval: Duration) -> Result<Algorithm, CubeError> {
+        let checkpoint = Checkpoint::load(path).map_err(CubeError::Checkpoint)?;
+        let search = ParallelSearch { split_depth: checkpoint.split_depth, max_depth: checkpoint.max_depth, ..self.clone() };
+        let cube = checkpoint.cube;
+        return search.run(&cube, Some(checkpoint), Some((path, interval))).map(|(algorithm, _)| algorithm);
+    }
+
+    fn run(&self, cube: &RubixCube, resumed: Option<Checkpoint>, saving: Option<(&Path, Duration)>) -> Result<(Algorithm, u64), CubeError> {
+        let start = CubieCube::from_rubix(cube).ok_or(CubeError::Unsolvable(String::from("some stickers don't make real pieces")))?;
+        let mut progress = resumed.unwrap_or(Checkpoint {
+            cube: *cube,
+            split_depth: self.split_depth,
+            max_depth: self.max_depth,
+            bound: estimate(&start),
+            next_bound: u8::MAX,
+            nodes: 0,
+            done: Vec::new(),
+        });
+        while progress.bound as usize <= self.max_depth {
+            if let Some((path, _)) = saving {
+                progress.save(path).map_err(CubeError::Checkpoint)?;
+            }
+            if self.cancelled() {
+                return Err(CubeError::Cancelled);
+            }
+            let stop = AtomicBool::new(false);
+            let mut iteration = Iteration::new(progress.bound, progress.next_bound, &stop, false);
+            iteration.cancel = self.cancel.as_deref();
+            let mut tasks = Vec::new();
+            let mut found = iteration.split(&start, &mut Vec::new(), self.split_depth, &mut tasks);
+            let done: Vec<AtomicBool> = (0..tasks.len()).map(|task| AtomicBool::new(progress.done.binary_search(&task).is_ok())).collect();
+            let last_save = Mutex::new(Instant::now());
+            let save_error = Mutex::new(None);
+            if found.is_none() {
+                let run = |(index, task): (usize, &Task)| {
+                    if done[index].load(Ordering::Relaxed) {
+                        return None;
+                    }
+                    let mut path = task.path.clone();
+                    let mut task_nodes = 0;
+                    let solved = iteration.search(&task.cube, &mut path, &mut task_nodes);
+                    iteration.nodes.fetch_add(task_nodes, Ordering::Relaxed);
+                    if solved {
+                        iteration.stop.store(true, Ordering::Relaxed);
+                        return Some(path);
+                    }
+                    if iteration.stopped() {
+                        return None;
+                    }
+                    done[index].store(true, Ordering::Relaxed);
+                    let (file, interval) = saving?;
+                    let mut last_save = last_save.lock().unwrap();
+                    if last_save.elapsed() >= interval {
+                        if let Err(error) = snapshot(&progress, &iteration, &done).save(file) {
+                            *save_error.lock().unwrap() = Some(error);
+                            iteration.stop.store(true, Ordering::Relaxed);
+                        }
+                        *last_save = Instant::now();
+                    }
+                    return None;
+                };
+                found = if self.threads.is_single() {
+                    tasks.iter().enumerate().find_map(run)
+                } else {
+                    self.threads.install(|| tasks.par_iter().enumerate().find_map_any(run))
+                };
+            }
+            if let Some(error) = save_error.into_inner().unwrap() {
+                return Err(CubeError::Checkpoint(error));
+            }
+            if let Some(path) = found {
+                return Ok((Algorithm::new(path), progress.nodes + iteration.nodes.load(Ordering::Relaxed)));
+            }
+            // Tasks cut short aren't done, so the bound can't move on past them, but the ones
+            // that finished are kept for resume
+            if self.cancelled() {
+                if let Some((path, _)) = saving {
+                    snapshot(&progress, &iteration, &done).save(path).map_err(CubeError::Checkpoint)?;
+                }
+                return Err(CubeError::Cancelled);
+            }
+            progress.nodes += iteration.nodes.load(Ordering::Relaxed);
+            progress.bound = iteration.next_bound.load(Ordering::Relaxed);
+            progress.next_bound = u8::MAX;
+            progress.done.clear();
+        }
+        return Err(CubeError::NoSolution(self.max_depth));
+    }
+}
+
+// progress part way through iteration, with the tasks in done finished
+fn snapshot(progress: &Checkpoint, iteration: &Iteration, done: &[AtomicBool]) -> Checkpoint {
+    return Checkpoint {
+        next_bound: iteration.next_bound.load(Ordering::Relaxed),
+        nodes: progress.nodes + iteration.nodes.load(Ordering::Relaxed),
+        done: (0..done.len()).filter(|&task| done[task].load(Ordering::Relaxed)).collect(),
+        ..progress.clone()
+    };
+}
+
+// How the search did over a set of scrambles with a given number of threads
+#[derive(Clone, Debug)]
+pub struct ScalingReport {
+    pub threads: usize,
+    pub solved: usize,
+    pub failed: usize,
+    pub nodes: u64,
+    pub time: Duration,
+    // Time with the first thread count over time with this one
+    pub speedup: f64,
+}
+
+impl ScalingReport {
+    pub fn nodes_per_second(&self) -> f64 {
+        return self.nodes as f64 / self.time.as_secs_f64().max(f64::EPSILON);
+    }
+}
+
+impl fmt::Display for ScalingReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>3} threads  solved {:>4}  failed {:>3}  nodes {:>12}  {:>7.2}M nodes/s  time {:?}  speedup {:.2}x",
+            self.threads,
+            self.solved,
+            self.failed,
+            self.nodes,
+            self.nodes_per_second() / 1e6,
+            self.time,
+            self.speedup
+        )
+    }
+}
+
+// Solves the same scrambles in a pool of each size. The estimate tables are built before
+// anything is timed.
+pub fn scaling_benchmark(search: &ParallelSearch, scrambles: &[RubixCube], thread_counts: &[usize]) -> Result<Vec<ScalingReport>, String> {
+    tables();
+    let mut reports: Vec<ScalingReport> = Vec::new();
+    for &threads in thread_counts {
+        let search = ParallelSearch::new(search.split_depth, search.max_depth).with_threads(Threads::count(threads)?);
+        let mut report = ScalingReport { threads, solved: 0, failed: 0, nodes: 0, time: Duration::ZERO, speedup: 1.0 };
+        let start = Instant::now();
+        for scramble in scrambles {
+            match search.solve_counting(scramble) {
+                Ok((_, nodes)) => {
+                    report.solved += 1;
+                    report.nodes += nodes;
+                }
+                Err(_) => report.failed += 1,
+            }
+        }
+        report.time = start.elapsed();
+        if let Some(first) = reports.first() {
+            report.speedup = first.time.as_secs_f64() / report.time.as_secs_f64().max(f64::EPSILON);
+        }
+        reports.push(report);
+    }
+    return Ok(reports);
+}
@@ -0,0 +1,326 @@
+use std::collections::VecDeque;
+use std::mem;
+use std::sync::atomic::AtomicBool;
+
+use crate::algorithm::{Algorithm, Layer, Move};
+use crate::cubies::{self, CubieCube, CubieMove};
+use crate::lazy_table::cancelled;
+use crate::rubix_cube::RubixCube;
+use crate::state_key::{StateKey, TranspositionTable};
+use crate::symmetry::{self, symmetries};
+
+// Optimal solving of a handful of pieces (a cross, a block, a pair) with a given move set.
+// Every arrangement of the tracked pieces gets its distance from solved, found once by a
+// breadth first search over the whole space, so a solve is just walking downhill.
+//
+// Each piece takes one base 24 digit: position * 3 + twist for corners, position * 2 + flip
+// for edges. That keeps the table at 24^n entries, so n should stay at five or below. A search
+// built with flips also wants every edge oriented, adding the flip at each of the twelve
+// edge positions as twelve more bits.
+//
+// A search over all the face turns can keep one distance per symmetry class instead. The
+// symmetries taking the tracked pieces among themselves (8 for a face's corners, 16 for the
+// E slice edges) give arrangements the same distance, so only the arrangement with the
+// smallest index in each class is kept, in order with its distance, and a lookup turns an
+// index into that one first.
+
+const UNREACHED: u8 = u8::MAX;
+
+pub struct PieceSearch {
+    corners: Vec<usize>,
+    edges: Vec<usize>,
+    flips: bool,
+    moves: Vec<(Move, CubieMove)>,
+    // One per index, or empty when classes are kept
+    distance: Vec<u8>,
+    // The symmetries keeping the tracked pieces together, as what each does to every digit
+    symmetries: Vec<DigitSymmetry>,
+    // Smallest index of each class shifted over its distance, smallest first
+    classes: Vec<u32>,
+}
+
+// For each digit of an index, what a symmetry makes of it: the digit it becomes, already
+// scaled by the place of the piece it turns into
+struct DigitSymmetry {
+    digits: Vec<[u32; 24]>,
+}
+
+impl PieceSearch {
+    // Tracks corners and edges, named by their home positions, over the face turns, with a
+    // distance for each symmetry class
+    pub fn symmetric(corners: &[usize], edges: &[usize]) -> PieceSearch {
+        let moves = PieceSearch::turns_of(&[Layer::U, Layer::D, Layer::L, Layer::R, Layer::F, Layer::B]);
+        let mut search = PieceSearch::build(corners, edges, false, &moves, None).unwrap();
+        let count = corners.len() + edges.len();
+        let place = |piece: usize, corner: bool| {
+            let slot = if corner { corners.iter().position(|&tracked| tracked == piece) } else { edges.iter().position(|&tracked| tracked == piece).map(|slot| corners.len() + slot) };
+            slot.map(|slot| 24u32.pow((count - 1 - slot) as u32))
+        };
+        for symmetry in symmetries() {
+            let mut digits = Vec::new();
+            for &piece in corners {
+                let Some(scale) = place(symmetry.map_corner(piece, 0, 0).0, true) else { break };
+                digits.push(std::array::from_fn(|digit| {
+                    let (_, position, twist) = symmetry.map_corner(piece, digit / 3, (digit % 3) as u8);
+                    (position * 3 + twist as usize) as u32 * scale
+                }));
+            }
+            for &piece in edges {
+                let Some(scale) = place(symmetry.map_edge(piece, 0, 0).0, false) else { break };
+                digits.push(std::array::from_fn(|digit| {
+                    let (_, position, flip) = symmetry.map_edge(piece, digit / 2, (digit % 2) as u8);
+                    (position * 2 + flip as usize) as u32 * scale
+                }));
+            }
+            if digits.len() == count {
+                search.symmetries.push(DigitSymmetry { digits });
+            }
+        }
+        let distance = mem::take(&mut search.distance);
+        search.classes = (0..distance.len())
+            .filter(|&index| distance[index] != UNREACHED && search.class(index) == index)
+            .map(|index| (index as u32) << 8 | distance[index] as u32)
+            .collect();
+        return search;
+    }
+
+    // As new, with flips also orienting all twelve edges (no F or B quarter turn needed to
+    // solve them). Gives up with None once cancel is set; five pieces take seconds.
+    pub fn build(corners: &[usize], edges: &[usize], flips: bool, moves: &[Move], cancel: Option<&AtomicBool>) -> Option<PieceSearch> {
+        let mut size = 24usize.pow((corners.len() + edges.len()) as u32);
+        if flips {
+            size <<= 12;
+        }
+        let mut search = PieceSearch {
+            corners: corners.to_vec(),
+            edges: edges.to_vec(),
+            flips,
+            moves: moves.iter().map(|&next| (next, CubieMove::from_move(next))).collect(),
+            distance: vec![UNREACHED; size],
+            symmetries: Vec::new(),
+            classes: Vec::new(),
+        };
+        let solved = search.encode(
+            &search.corners.iter().map(|&piece| (piece as u8, 0)).collect::<Vec<_>>(),
+            &search.edges.iter().map(|&piece| (piece as u8, 0)).collect::<Vec<_>>(),
+            0,
+        );
+        search.distance[solved] = 0;
+        let mut queue = VecDeque::from([solved]);
+        while let Some(index) = queue.pop_front() {
+            if cancelled(cancel) {
+                return None;
+            }
+            let depth = search.distance[index];
+            for m in 0..search.moves.len() {
+                let next = search.step(index, m);
+                if search.distance[next] == UNREACHED {
+                    search.distance[next] = depth + 1;
+                    queue.push_back(next);
+                }
+            }
+        }
+        return Some(search);
+    }
+
+    // Each layer turned a quarter, half and three quarters
+    pub fn turns_of(layers: &[Layer]) -> Vec<Move> {
+        let mut moves = Vec::new();
+        for &layer in layers {
+            for amount in 1..4 {
+                moves.push(Move::new(layer, amount));
+            }
+        }
+        return moves;
+    }
+
+    // Moves needed to solve the tracked pieces of a cube already read as pieces. Every
+    // arrangement is reachable with the face turns, so with those this never gives UNREACHED.
+    pub fn cubie_distance(&self, cube: &CubieCube) -> u8 {
+        let mut index = 0;
+        for &piece in &self.corners {
+            let position = cube.corners.iter().position(|&(found, _)| found as usize == piece).unwrap();
+            index = index * 24 + position * 3 + cube.corners[position].1 as usize;
+        }
+        for &piece in &self.edges {
+            let position = cube.edges.iter().position(|&(found, _)| found as usize == piece).unwrap();
+            index = index * 24 + position * 2 + cube.edges[position].1 as usize;
+        }
+        if self.flips {
+            index = (0..12).fold(index << 12, |index, position| index | (cube.edges[position].1 as usize) << position);
+        }
+        return self.distance_at(index);
+    }
+
+    // Distances kept, one per index or one per class
+    pub fn entries(&self) -> usize {
+        return self.distance.len() + self.classes.len();
+    }
+
+    fn distance_at(&self, index: usize) -> u8 {
+        if self.classes.is_empty() {
+            return self.distance[index];
+        }
+        let class = self.class(index) as u32;
+        return match self.classes.binary_search_by_key(&class, |entry| entry >> 8) {
+            Ok(found) => self.classes[found] as u8,
+            Err(_) => UNREACHED,
+        };
+    }
+
+    // Smallest index among the images of index under the symmetries kept
+    fn class(&self, index: usize) -> usize {
+        let count = self.corners.len() + self.edges.len();
+        let mut digits = [0; 8];
+        let mut rest = index;
+        for slot in (0..count).rev() {
+            digits[slot] = rest % 24;
+            rest /= 24;
+        }
+        let image = |symmetry: usize| self.symmetries[symmetry].digits.iter().zip(digits).map(|(scaled, digit)| scaled[digit]).sum::<u32>();
+        return symmetry::smallest(0..self.symmetries.len(), image).0 as usize;
+    }
+
+    // A shortest algorithm solving the tracked pieces
+    pub fn solve(&self, cube: &RubixCube) -> Option<Algorithm> {
+        let mut index = self.read(cube)?;
+        if self.distance_at(index) == UNREACHED {
+            return None;
+        }
+        let mut algorithm = Algorithm::default();
+        while self.distance_at(index) > 0 {
+            let (m, next) = (0..self.moves.len())
+                .map(|m| (m, self.step(index, m)))
+                .find(|&(_, next)| self.distance_at(next) == self.distance_at(index) - 1)?;
+            algorithm.push(self.moves[m].0);
+            index = next;
+        }
+        return Some(algorithm);
+    }
+
+    fn read(&self, cube: &RubixCube) -> Option<usize> {
+        let mut corners = Vec::new();
+        for &piece in &self.corners {
+            let (position, twist) = cubies::find_corner(cube, piece)?;
+            corners.push((position as u8, twist));
+        }
+        let mut edges = Vec::new();
+        for &piece in &self.edges {
+            let (position, flip) = cubies::find_edge(cube, piece)?;
+            edges.push((position as u8, flip));
+        }
+        let mut flips = 0;
+        if self.flips {
+            for position in 0..12 {
+                flips |= (cubies::edge_at(cube, position)?.1 as usize) << position;
+            }
+        }
+        return Some(self.encode(&corners, &edges, flips));
+    }
+
+    fn encode(&self, corners: &[(u8, u8)], edges: &[(u8, u8)], flips: usize) -> usize {
+        let mut index = 0;
+        for &(position, twist) in corners {
+            index = index * 24 + position as usize * 3 + twist as usize;
+        }
+        for &(position, flip) in edges {
+            index = index * 24 + position as usize * 2 + flip as usize;
+        }
+        if self.flips {
+            index = (index << 12) | flips;
+        }
+        return index;
+    }
+
+    fn step(&self, index: usize, m: usize) -> usize {
+        let table = &self.moves[m].1;
+        let mut rest = index;
+        let mut flips = 0;
+        if self.flips {
+            for position in 0..12 {
+                let (to, flip) = table.edges[position];
+                flips |= (((rest >> position) & 1) ^ flip as usize) << to;
+            }
+            rest >>= 12;
+        }
+        // Digits come off the end, so edges first and every digit scaled by its place
+        let mut next = 0;
+        let mut place = 1;
+        for i in (0..self.corners.len() + self.edges.len()).rev() {
+            let digit = rest % 24;
+            rest /= 24;
+            let moved = if i < self.corners.len() {
+                let (position, twist) = table.corners[digit / 3];
+                position as usize * 3 + (digit % 3 + twist as usize) % 3
+            } else {
+                let (position, flip) = table.edges[digit / 2];
+                position as usize * 2 + (digit % 2 + flip as usize) % 2
+            };
+            next += moved * place;
+            place *= 24;
+        }
+        if self.flips {
+            next = (next << 12) | flips;
+        }
+        return next;
+    }
+}
+
+// Shortest sequence of moves reaching goal, trying every length up to max_depth. For stages
+// small enough that a table would be overkill; the same layer is never turned twice in a row,
+// and a state already tried this iteration with as many moves to spare is not tried again.
+pub fn search(cube: &RubixCube, moves: &[Move], max_depth: usize, goal: impl Fn(&RubixCube) -> bool) -> Option<Algorithm> {
+    let mut cube = *cube;
+    let mut path = Vec::new();
+    let mut tried = TranspositionTable::new(SEARCH_TABLE_BYTES);
+    for depth in 0..=max_depth {
+        let mut iteration = Iteration { moves, goal: &goal, tried: &mut tried, depth: depth as u8 };
+        if iteration.search(&mut cube, depth as u8, &mut path) {
+            return Some(Algorithm::new(path));
+        }
+    }
+    return None;
+}
+
+const SEARCH_TABLE_BYTES: usize = 1 << 20;
+
+struct Iteration<'a, G: Fn(&RubixCube) -> bool> {
+    moves: &'a [Move],
+    goal: &'a G,
+    // (iteration, moves to spare) of states already tried
+    tried: &'a mut TranspositionTable<(u8, u8)>,
+    depth: u8,
+}
+
+impl<G: Fn(&RubixCube) -> bool> Iteration<'_, G> {
+    fn search(&mut self, cube: &mut RubixCube, spare: u8, path: &mut Vec<Move>) -> bool {
+        if spare == 0 {
+            return (self.goal)(cube);
+        }
+        // Reading the key costs more than a last move, so states that close to the end aren't noted
+        if let Some(key) = (spare > 1).then(|| StateKey::from_rubix(cube)).flatten() {
+            if let Some((iteration, tried)) = self.tried.get(&key) {
+                if iteration == self.depth && tried >= spare {
+                    return false;
+                }
+            }
+            self.tried.insert(key, (self.depth, spare));
+        }
+        for &next in self.moves {
+            if path.last().is_some_and(|last: &Move| last.layer == next.layer) {
+                continue;
+            }
+            let before = *cube;
+            for code in next.codes() {
+                cube.turn_cube(&code);
+            }
+            path.push(next);
+            if self.search(cube, spare - 1, path) {
+                return true;
+            }
+            path.pop();
+            *cube = before;
+        }
+        return false;
+    }
+}
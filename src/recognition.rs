@@ -0,0 +1,338 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::algorithm::{Algorithm, Layer, Move};
+use crate::cubies::{self, B, D, F, L, R, U};
+use crate::rubix_cube::RubixCube;
+
+// Last layer cases are recognised on the U face (F1) with F2L on the D face (F5).
+// Each case is defined by a standard algorithm solving it from its usual angle; the
+// case itself is whatever that algorithm undoes from a solved cube.
+
+pub const OLL_CASES: [(&str, &str); 57] = [
+    ("OLL 1", "R U2 R2 F R F' U2 R' F R F'"),
+    ("OLL 2", "F R U R' U' F' f R U R' U' f'"),
+    ("OLL 3", "f R U R' U' f' U' F R U R' U' F'"),
+    ("OLL 4", "f R U R' U' f' U F R U R' U' F'"),
+    ("OLL 5", "l' U2 L U L' U l"),
+    ("OLL 6", "r U2 R' U' R U' r'"),
+    ("OLL 7", "r U R' U R U2 r'"),
+    ("OLL 8", "l' U' L U' L' U2 l"),
+    ("OLL 9", "R U R' U' R' F R2 U R' U' F'"),
+    ("OLL 10", "R U R' U R' F R F' R U2 R'"),
+    ("OLL 11", "r U R' U R' F R F' R U2 r'"),
+    ("OLL 12", "M' R' U' R U' R' U2 R U' R r'"),
+    ("OLL 13", "F U R U' R2 F' R U R U' R'"),
+    ("OLL 14", "R' F R U R' F' R F U' F'"),
+    ("OLL 15", "l' U' l L' U' L U l' U l"),
+    ("OLL 16", "r U r' R U R' U' r U' r'"),
+    ("OLL 17", "R U R' U R' F R F' U2 R' F R F'"),
+    ("OLL 18", "r U R' U R U2 r2 U' R U' R' U2 r"),
+    ("OLL 19", "r' R U R U R' U' M' R' F R F'"),
+    ("OLL 20", "r U R' U' M2 U R U' R' U' M'"),
+    ("OLL 21", "R U2 R' U' R U R' U' R U' R'"),
+    ("OLL 22", "R U2 R2 U' R2 U' R2 U2 R"),
+    ("OLL 23", "R2 D' R U2 R' D R U2 R"),
+    ("OLL 24", "r U R' U' r' F R F'"),
+    ("OLL 25", "F' r U R' U' r' F R"),
+    ("OLL 26", "R U2 R' U' R U' R'"),
+    ("OLL 27", "R U R' U R U2 R'"),
+    ("OLL 28", "r U R' U' r' R U R U' R'"),
+    ("OLL 29", "R U R' U' R U' R' F' U' F R U R'"),
+    ("OLL 30", "F R' F R2 U' R' U' R U R' F2"),
+    ("OLL 31", "R' U' F U R U' R' F' R"),
+    ("OLL 32", "L U F' U' L' U L F L'"),
+    ("OLL 33", "R U R' U' R' F R F'"),
+    ("OLL 34", "R U R2 U' R' F R U R U' F'"),
+    ("OLL 35", "R U2 R2 F R F' R U2 R'"),
+    ("OLL 36", "L' U' L U' L' U L U L F' L' F"),
+    ("OLL 37", "F R' F' R U R U' R'"),
+    ("OLL 38", "R U R' U R U' R' U' R' F R F'"),
+    ("OLL 39", "L F' L' U' L U F U' L'"),
+    ("OLL 40", "R' F R U R' U' F' U R"),
+    ("OLL 41", "R U R' U R U2 R' F R U R' U' F'"),
+    ("OLL 42", "R' U' R U' R' U2 R F R U R' U' F'"),
+    ("OLL 43", "F' U' L' U L F"),
+    ("OLL 44", "F U R U' R' F'"),
+    ("OLL 45", "F R U R' U' F'"),
+    ("OLL 46", "R' U' R' F R F' U R"),
+    ("OLL 47", "R' U' R' F R F' R' F R F' U R"),
+    ("OLL 48", "F R U R' U' R U R' U' F'"),
+    ("OLL 49", "r U' r2 U r2 U r2 U' r"),
+    ("OLL 50", "r' U r2 U' r2 U' r2 U r'"),
+    ("OLL 51", "F U R U' R' U R U' R' F'"),
+    ("OLL 52", "R U R' U R U' B U' B' R'"),
+    ("OLL 53", "l' U2 L U L' U' L U L' U l"),
+    ("OLL 54", "r U2 R' U' R U R' U' R U' r'"),
+    ("OLL 55", "R' F R U R U' R2 F' R2 U' R' U R U R'"),
+    ("OLL 56", "r' U' r U' R' U R U' R' U R r' U r"),
+    ("OLL 57", "R U R' U' M' U R U' r'"),
+];
+
+pub const PLL_CASES: [(&str, &str); 21] = [
+    ("Aa", "x R' U R' D2 R U' R' D2 R2 x'"),
+    ("Ab", "x R2 D2 R U R' D2 R U' R x'"),
+    ("E", "x' R U' R' D R U R' D' R U R' D R U' R' D' x"),
+    ("F", "R' U' F' R U R' U' R' F R2 U' R' U' R U R' U R"),
+    ("Ga", "R2 U R' U R' U' R U' R2 U' D R' U R D'"),
+    ("Gb", "R' U' R U D' R2 U R' U R U' R U' R2 D"),
+    ("Gc", "R2 U' R U' R U R' U R2 U D' R U' R' D"),
+    ("Gd", "R U R' U' D R2 U' R U' R' U R' U R2 D'"),
+    ("H", "M2 U M2 U2 M2 U M2"),
+    ("Ja", "R' U L' U2 R U' R' U2 R L U'"),
+    ("Jb", "R U R' F' R U R' U' R' F R2 U' R'"),
+    ("Na", "R U R' U R U R' F' R U R' U' R' F R2 U' R' U2 R U' R'"),
+    ("Nb", "R' U R U' R' F' U' F R U R' F R' F' R U' R"),
+    ("Ra", "R U' R' U' R U R D R' U' R D' R' U2 R'"),
+    ("Rb", "R2 F R U R U' R' F' R U2 R' U2 R"),
+    ("T", "R U R' U' R' F R2 U' R' U' R U R' F'"),
+    ("Ua", "M2 U M U2 M' U M2"),
+    ("Ub", "M2 U' M U2 M' U' M2"),
+    ("V", "R' U R' U' B' R' B2 U' B' U B' R B R"),
+    ("Y", "F R U' R' U' R U R' F' R U R' U' R' F R F'"),
+    ("Z", "M' U M2 U M2 U M' U2 M2"),
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum CaseSet {
+    F2l,
+    Oll,
+    Pll,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Slot {
+    FR,
+    FL,
+    BL,
+    BR,
+}
+
+impl Slot {
+    // Cube rotation bringing this slot to the front right
+    pub fn rotation(&self) -> Option<Move> {
+        return match self {
+            Slot::FR => None,
+            Slot::FL => Some(Move::new(Layer::Y, 3)),
+            Slot::BL => Some(Move::new(Layer::Y, 2)),
+            Slot::BR => Some(Move::new(Layer::Y, 1)),
+        };
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Recognition {
+    pub set: CaseSet,
+    pub name: String,
+    // U turns to do before the case's algorithm
+    pub pre_auf: u8,
+    // U turns after the algorithm to line the last layer up with the centres (PLL only)
+    pub post_auf: u8,
+}
+
+impl Recognition {
+    pub fn pre_auf_move(&self) -> Option<Move> {
+        return auf_move(self.pre_auf);
+    }
+
+    pub fn post_auf_move(&self) -> Option<Move> {
+        return auf_move(self.post_auf);
+    }
+
+    pub fn is_skip(&self) -> bool {
+        return self.name.ends_with("skip");
+    }
+}
+
+fn auf_move(turns: u8) -> Option<Move> {
+    if turns.is_multiple_of(4) {
+        return None;
+    }
+    return Some(Move::new(Layer::U, turns));
+}
+
+fn sticker_matches(faces: &[[crate::rubix_cube::Colour; 9]; 6], face: usize, index: usize) -> bool {
+    return faces[face][index] == faces[face][4];
+}
+
+pub fn cross_solved(cube: &RubixCube) -> bool {
+    return (4..8).all(|edge| cubies::edge_solved(cube, edge));
+}
+
+pub fn f2l_solved(cube: &RubixCube) -> bool {
+    let faces = cube.get_faces();
+    return (0..9).all(|i| sticker_matches(&faces, D, i))
+        && [L, F, R].iter().all(|&face| (3..9).all(|i| sticker_matches(&faces, face, i)))
+        && (0..6).all(|i| sticker_matches(&faces, B, i));
+}
+
+pub fn last_layer_oriented(cube: &RubixCube) -> bool {
+    let faces = cube.get_faces();
+    return (0..9).all(|i| sticker_matches(&faces, U, i));
+}
+
+pub fn last_layer_solved(cube: &RubixCube) -> bool {
+    let faces = cube.get_faces();
+    return last_layer_oriented(cube)
+        && [L, F, R].iter().all(|&face| (0..3).all(|i| sticker_matches(&faces, face, i)))
+        && (6..9).all(|i| sticker_matches(&faces, B, i));
+}
+
+// One bit per last layer sticker showing the U colour: the U face then the top row of L, F, R, B
+fn oll_key(cube: &RubixCube) -> u32 {
+    let faces = cube.get_faces();
+    let up = faces[U][4];
+    let mut stickers = Vec::with_capacity(21);
+    stickers.extend((0..9).map(|i| faces[U][i]));
+    for face in [L, F, R] {
+        stickers.extend((0..3).map(|i| faces[face][i]));
+    }
+    stickers.extend((6..9).map(|i| faces[B][i]));
+    let mut key = 0;
+    for (bit, colour) in stickers.iter().enumerate() {
+        if *colour == up {
+            key |= 1 << bit;
+        }
+    }
+    return key;
+}
+
+// The top row of L, F, R, B written as which side centre each sticker matches
+fn pll_key(cube: &RubixCube) -> Option<[u8; 12]> {
+    let faces = cube.get_faces();
+    let mut key = [0; 12];
+    let ring = [(L, 0), (L, 1), (L, 2), (F, 0), (F, 1), (F, 2), (R, 0), (R, 1), (R, 2), (B, 8), (B, 7), (B, 6)];
+    for (i, &(face, index)) in ring.iter().enumerate() {
+        key[i] = cubies::face_of(&faces, faces[face][index])? as u8;
+    }
+    return Some(key);
+}
+
+fn turn_u(cube: &mut RubixCube, turns: u8) {
+    for _ in 0..turns % 4 {
+        cube.turn_cube(&3);
+    }
+}
+
+// Sets up the state solved by `turns` U moves, then `algorithm`
+fn case_state(algorithm: &str, pre_auf: u8, post_auf: u8) -> RubixCube {
+    let algorithm = Algorithm::parse(algorithm).expect("built in case algorithm");
+    let mut cube = RubixCube::create_solved_rubix();
+    turn_u(&mut cube, 4 - post_auf % 4);
+    cube.apply_algorithm(&algorithm.inverse());
+    turn_u(&mut cube, 4 - pre_auf % 4);
+    return cube;
+}
+
+fn oll_table() -> &'static HashMap<u32, (usize, u8)> {
+    static TABLE: OnceLock<HashMap<u32, (usize, u8)>> = OnceLock::new();
+    return TABLE.get_or_init(|| {
+        let mut table = HashMap::new();
+        for (case, (_, algorithm)) in OLL_CASES.iter().enumerate() {
+            for pre_auf in 0..4 {
+                table.entry(oll_key(&case_state(algorithm, pre_auf, 0))).or_insert((case, pre_auf));
+            }
+        }
+        table
+    });
+}
+
+fn pll_table() -> &'static HashMap<[u8; 12], (usize, u8, u8)> {
+    static TABLE: OnceLock<HashMap<[u8; 12], (usize, u8, u8)>> = OnceLock::new();
+    return TABLE.get_or_init(|| {
+        let mut table = HashMap::new();
+        for (case, (_, algorithm)) in PLL_CASES.iter().enumerate() {
+            for pre_auf in 0..4 {
+                for post_auf in 0..4 {
+                    let key = pll_key(&case_state(algorithm, pre_auf, post_auf)).expect("solved centres");
+                    table.entry(key).or_insert((case, pre_auf, post_auf));
+                }
+            }
+        }
+        table
+    });
+}
+
+// Needs F2L solved. An oriented last layer comes back as "OLL skip".
+pub fn recognise_oll(cube: &RubixCube) -> Option<Recognition> {
+    if !f2l_solved(cube) {
+        return None;
+    }
+    if last_layer_oriented(cube) {
+        return Some(Recognition { set: CaseSet::Oll, name: String::from("OLL skip"), pre_auf: 0, post_auf: 0 });
+    }
+    let &(case, pre_auf) = oll_table().get(&oll_key(cube))?;
+    return Some(Recognition { set: CaseSet::Oll, name: String::from(OLL_CASES[case].0), pre_auf, post_auf: 0 });
+}
+
+// Needs F2L solved and the last layer oriented. A solved last layer comes back as "PLL skip".
+pub fn recognise_pll(cube: &RubixCube) -> Option<Recognition> {
+    if !f2l_solved(cube) || !last_layer_oriented(cube) {
+        return None;
+    }
+    let mut aligned = *cube;
+    for post_auf in 0..4 {
+        if last_layer_solved(&aligned) {
+            return Some(Recognition { set: CaseSet::Pll, name: String::from("PLL skip"), pre_auf: 0, post_auf });
+        }
+        turn_u(&mut aligned, 1);
+    }
+    let &(case, pre_auf, post_auf) = pll_table().get(&pll_key(cube)?)?;
+    return Some(Recognition { set: CaseSet::Pll, name: String::from(PLL_CASES[case].0), pre_auf, post_auf });
+}
+
+// OLL while the last layer is unoriented, PLL after
+pub fn recognise_last_layer(cube: &RubixCube) -> Option<Recognition> {
+    if !last_layer_oriented(cube) {
+        return recognise_oll(cube);
+    }
+    return recognise_pll(cube);
+}
+
+const F2L_CORNER: usize = 4;
+const F2L_EDGE: usize = 8;
+
+// Describes where the pair for the slot is, after the slot's rotation and an AUF that puts
+// the corner at URF (or the edge at UF when only the edge is in the U layer).
+// Twist and flip are named by the face showing the corner's D colour and the edge's front colour.
+pub fn recognise_f2l(cube: &RubixCube, slot: Slot) -> Option<Recognition> {
+    let mut rotated = *cube;
+    if let Some(rotation) = slot.rotation() {
+        rotated.apply_algorithm(&Algorithm::new(vec![rotation]));
+    }
+    for pre_auf in 0..4 {
+        let (corner, twist) = cubies::find_corner(&rotated, F2L_CORNER)?;
+        let (edge, flip) = cubies::find_edge(&rotated, F2L_EDGE)?;
+        let corner_in_u = corner < 4;
+        let edge_in_u = edge < 4;
+        let canonical = if corner_in_u { corner == 0 } else if edge_in_u { edge == 1 } else { true };
+        if !corner_in_u && corner != F2L_CORNER || !edge_in_u && edge != F2L_EDGE {
+            // Pair is stuck in another slot
+            return None;
+        }
+        if canonical {
+            let name = if corner == F2L_CORNER && twist == 0 && edge == F2L_EDGE && flip == 0 {
+                String::from("F2L solved")
+            } else {
+                let corner_facelet = cubies::CORNER_FACELETS[corner][twist as usize].0;
+                let edge_facelet = cubies::EDGE_FACELETS[edge][flip as usize].0;
+                format!(
+                    "corner {} ({} colour on {}), edge {} ({} colour on {})",
+                    cubies::CORNER_NAMES[corner],
+                    face_letter(D),
+                    face_letter(corner_facelet),
+                    cubies::EDGE_NAMES[edge],
+                    face_letter(F),
+                    face_letter(edge_facelet)
+                )
+            };
+            return Some(Recognition { set: CaseSet::F2l, name, pre_auf, post_auf: 0 });
+        }
+        turn_u(&mut rotated, 1);
+    }
+    return None;
+}
+
+pub fn face_letter(face: usize) -> char {
+    return ['U', 'L', 'F', 'R', 'D', 'B'][face];
+}
@@ -0,0 +1,180 @@
+use std::sync::atomic::AtomicBool;
+
+use crate::algorithm::{Algorithm, Layer, Move};
+use crate::cubies::{self, D, L, R, U};
+use crate::lazy_table::LazyTable;
+use crate::library::{self, AlgorithmLibrary, Goal};
+use crate::method::{match_cost, StageSolver};
+use crate::piece_search::{self, PieceSearch};
+use crate::rubix_cube::RubixCube;
+use crate::solution::Solution;
+
+// Roux: a 1x2x3 block on the left, another on the right using only R, r, U and M, the last
+// layer corners with the M slice free (CMLL), then the six edges and centres left over (LSE)
+// with M and U. Both blocks are searched for and optimal; CMLL borrows OLL algorithms to
+// orient the corners and PLL algorithms to permute them, as those leave both blocks alone.
+
+const FIRST_BLOCK_CORNERS: [usize; 2] = [5, 6]; // DLF, DBL
+const FIRST_BLOCK_EDGES: [usize; 3] = [6, 9, 10]; // DL, FL, BL
+const SECOND_BLOCK_CORNERS: [usize; 2] = [4, 7]; // DFR, DRB
+const SECOND_BLOCK_EDGES: [usize; 3] = [4, 8, 11]; // DR, FR, BR
+
+// UR, UF, UL, UB, DF, DB
+const LSE_EDGES: [usize; 6] = [0, 1, 2, 3, 5, 7];
+
+// Longest M/U sequence tried for each step of LSE
+const LSE_STEP_DEPTH: usize = 16;
+
+fn first_block_search(cancel: Option<&AtomicBool>) -> Option<&'static PieceSearch> {
+    static SEARCH: LazyTable<PieceSearch> = LazyTable::new();
+    return SEARCH.get(cancel, || {
+        let moves = PieceSearch::turns_of(&[Layer::U, Layer::D, Layer::L, Layer::R, Layer::F, Layer::B]);
+        PieceSearch::build(&FIRST_BLOCK_CORNERS, &FIRST_BLOCK_EDGES, false, &moves, cancel)
+    });
+}
+
+fn second_block_search(cancel: Option<&AtomicBool>) -> Option<&'static PieceSearch> {
+    static SEARCH: LazyTable<PieceSearch> = LazyTable::new();
+    return SEARCH.get(cancel, || {
+        let moves = PieceSearch::turns_of(&[Layer::R, Layer::Rw, Layer::U, Layer::M]);
+        PieceSearch::build(&SECOND_BLOCK_CORNERS, &SECOND_BLOCK_EDGES, false, &moves, cancel)
+    });
+}
+
+fn first_block(solver: &mut StageSolver) -> Option<()> {
+    let algorithm = first_block_search(solver.cancel)?.solve(&solver.cube)?;
+    let explanation = format!(
+        "Build a 1x2x3 block on the {} side, resting on the {} face, in {} moves, the fewest it can be done in.",
+        solver.colour_name(L),
+        solver.colour_name(D),
+        algorithm.len()
+    );
+    solver.push_stage("First block", &explanation, algorithm);
+    return Some(());
+}
+
+// The block is found with the centres where they were after the first block, then M turns
+// bring the U centre home so the later stages can judge pieces by the centres again
+fn second_block(solver: &mut StageSolver) -> Option<()> {
+    let up = solver.cube.get_faces()[U][4];
+    let mut algorithm = second_block_search(solver.cancel)?.solve(&solver.cube)?;
+    let block_moves = algorithm.len();
+    let mut check = solver.cube;
+    check.apply_algorithm(&algorithm);
+    let mut turns = 0;
+    while check.get_faces()[U][4] != up && turns < 3 {
+        check.apply_algorithm(&Algorithm::parse("M").unwrap());
+        turns += 1;
+    }
+    if turns > 0 {
+        algorithm.push(Move::new(Layer::M, turns));
+    }
+    let explanation = format!(
+        "Build the matching block on the right with R, r, U and M alone so the first block stays solved, in {} moves{}.",
+        block_moves,
+        if algorithm.len() > block_moves { ", then turn M to put the centres back" } else { "" }
+    );
+    solver.push_stage("Second block", &explanation, algorithm);
+    return Some(());
+}
+
+fn corners_oriented(cube: &RubixCube) -> bool {
+    let faces = cube.get_faces();
+    return [0, 2, 6, 8].iter().all(|&i| faces[U][i] == faces[U][4]);
+}
+
+fn corners_permuted(cube: &RubixCube) -> Option<u8> {
+    return library::aligning_auf(cube, |aligned| (0..4).all(|corner| cubies::corner_solved(aligned, corner)));
+}
+
+fn cmll(solver: &mut StageSolver) -> Option<()> {
+    let library = solver.library;
+    let mut algorithm = Algorithm::default();
+    let mut steps = Vec::new();
+    if !corners_oriented(&solver.cube) {
+        let matches = library.algorithms_reaching(Goal::Oll, &solver.cube, |cube| corners_oriented(cube).then_some(0));
+        let found = matches.into_iter().min_by_key(match_cost)?;
+        algorithm = algorithm.concat(&solver.play_match(&found, 0));
+        steps.push(format!("orient the corners with the {} algorithm", found.entry.case));
+    }
+    match corners_permuted(&solver.cube) {
+        Some(0) => {}
+        Some(auf) => {
+            let aligned = Algorithm::new(vec![Move::new(Layer::U, auf)]);
+            solver.cube.apply_algorithm(&aligned);
+            algorithm = algorithm.concat(&aligned);
+        }
+        None => {
+            let matches = library.algorithms_reaching(Goal::Pll, &solver.cube, corners_permuted);
+            let found = matches.into_iter().min_by_key(match_cost)?;
+            algorithm = algorithm.concat(&solver.play_match(&found, 0));
+            steps.push(format!("swap them into place with the {} perm", found.entry.case));
+        }
+    }
+    let explanation = match steps.len() {
+        0 => String::from("CMLL skip: the corners only need lining up."),
+        _ => {
+            let mut explanation = steps.join(", then ");
+            explanation[..1].make_ascii_uppercase();
+            explanation + ". Both blocks are left as they were."
+        }
+    };
+    solver.record("CMLL", &explanation, algorithm);
+    return Some(());
+}
+
+// Every sticker the colour of its face's centre, wherever the centres are
+fn solid(cube: &RubixCube) -> bool {
+    return cube.get_faces().iter().all(|face| face.iter().all(|&sticker| sticker == face[4]));
+}
+
+// The usual three steps, each a short search: orient the six edges so each has its U or D
+// sticker on U or D with the U and D centres there too, place UL and UR under the solved
+// corners, then finish the M slice with M and U2
+fn last_six_edges(solver: &mut StageSolver) -> Option<()> {
+    let faces = solver.cube.get_faces();
+    let (up, down) = (faces[U][4], faces[D][4]);
+    let oriented = move |cube: &RubixCube| {
+        let faces = cube.get_faces();
+        let on_axis = |colour| colour == up || colour == down;
+        return on_axis(faces[U][4])
+            && LSE_EDGES.iter().all(|&edge| {
+                let (face, index) = cubies::EDGE_FACELETS[edge][0];
+                on_axis(faces[face][index])
+            });
+    };
+    let sides_placed = move |cube: &RubixCube| {
+        let faces = cube.get_faces();
+        return oriented(cube)
+            && [L, R].iter().all(|&side| faces[side][..3].iter().all(|&sticker| sticker == faces[side][4]))
+            && [0, 2, 3, 5, 6, 8].iter().all(|&i| faces[U][i] == up);
+    };
+    let m_and_u = PieceSearch::turns_of(&[Layer::M, Layer::U]);
+    let m_and_u2 = [Move::new(Layer::M, 1), Move::new(Layer::M, 2), Move::new(Layer::M, 3), Move::new(Layer::U, 2)];
+    let mut cube = solver.cube;
+    let mut steps = Vec::new();
+    steps.push(piece_search::search(&cube, &m_and_u, LSE_STEP_DEPTH, oriented)?);
+    cube.apply_algorithm(&steps[0]);
+    steps.push(piece_search::search(&cube, &m_and_u, LSE_STEP_DEPTH, sides_placed)?);
+    cube.apply_algorithm(&steps[1]);
+    steps.push(piece_search::search(&cube, &m_and_u2, LSE_STEP_DEPTH, solid)?);
+    let explanation = format!(
+        "Orient the six edges in {} moves, place UL and UR in {}, then solve the M slice in {}, all with M and U.",
+        steps[0].len(),
+        steps[1].len(),
+        steps[2].len()
+    );
+    solver.push_stage("LSE", &explanation, steps[0].concat(&steps[1]).concat(&steps[2]));
+    return Some(());
+}
+
+// Solves any valid cube by Roux with the blocks on the L (F2) and R (F4) faces, resting on
+// D (F5). Fails if the cube can't be solved or the library lacks a CMLL case.
+pub fn solve_roux(cube: &RubixCube, library: &AlgorithmLibrary, cancel: Option<&AtomicBool>) -> Result<Solution, String> {
+    let mut solver = StageSolver::new(cube, library, "Roux", cancel);
+    first_block(&mut solver).ok_or("first block could not be solved")?;
+    second_block(&mut solver).ok_or("second block could not be solved")?;
+    cmll(&mut solver).ok_or("no library algorithms for this CMLL case")?;
+    last_six_edges(&mut solver).ok_or("last six edges could not be solved")?;
+    return solver.finish();
+}
@@ -3,14 +3,17 @@
 use rand::Rng;
 use std::fmt::{self, Debug};
 use std::time::Instant;
-use rayon::prelude::*;
+
+use crate::error::CubeError;
+use crate::parallel_search::ParallelSearch;
+use crate::scheme::ColourScheme;
 
 static MOVED_BY_3_UP : [u8; 9] =   [3, 4, 5, 9, 10, 11, 15, 16, 17];
-static MOVED_BY_3_DOWN : [u8; 9] = [0, 1, 2, 6,  7,  8, 12, 13, 14];
 
 #[derive(Clone)]
 #[derive(Copy)]
 #[derive(PartialEq)]
+#[derive(Eq, Hash, PartialOrd, Ord)]
 pub enum Colour{
     White = 0,
     Yellow = 1,
@@ -32,6 +35,46 @@ impl Debug for Colour {
         }
     }
 }
+impl Colour {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::White => "white",
+            Self::Yellow => "yellow",
+            Self::Red => "red",
+            Self::Green => "green",
+            Self::Orange => "orange",
+            Self::Blue => "blue",
+        }
+    }
+
+    // The usual sticker shades, as red, green and blue
+    pub fn rgb(&self) -> (u8, u8, u8) {
+        match self {
+            Self::White => (255, 255, 255),
+            Self::Yellow => (255, 213, 0),
+            Self::Red => (183, 18, 52),
+            Self::Green => (0, 155, 72),
+            Self::Orange => (255, 88, 0),
+            Self::Blue => (0, 70, 173),
+        }
+    }
+
+    pub const ALL: [Colour; 6] = [Colour::White, Colour::Yellow, Colour::Red, Colour::Green, Colour::Orange, Colour::Blue];
+
+    // The letter Display writes
+    pub fn from_letter(letter: char) -> Option<Colour> {
+        match letter {
+            'W' => Some(Self::White),
+            'Y' => Some(Self::Yellow),
+            'R' => Some(Self::Red),
+            'G' => Some(Self::Green),
+            'O' => Some(Self::Orange),
+            'B' => Some(Self::Blue),
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Display for Colour {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -45,17 +88,72 @@ impl fmt::Display for Colour {
     }
 }
 
-#[derive(Clone)]
+// Just the stickers, so a cube is a small Copy value; the turns that led to it are kept in a
+// History by whoever needs them
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct RubixCube{
     faces : [[Colour; 9]; 6],
-    past_moves : Vec<u8>,
-    solving_route : Vec<u8>
+}
+
+// Turn codes made on a cube, oldest first. Searches keep one as their current path.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct History{
+    moves : Vec<u8>,
+}
+
+impl History{
+    pub fn new() -> History{
+        return History{moves : Vec::new()};
+    }
+
+    pub fn moves(&self) -> &[u8]{
+        return &self.moves;
+    }
+
+    pub fn last(&self) -> Option<u8>{
+        return self.moves.last().copied();
+    }
+
+    pub fn push(&mut self, code: u8){
+        self.moves.push(code);
+    }
+
+    pub fn pop(&mut self) -> Option<u8>{
+        return self.moves.pop();
+    }
+
+    pub fn len(&self) -> usize{
+        return self.moves.len();
+    }
+
+    pub fn is_empty(&self) -> bool{
+        return self.moves.is_empty();
+    }
+
+    // Whether next would just undo the last turn
+    pub fn undoes_last(&self, next: u8) -> bool{
+        return self.last().is_some_and(|last| last == inverse_of(next));
+    }
+}
+
+impl fmt::Display for History{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, code) in self.moves.iter().enumerate(){
+            if i > 0{
+                write!(f, " ")?;
+            }
+            write!(f, "{}", code)?;
+        }
+        Ok(())
+    }
+}
+
+fn inverse_of(code: u8) -> u8{
+    return if MOVED_BY_3_UP.contains(&code){code - 3} else {code + 3};
 }
 
 impl RubixCube{
     pub fn get_faces(&self) ->  [[Colour; 9]; 6]{return self.faces;}
-    pub fn get_past_moves(&self) ->  Vec<u8>{return self.past_moves.clone();}
-    pub fn get_solving_route(&self) -> Vec<u8>{return self.solving_route.clone();}
     //    F1
     // F2 F3 F4
     //    F5
@@ -66,39 +164,56 @@ impl RubixCube{
     // Operates looking at face 3
     pub fn create_solved_rubix() -> RubixCube
     {
-        let face3 = [Colour::White, Colour::White, Colour::White, Colour::White, Colour::White, Colour::White, Colour::White, Colour::White, Colour::White];
-        let face6 = [Colour::Yellow, Colour::Yellow, Colour::Yellow, Colour::Yellow, Colour::Yellow, Colour::Yellow, Colour::Yellow, Colour::Yellow, Colour::Yellow];
-
-        let face2 = [Colour::Red, Colour::Red, Colour::Red, Colour::Red, Colour::Red, Colour::Red, Colour::Red, Colour::Red, Colour::Red];
-        let face4 = [Colour::Orange, Colour::Orange, Colour::Orange, Colour::Orange, Colour::Orange, Colour::Orange, Colour::Orange, Colour::Orange, Colour::Orange];
-
-        let face1 = [Colour::Blue, Colour::Blue, Colour::Blue, Colour::Blue, Colour::Blue, Colour::Blue, Colour::Blue, Colour::Blue, Colour::Blue];
-        let face5 = [Colour::Green, Colour::Green, Colour::Green, Colour::Green, Colour::Green, Colour::Green, Colour::Green, Colour::Green, Colour::Green];
-        return RubixCube{faces : [face1, face2, face3, face4, face5, face6], past_moves : Vec::new(), solving_route : Vec::new()}
+        return ColourScheme::default().solved();
     }
     
     pub fn create_custom_rubix(f1 :  &[Colour; 9], f2 : &[Colour; 9], f3 : &[Colour; 9], f4 : &[Colour; 9], f5 : &[Colour; 9], f6 : &[Colour; 9]) -> RubixCube
     {
-        return RubixCube{faces : [*f1, *f2, *f3, *f4, *f5, *f6], past_moves : Vec::new(), solving_route : Vec::new()}
+        return RubixCube{faces : [*f1, *f2, *f3, *f4, *f5, *f6]}
+    }
+
+    // All 54 stickers as colour letters, face by face in the order of faces, each face read
+    // row by row as print_cube shows it
+    pub fn facelets(&self) -> String
+    {
+        return self.faces.iter().flatten().map(|colour| colour.to_string()).collect();
+    }
+
+    // Reads what facelets writes; whitespace is ignored. Needs nine stickers of each colour
+    // but does not check the cube can be solved.
+    pub fn from_facelets(text: &str) -> Result<RubixCube, CubeError>
+    {
+        let letters: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+        if letters.len() != 54{
+            return Err(CubeError::Facelets(format!("expected 54 stickers, found {}", letters.len())));
+        }
+        let mut faces = [[Colour::White; 9]; 6];
+        for (i, &letter) in letters.iter().enumerate(){
+            faces[i / 9][i % 9] = Colour::from_letter(letter).ok_or(CubeError::Facelets(format!("'{}' is not a colour letter (W Y R G O B)", letter)))?;
+        }
+        for colour in Colour::ALL{
+            let count = faces.iter().flatten().filter(|&&sticker| sticker == colour).count();
+            if count != 9{
+                return Err(CubeError::Facelets(format!("{} stickers: {} of them instead of 9", colour.name(), count)));
+            }
+        }
+        return Ok(RubixCube{faces});
     }
     
     pub fn print_cube(&self) 
     {
         print!("\n       {} {} {}\n       {} {} {}\n       {} {} {}\n\n", &self.faces[0][0], &self.faces[0][1], &self.faces[0][2], &self.faces[0][3], &self.faces[0][4], &self.faces[0][5], &self.faces[0][6], &self.faces[0][7], &self.faces[0][8]);
 
-        print!("{} {} {}  {} {} {}  {} {} {}\n", &self.faces[1][0], &self.faces[1][1], &self.faces[1][2], &self.faces[2][0], &self.faces[2][1], &self.faces[2][2], &self.faces[3][0], &self.faces[3][1], &self.faces[3][2]);
-        print!("{} {} {}  {} {} {}  {} {} {}\n", &self.faces[1][3], &self.faces[1][4], &self.faces[1][5], &self.faces[2][3], &self.faces[2][4], &self.faces[2][5], &self.faces[3][3], &self.faces[3][4], &self.faces[3][5]);
+        println!("{} {} {}  {} {} {}  {} {} {}", &self.faces[1][0], &self.faces[1][1], &self.faces[1][2], &self.faces[2][0], &self.faces[2][1], &self.faces[2][2], &self.faces[3][0], &self.faces[3][1], &self.faces[3][2]);
+        println!("{} {} {}  {} {} {}  {} {} {}", &self.faces[1][3], &self.faces[1][4], &self.faces[1][5], &self.faces[2][3], &self.faces[2][4], &self.faces[2][5], &self.faces[3][3], &self.faces[3][4], &self.faces[3][5]);
         print!("{} {} {}  {} {} {}  {} {} {}\n\n", &self.faces[1][6], &self.faces[1][7], &self.faces[1][8], &self.faces[2][6], &self.faces[2][7], &self.faces[2][8], &self.faces[3][6], &self.faces[3][7], &self.faces[3][8]);
 
         print!("       {} {} {}\n       {} {} {}\n       {} {} {}\n\n", &self.faces[4][0], &self.faces[4][1], &self.faces[4][2], &self.faces[4][3], &self.faces[4][4], &self.faces[4][5], &self.faces[4][6], &self.faces[4][7], &self.faces[4][8]);
         print!("       {} {} {}\n       {} {} {}\n       {} {} {}\n\n", &self.faces[5][0], &self.faces[5][1], &self.faces[5][2], &self.faces[5][3], &self.faces[5][4], &self.faces[5][5], &self.faces[5][6], &self.faces[5][7], &self.faces[5][8]);
-        println!("");
-        for val in &self.past_moves{
-            print!("{} ", val);
-        }
-        print!("\n");
+        println!();
     }
 
+    // Panics on a code over 17; try_turn returns an error instead
     pub fn turn_cube(&mut self, &col_row: &u8)
     {
         // Col_row : which row/column is moving
@@ -130,19 +245,17 @@ impl RubixCube{
         assert!(self.faces[4].len() >= 9);
         assert!(self.faces[5].len() >= 9); 
 
-        self.past_moves.push(col_row);
-
         match col_row {
             0  => {
                 let tmp = [self.faces[1][0], self.faces[1][1], self.faces[1][2]];
 
-                self.faces[1][0] = self.faces[5][6];
+                self.faces[1][0] = self.faces[5][8];
                 self.faces[1][1] = self.faces[5][7];
-                self.faces[1][2] = self.faces[5][8];
+                self.faces[1][2] = self.faces[5][6];
 
-                self.faces[5][6] = self.faces[3][0];
+                self.faces[5][8] = self.faces[3][0];
                 self.faces[5][7] = self.faces[3][1];
-                self.faces[5][8] = self.faces[3][2];
+                self.faces[5][6] = self.faces[3][2];
 
                 self.faces[3][0] = self.faces[2][0];
                 self.faces[3][1] = self.faces[2][1];
@@ -194,13 +307,13 @@ impl RubixCube{
             2  => {
                 let tmp = [self.faces[1][6], self.faces[1][7], self.faces[1][8]];
 
-                self.faces[1][6] = self.faces[5][0];
+                self.faces[1][6] = self.faces[5][2];
                 self.faces[1][7] = self.faces[5][1];
-                self.faces[1][8] = self.faces[5][2];
+                self.faces[1][8] = self.faces[5][0];
 
-                self.faces[5][0] = self.faces[3][6];
+                self.faces[5][2] = self.faces[3][6];
                 self.faces[5][1] = self.faces[3][7];
-                self.faces[5][2] = self.faces[3][8];
+                self.faces[5][0] = self.faces[3][8];
 
                 self.faces[3][6] = self.faces[2][6];
                 self.faces[3][7] = self.faces[2][7];
@@ -229,11 +342,11 @@ impl RubixCube{
                 return;
             }
             3  => {
-                let tmp = [self.faces[5][6], self.faces[5][7], self.faces[5][8]];
+                let tmp = [self.faces[5][8], self.faces[5][7], self.faces[5][6]];
 
-                self.faces[5][6] = self.faces[1][0];
+                self.faces[5][8] = self.faces[1][0];
                 self.faces[5][7] = self.faces[1][1];
-                self.faces[5][8] = self.faces[1][2];
+                self.faces[5][6] = self.faces[1][2];
 
                 self.faces[1][0] = self.faces[2][0];
                 self.faces[1][1] = self.faces[2][1];
@@ -297,13 +410,13 @@ impl RubixCube{
                 self.faces[2][7] = self.faces[3][7];
                 self.faces[2][8] = self.faces[3][8];
 
-                self.faces[3][6] = self.faces[5][0];
+                self.faces[3][6] = self.faces[5][2];
                 self.faces[3][7] = self.faces[5][1];
-                self.faces[3][8] = self.faces[5][2];
+                self.faces[3][8] = self.faces[5][0];
 
-                self.faces[5][0] = tmp[0];
+                self.faces[5][2] = tmp[0];
                 self.faces[5][1] = tmp[1];
-                self.faces[5][2] = tmp[2];
+                self.faces[5][0] = tmp[2];
 
                 //Clockwise
                 let mut tmp = self.faces[4][0];
@@ -584,17 +697,17 @@ impl RubixCube{
                 //Clockwise
                 let mut tmp = self.faces[5][0];
 
-                self.faces[5][0] = self.faces[5][2];
-                self.faces[5][2] = self.faces[5][8];
-                self.faces[5][8] = self.faces[5][6];
-                self.faces[5][6] = tmp;
+                self.faces[5][0] = self.faces[5][6];
+                self.faces[5][6] = self.faces[5][8];
+                self.faces[5][8] = self.faces[5][2];
+                self.faces[5][2] = tmp;
 
                 tmp = self.faces[5][1];
 
-                self.faces[5][1] = self.faces[5][5];
-                self.faces[5][5] = self.faces[5][7];
-                self.faces[5][7] = self.faces[5][3];
-                self.faces[5][3] = tmp;
+                self.faces[5][1] = self.faces[5][3];
+                self.faces[5][3] = self.faces[5][7];
+                self.faces[5][7] = self.faces[5][5];
+                self.faces[5][5] = tmp;
                 return;
             }
             15 => {
@@ -674,17 +787,17 @@ impl RubixCube{
                 //Clockwise
                 let mut tmp = self.faces[5][0];
 
-                self.faces[5][0] = self.faces[5][6];
-                self.faces[5][6] = self.faces[5][8];
-                self.faces[5][8] = self.faces[5][2];
-                self.faces[5][2] = tmp;
+                self.faces[5][0] = self.faces[5][2];
+                self.faces[5][2] = self.faces[5][8];
+                self.faces[5][8] = self.faces[5][6];
+                self.faces[5][6] = tmp;
 
                 tmp = self.faces[5][1];
 
-                self.faces[5][1] = self.faces[5][3];
-                self.faces[5][3] = self.faces[5][7];
-                self.faces[5][7] = self.faces[5][5];
-                self.faces[5][5] = tmp;
+                self.faces[5][1] = self.faces[5][5];
+                self.faces[5][5] = self.faces[5][7];
+                self.faces[5][7] = self.faces[5][3];
+                self.faces[5][3] = tmp;
                 return;
             }
             _default => {
@@ -693,7 +806,7 @@ impl RubixCube{
         }
     }
 
-    pub fn is_solved(&mut self) -> bool
+    pub fn is_solved(&self) -> bool
     {
         for face in 0..5{
             for piece in 1..8{
@@ -705,52 +818,109 @@ impl RubixCube{
         return true;
     }
 
-    pub fn undo_turn(&mut self)
+    // Turns the cube and notes the turn in history
+    pub fn turn_recorded(&mut self, code: u8, history: &mut History)
+    {
+        self.turn_cube(&code);
+        history.push(code);
+    }
+
+    // Takes back the last turn in history, returning it; does nothing if there is none or
+    // its last code isn't a turn
+    pub fn undo_turn(&mut self, history: &mut History) -> Option<u8>
+    {
+        return self.try_undo_turn(history).ok();
+    }
+
+    pub fn try_turn(&mut self, code: u8) -> Result<(), CubeError>
     {
-        let move_to_undo = self.past_moves.pop().expect("should be u8");
+        if code >= 18{
+            return Err(CubeError::InvalidTurn(code));
+        }
+        self.turn_cube(&code);
+        return Ok(());
+    }
 
-        self.turn_cube(&(if MOVED_BY_3_UP.contains(&move_to_undo){move_to_undo - 3} else {move_to_undo + 3} ));
+    pub fn try_turn_recorded(&mut self, code: u8, history: &mut History) -> Result<(), CubeError>
+    {
+        self.try_turn(code)?;
+        history.push(code);
+        return Ok(());
+    }
 
-        self.past_moves.pop();
+    // undo_turn that tells an empty history apart, and leaves history alone if its last
+    // code isn't a turn
+    pub fn try_undo_turn(&mut self, history: &mut History) -> Result<u8, CubeError>
+    {
+        let move_to_undo = history.last().ok_or(CubeError::NothingToUndo)?;
+        if move_to_undo >= 18{
+            return Err(CubeError::InvalidTurn(move_to_undo));
+        }
+        history.pop();
+        self.turn_cube(&inverse_of(move_to_undo));
+        return Ok(move_to_undo);
     }
 
-    pub fn make_random_moves(&mut self, number_of_moves: u8)
+    // Returns the turns made
+    pub fn make_random_moves(&mut self, number_of_moves: u8) -> History
     {
         let mut rng = rand::thread_rng();
+        let mut history = History::new();
         for _ in 0..number_of_moves{
             let n1 = rng.gen_range(0..18);
-            self.turn_cube(&n1);
+            self.turn_recorded(n1, &mut history);
         }
+        return history;
     }
     
-    pub fn search_astar(&mut self){
+    // Returns the solving route, if one is found
+    pub fn search_astar(&self) -> Option<History>{
         let mut threshold = self.heuristic();
         let now = Instant::now();
         loop{
-            let (result, new_threshold) = self.astar(0, threshold);
+            let mut cube = *self;
+            let mut path = History::new();
+            let (result, new_threshold) = cube.astar(&mut path, 0, threshold);
             let elapsed_time = now.elapsed();
             if result{
                 println!("found solution at threshold: {}; Elapsed time: {:?}.{:?}s", new_threshold+1, elapsed_time.as_secs(), elapsed_time.as_millis());
-                for v in self.get_solving_route(){
-                    print!("{} ", v);
-                }
-                break;
+                println!("{}", path);
+                return Some(path);
             }
-            else{
-                
-                threshold = new_threshold as u8;
-    
-                println!("found no solution at threshold: {}; Elapsed time: {:?}.{:?}s", threshold, elapsed_time.as_secs(), elapsed_time.as_millis());
+            if new_threshold == 255{
+                println!("no solution exists; Elapsed time: {:?}.{:?}s", elapsed_time.as_secs(), elapsed_time.as_millis());
+                return None;
             }
+            threshold = new_threshold;
+
+            println!("found no solution at threshold: {}; Elapsed time: {:?}.{:?}s", threshold, elapsed_time.as_secs(), elapsed_time.as_millis());
         }
     }
     
-    pub fn astar(&mut self, depth : u8, threshold : u8) -> (bool, u8){
+    // search_astar without the printing, giving up past max_depth moves. A cube that can't be
+    // solved is turned away at once rather than searched to the limit.
+    pub fn try_search_astar(&self, max_depth: u8) -> Result<History, CubeError>{
+        self.check_solvable()?;
+        let mut threshold = self.heuristic();
+        while threshold <= max_depth{
+            let mut cube = *self;
+            let mut path = History::new();
+            let (result, new_threshold) = cube.astar(&mut path, 0, threshold);
+            if result{
+                return Ok(path);
+            }
+            threshold = new_threshold;
+        }
+        return Err(CubeError::NoSolution(max_depth as usize));
+    }
+
+    // path holds the turns made so far and is left holding the route when one is found
+    pub fn astar(&mut self, path: &mut History, depth : u8, threshold : u8) -> (bool, u8){
         if self.is_solved(){
             return (true, threshold);
         }
 
-        let estimated_total_path_length = depth + self.heuristic();
+        let estimated_total_path_length = depth.saturating_add(self.heuristic());
 
         if estimated_total_path_length > threshold{
             return (false, estimated_total_path_length);
@@ -758,18 +928,17 @@ impl RubixCube{
 
         let mut min_threshold_exceeded: u8 = 255;
         for next_move in 0..18{
-            if (self.past_moves[self.past_moves.len()-1] == 3 + next_move && MOVED_BY_3_UP.contains(&next_move)) || (self.past_moves[self.past_moves.len()-1] + 3 == next_move && MOVED_BY_3_DOWN.contains(&next_move)){
+            if path.undoes_last(next_move){
                 continue;
             }
 
-            self.turn_cube(&next_move);
+            self.turn_recorded(next_move, path);
 
-            let (result, new_threshold) = self.astar(depth+1, threshold);
-            self.undo_turn();
+            let (result, new_threshold) = self.astar(path, depth+1, threshold);
             if result{
-                self.solving_route.push(next_move);
                 return (result, new_threshold);
             }
+            self.undo_turn(path);
             if new_threshold < min_threshold_exceeded{
                 min_threshold_exceeded = new_threshold;
             }
@@ -787,86 +956,26 @@ impl RubixCube{
                 total[val as usize] += 1;
             }
         }
-        let max_counts = total.iter().max().expect("failed");
-        return 9 - max_counts;
+        let max_counts = total.iter().max().copied().unwrap_or(9);
+        return 9u8.saturating_sub(max_counts);
     }
 
 }
 
 
-pub(crate) trait Iddfs {
-    fn thread_search_iddfs(&mut self, depth_to_search: u8);
- 
-    fn thread_iddfs(&mut self, depth: u8) -> (bool, Vec<u8>);
+pub trait Iddfs {
+    fn thread_search_iddfs(&self, depth_to_search: u8) -> Result<History, String>;
  }
 
 impl Iddfs for  RubixCube{
-    fn thread_search_iddfs(&mut self, depth_to_search : u8) {
-        //fine up to depth 6 but beyond that begins to take too long
-
-        //depth 7 -> 100 seconds
-        //depth 8 -> ~40000 seconds (expected)
-
-        for depth in 0..depth_to_search{
-            let now = Instant::now();
-        
-            // Split the work among threads
-            let solutions: Vec<(bool, Vec<u8>)> = (0..18)
-                .into_par_iter()
-                .map(|init_move| {
-                    let mut v = self.clone();
-                    v.turn_cube(&init_move);
-                    v.thread_iddfs(depth)
-                })
-                .collect();
-            
-            for (solution, path) in solutions {
-                if solution{
-                    println!("found solution at depth: {}; Elapsed time: {:?}", depth, now.elapsed());
-                    for v in path {
-                       print!("{} ", v);
-                    }
-                    return;
-                }
-            };
-            println!("found no solution at depth: {}; Elapsed time: {:?}", depth, now.elapsed());
+    // Shortest face turn solution of up to depth_to_search moves, found by ParallelSearch, as
+    // turn codes
+    fn thread_search_iddfs(&self, depth_to_search : u8) -> Result<History, String> {
+        let algorithm = ParallelSearch::new(3, depth_to_search as usize).solve(self)?;
+        let mut path = History::new();
+        for code in algorithm.codes(){
+            path.push(code);
         }
+        return Ok(path);
     }
-
-    fn thread_iddfs(&mut self, depth: u8) -> (bool, Vec<u8>)
-    {
-
-        if depth <= 0{
-            return (self.is_solved(), Vec::new());
-        }
-
-        for next_move in MOVED_BY_3_UP{
-            if self.past_moves[self.past_moves.len()-1] + 3 == next_move{
-                continue;
-            };
-            
-
-            self.turn_cube(&next_move);
-            let (solved, mut path) = self.thread_iddfs(depth-1);
-            if solved{
-                path.push(next_move);
-                return (true, path);
-            }
-            self.undo_turn();
-        };
-        for next_move in MOVED_BY_3_DOWN{
-            if self.past_moves[self.past_moves.len()-1] == 3 + next_move{
-                continue;
-            };
-            
-            self.turn_cube(&next_move);
-            let (solved, mut path) = self.thread_iddfs(depth-1);
-            if solved{
-                path.push(next_move);
-                return (true, path);
-            }
-            self.undo_turn();
-        };
-        return (false, Vec::new());
-    }  
-}
\ No newline at end of file
+}
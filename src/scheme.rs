@@ -0,0 +1,168 @@
+use crate::algorithm::{Layer, Move};
+use crate::diagram::{parse_shades, Rgb};
+use crate::error::CubeError;
+use crate::rubix_cube::{Colour, RubixCube};
+
+// Where the colours go on a solved cube and how each one looks. The solvers and check_solvable
+// judge pieces by the centres, so they work in any scheme already; a scheme adds the check
+// that a cube's centres are the ones it should have, held some way up, and a solved cube to
+// start from.
+//
+// Turning the whole cube keeps opposite colours opposite and keeps their hand, so the centres
+// of a cube in this scheme are always one of its 24 orientations: a mirror image, or a cube
+// with two colours swapped, isn't.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ColourScheme {
+    // The centre of each face, in the order of faces: U L F R D B
+    pub centres: [Colour; 6],
+    // How each colour is drawn, indexed by Colour as usize
+    pub shades: [Rgb; 6],
+}
+
+// The face across the cube from each, in the order of faces
+const OPPOSITE: [usize; 6] = [4, 3, 5, 1, 0, 2];
+
+const FACE_NAMES: [&str; 6] = ["U", "L", "F", "R", "D", "B"];
+
+impl Default for ColourScheme {
+    fn default() -> ColourScheme {
+        return ColourScheme::rubix();
+    }
+}
+
+impl ColourScheme {
+    pub const NAMES: [&'static str; 3] = ["rubix", "western", "japanese"];
+
+    // create_solved_rubix's: blue U, white F, orange R. The western opposites, but the mirror
+    // image of a western cube
+    pub fn rubix() -> ColourScheme {
+        return ColourScheme::with_centres([Colour::Blue, Colour::Red, Colour::White, Colour::Orange, Colour::Green, Colour::Yellow]);
+    }
+
+    // White U, green F, red R: white opposite yellow, red opposite orange, blue opposite green
+    pub fn western() -> ColourScheme {
+        return ColourScheme::with_centres([Colour::White, Colour::Orange, Colour::Green, Colour::Red, Colour::Yellow, Colour::Blue]);
+    }
+
+    // The western scheme with blue and yellow swapped: white opposite blue, yellow opposite green
+    pub fn japanese() -> ColourScheme {
+        return ColourScheme::with_centres([Colour::White, Colour::Orange, Colour::Green, Colour::Red, Colour::Blue, Colour::Yellow]);
+    }
+
+    // Centres in the order of faces, drawn in the usual shades; validate checks them
+    pub fn with_centres(centres: [Colour; 6]) -> ColourScheme {
+        return ColourScheme { centres, shades: Colour::ALL.map(|colour| colour.rgb()) };
+    }
+
+    // A name from NAMES, or six centre letters in the order of faces, e.g. "BRWOGY"
+    pub fn parse(text: &str) -> Result<ColourScheme, CubeError> {
+        match text.trim().to_lowercase().as_str() {
+            "rubix" => return Ok(ColourScheme::rubix()),
+            "western" => return Ok(ColourScheme::western()),
+            "japanese" => return Ok(ColourScheme::japanese()),
+            _ => {}
+        }
+        let letters: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+        if letters.len() != 6 {
+            return Err(CubeError::Scheme(format!("'{}' is not {} or six centre letters in the order U L F R D B", text.trim(), ColourScheme::NAMES.join(", "))));
+        }
+        let mut centres = [Colour::White; 6];
+        for (face, &letter) in letters.iter().enumerate() {
+            centres[face] = Colour::from_letter(letter).ok_or(CubeError::Scheme(format!("'{}' is not a colour letter (W Y R G O B)", letter)))?;
+        }
+        let scheme = ColourScheme::with_centres(centres);
+        scheme.validate()?;
+        return Ok(scheme);
+    }
+
+    pub fn with_shade(mut self, colour: Colour, rgb: Rgb) -> ColourScheme {
+        self.shades[colour as usize] = rgb;
+        return self;
+    }
+
+    // Some shades changed, e.g. "Y=#ffff00,O=#ff8000"
+    pub fn with_shades(mut self, text: &str) -> Result<ColourScheme, CubeError> {
+        self.shades = parse_shades(text, self.shades).map_err(CubeError::Scheme)?;
+        return Ok(self);
+    }
+
+    pub fn rgb(&self, colour: Colour) -> Rgb {
+        return self.shades[colour as usize];
+    }
+
+    // Each colour on one face, which makes each the opposite of just one other
+    pub fn validate(&self) -> Result<(), CubeError> {
+        for colour in Colour::ALL {
+            let faces: Vec<&str> = (0..6).filter(|&face| self.centres[face] == colour).map(|face| FACE_NAMES[face]).collect();
+            match faces.len() {
+                0 => return Err(CubeError::Scheme(format!("no face is {}", colour.name()))),
+                1 => {}
+                _ => return Err(CubeError::Scheme(format!("{} is on {}", colour.name(), faces.join(" and ")))),
+            }
+        }
+        return Ok(());
+    }
+
+    // The colour across the cube from colour
+    pub fn opposite(&self, colour: Colour) -> Option<Colour> {
+        let face = self.centres.iter().position(|&centre| centre == colour)?;
+        return Some(self.centres[OPPOSITE[face]]);
+    }
+
+    pub fn solved(&self) -> RubixCube {
+        let faces = self.centres.map(|centre| [centre; 9]);
+        return RubixCube::create_custom_rubix(&faces[0], &faces[1], &faces[2], &faces[3], &faces[4], &faces[5]);
+    }
+
+    // The centres of the solved cube held each of the 24 ways up
+    pub fn orientations(&self) -> Vec<[Colour; 6]> {
+        let centres = |cube: &RubixCube| cube.get_faces().map(|face| face[4]);
+        let mut cubes = vec![self.solved()];
+        let mut seen = vec![centres(&cubes[0])];
+        while let Some(cube) = cubes.pop() {
+            for layer in [Layer::X, Layer::Y] {
+                let mut turned = cube;
+                turned.apply_move(Move::new(layer, 1));
+                if !seen.contains(&centres(&turned)) {
+                    seen.push(centres(&turned));
+                    cubes.push(turned);
+                }
+            }
+        }
+        return seen;
+    }
+
+    // Whether cube's centres are this scheme's, turned some way up
+    pub fn check_centres(&self, cube: &RubixCube) -> Result<(), CubeError> {
+        self.validate()?;
+        let centres = cube.get_faces().map(|face| face[4]);
+        if self.orientations().contains(&centres) {
+            return Ok(());
+        }
+        if let Err(CubeError::Scheme(reason)) = ColourScheme::with_centres(centres).validate() {
+            return Err(CubeError::Scheme(format!("the cube's centres: {}", reason)));
+        }
+        for face in 0..6 {
+            let colour = centres[face];
+            let across = centres[OPPOSITE[face]];
+            if self.opposite(colour) != Some(across) {
+                let expected = self.opposite(colour).map_or("", |opposite| opposite.name());
+                return Err(CubeError::Scheme(format!("the {} centre is opposite {}, not {}", colour.name(), across.name(), expected)));
+            }
+        }
+        return Err(CubeError::Scheme(String::from("the centres are a mirror image of the scheme")));
+    }
+
+    // Reads facelets as RubixCube::from_facelets does, then checks the centres are this scheme's
+    pub fn from_facelets(&self, text: &str) -> Result<RubixCube, CubeError> {
+        let cube = RubixCube::from_facelets(text)?;
+        self.check_centres(&cube)?;
+        return Ok(cube);
+    }
+
+    // check_solvable, in this scheme
+    pub fn check_solvable(&self, cube: &RubixCube) -> Result<(), CubeError> {
+        self.check_centres(cube)?;
+        return cube.check_solvable();
+    }
+}
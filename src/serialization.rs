@@ -0,0 +1,195 @@
+use std::time::Duration;
+
+use serde::de::{self, Deserializer};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+
+use crate::algorithm::{Algorithm, Move};
+use crate::batch::CaseResult;
+use crate::rubix_cube::{Colour, RubixCube};
+use crate::solution::{SolveReport, Solution, Stage};
+
+// serde for the types passed between services, written to match schema/rubix.schema.json:
+//
+//   Colour       "W"                           its letter
+//   RubixCube    "BBBBBBBBB...YYY"             the 54 facelets letters, stickers only
+//   Move         "R'"                          notation
+//   Algorithm    ["R", "U", "R'", "U'"]        a list of moves
+//   Stage        {"name", "explanation", "algorithm", "move_count", "subgroup" (Thistlethwaite only)}
+//   Solution     {"method", "algorithm", "move_count", "stages"}
+//   SolveReport  {"facelets", "solution", "nodes", "time_us"}
+//   CaseResult   {"line", "input", "report"} or {"line", "input", "facelets"?, "error"}
+//
+// move_count and a solution's algorithm are worked out from the stages on the way out and
+// ignored on the way in. A CaseResult is only written.
+
+impl Serialize for Colour {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        return serializer.collect_str(self);
+    }
+}
+
+impl<'de> Deserialize<'de> for Colour {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Colour, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        let mut letters = text.chars();
+        return match (letters.next(), letters.next()) {
+            (Some(letter), None) => Colour::from_letter(letter).ok_or_else(|| de::Error::custom(format!("'{}' is not a colour letter", text))),
+            _ => Err(de::Error::custom(format!("'{}' is not a colour letter", text))),
+        };
+    }
+}
+
+impl Serialize for RubixCube {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        return serializer.serialize_str(&self.facelets());
+    }
+}
+
+impl<'de> Deserialize<'de> for RubixCube {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<RubixCube, D::Error> {
+        return RubixCube::from_facelets(&String::deserialize(deserializer)?).map_err(de::Error::custom);
+    }
+}
+
+impl Serialize for Move {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        return serializer.collect_str(self);
+    }
+}
+
+impl<'de> Deserialize<'de> for Move {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Move, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        let algorithm = Algorithm::parse(&text).map_err(de::Error::custom)?;
+        return match algorithm.moves() {
+            [single] => Ok(*single),
+            _ => Err(de::Error::custom(format!("'{}' is not a single move", text))),
+        };
+    }
+}
+
+impl Serialize for Algorithm {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        return serializer.collect_seq(self.moves());
+    }
+}
+
+impl<'de> Deserialize<'de> for Algorithm {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Algorithm, D::Error> {
+        return Ok(Algorithm::new(Vec::deserialize(deserializer)?));
+    }
+}
+
+// The JSON layouts, with the derived fields filled in or skipped
+
+#[derive(Serialize, Deserialize)]
+struct StageJson {
+    name: String,
+    explanation: String,
+    algorithm: Algorithm,
+    #[serde(default, skip_deserializing)]
+    move_count: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    subgroup: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SolutionJson {
+    method: String,
+    #[serde(default, skip_deserializing)]
+    algorithm: Algorithm,
+    #[serde(default, skip_deserializing)]
+    move_count: usize,
+    stages: Vec<Stage>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SolveReportJson {
+    facelets: RubixCube,
+    solution: Solution,
+    nodes: Option<u64>,
+    time_us: u64,
+}
+
+// A solved case has its report, which holds the facelets; a failed one has them on their own
+// if the line could be read as a cube
+#[derive(Serialize)]
+struct CaseResultJson<'a> {
+    line: usize,
+    input: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    report: Option<&'a SolveReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    facelets: Option<RubixCube>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<&'a str>,
+}
+
+impl Serialize for Stage {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let json = StageJson {
+            name: self.name.clone(),
+            explanation: self.explanation.clone(),
+            algorithm: self.algorithm.clone(),
+            move_count: self.move_count(),
+            subgroup: self.subgroup,
+        };
+        return json.serialize(serializer);
+    }
+}
+
+impl<'de> Deserialize<'de> for Stage {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Stage, D::Error> {
+        let json = StageJson::deserialize(deserializer)?;
+        return Ok(Stage { name: json.name, explanation: json.explanation, algorithm: json.algorithm, subgroup: json.subgroup });
+    }
+}
+
+impl Serialize for Solution {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let json = SolutionJson {
+            method: self.method.clone(),
+            algorithm: self.algorithm(),
+            move_count: self.move_count(),
+            stages: self.stages.clone(),
+        };
+        return json.serialize(serializer);
+    }
+}
+
+impl<'de> Deserialize<'de> for Solution {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Solution, D::Error> {
+        let json = SolutionJson::deserialize(deserializer)?;
+        return Ok(Solution { method: json.method, stages: json.stages });
+    }
+}
+
+impl Serialize for SolveReport {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let json = SolveReportJson {
+            facelets: self.cube,
+            solution: self.solution.clone(),
+            nodes: self.nodes,
+            time_us: self.time.as_micros().min(u64::MAX as u128) as u64,
+        };
+        return json.serialize(serializer);
+    }
+}
+
+impl<'de> Deserialize<'de> for SolveReport {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<SolveReport, D::Error> {
+        let json = SolveReportJson::deserialize(deserializer)?;
+        return Ok(SolveReport { cube: json.facelets, solution: json.solution, nodes: json.nodes, time: Duration::from_micros(json.time_us) });
+    }
+}
+
+impl Serialize for CaseResult {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let json = match &self.outcome {
+            Ok(report) => CaseResultJson { line: self.line, input: &self.input, report: Some(report), facelets: None, error: None },
+            Err(error) => CaseResultJson { line: self.line, input: &self.input, report: None, facelets: self.cube, error: Some(error) },
+        };
+        return json.serialize(serializer);
+    }
+}
@@ -0,0 +1,227 @@
+use rand::Rng;
+
+use crate::algorithm::{Algorithm, Layer, Move};
+use crate::rubix_cube::RubixCube;
+use crate::scheme::ColourScheme;
+
+// A cube to play with by hand, as rubix-tui drives it: moves from keys, undo and redo over
+// everything turned since the start, and a solution to step through. The start is the cube
+// it was made with, or the last scramble, which turns a solved cube in the simulator's colour
+// scheme.
+//
+// Stepping through a solution is just turning its next move, so undo and redo work on it too;
+// the solution stays up as long as the moves made follow it, and is dropped by any other.
+
+// Which key turns what
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Keymap {
+    // The move's own letter, clockwise, or anticlockwise with shift: r R, m M, y Y ...
+    Notation,
+    // The speedcubing timers' two-handed layout: i k for R R', j f for U U', h g for F F' ...
+    Speedcubing,
+}
+
+impl Keymap {
+    pub const ALL: [Keymap; 2] = [Keymap::Notation, Keymap::Speedcubing];
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            Keymap::Notation => "notation",
+            Keymap::Speedcubing => "speedcubing",
+        };
+    }
+
+    pub fn parse(name: &str) -> Option<Keymap> {
+        return Keymap::ALL.iter().find(|keymap| keymap.name() == name.to_lowercase()).copied();
+    }
+
+    pub fn next(&self) -> Keymap {
+        return match self {
+            Keymap::Notation => Keymap::Speedcubing,
+            Keymap::Speedcubing => Keymap::Notation,
+        };
+    }
+
+    pub fn move_for(&self, key: char) -> Option<Move> {
+        return match self {
+            Keymap::Notation => {
+                let layer = match key.to_ascii_lowercase() {
+                    'u' => Layer::U,
+                    'd' => Layer::D,
+                    'l' => Layer::L,
+                    'r' => Layer::R,
+                    'f' => Layer::F,
+                    'b' => Layer::B,
+                    'm' => Layer::M,
+                    'e' => Layer::E,
+                    's' => Layer::S,
+                    'x' => Layer::X,
+                    'y' => Layer::Y,
+                    'z' => Layer::Z,
+                    _ => return None,
+                };
+                Some(Move::new(layer, if key.is_ascii_uppercase() { 3 } else { 1 }))
+            }
+            Keymap::Speedcubing => {
+                let (layer, amount) = match key.to_ascii_lowercase() {
+                    'i' => (Layer::R, 1),
+                    'k' => (Layer::R, 3),
+                    'd' => (Layer::L, 1),
+                    'e' => (Layer::L, 3),
+                    'j' => (Layer::U, 1),
+                    'f' => (Layer::U, 3),
+                    's' => (Layer::D, 1),
+                    'l' => (Layer::D, 3),
+                    'h' => (Layer::F, 1),
+                    'g' => (Layer::F, 3),
+                    'w' => (Layer::B, 1),
+                    'o' => (Layer::B, 3),
+                    'u' => (Layer::Rw, 1),
+                    'm' => (Layer::Rw, 3),
+                    'v' => (Layer::Lw, 1),
+                    'r' => (Layer::Lw, 3),
+                    '5' | '6' => (Layer::M, 1),
+                    'x' | '.' => (Layer::M, 3),
+                    't' | 'y' => (Layer::X, 1),
+                    'b' | 'n' => (Layer::X, 3),
+                    ';' => (Layer::Y, 1),
+                    'a' => (Layer::Y, 3),
+                    'p' => (Layer::Z, 1),
+                    'q' => (Layer::Z, 3),
+                    _ => return None,
+                };
+                Some(Move::new(layer, amount))
+            }
+        };
+    }
+}
+
+// A solution being stepped through, and how many of its moves have been made
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Playback {
+    pub solver: String,
+    pub solution: Algorithm,
+    pub position: usize,
+}
+
+impl Playback {
+    pub fn next_move(&self) -> Option<Move> {
+        return self.solution.moves().get(self.position).copied();
+    }
+
+    pub fn finished(&self) -> bool {
+        return self.position == self.solution.moves().len();
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Simulator {
+    start: RubixCube,
+    cube: RubixCube,
+    done: Vec<Move>,
+    // Undone moves, the next to redo last
+    undone: Vec<Move>,
+    playback: Option<Playback>,
+    scheme: ColourScheme,
+}
+
+impl Simulator {
+    pub fn new(cube: RubixCube) -> Simulator {
+        return Simulator { start: cube, cube, done: Vec::new(), undone: Vec::new(), playback: None, scheme: ColourScheme::default() };
+    }
+
+    pub fn with_scheme(self, scheme: ColourScheme) -> Simulator {
+        return Simulator { scheme, ..self };
+    }
+
+    pub fn scheme(&self) -> &ColourScheme {
+        return &self.scheme;
+    }
+
+    pub fn cube(&self) -> &RubixCube {
+        return &self.cube;
+    }
+
+    pub fn start(&self) -> &RubixCube {
+        return &self.start;
+    }
+
+    // Moves made since the start, undone ones left out
+    pub fn history(&self) -> &[Move] {
+        return &self.done;
+    }
+
+    pub fn can_redo(&self) -> bool {
+        return !self.undone.is_empty();
+    }
+
+    pub fn playback(&self) -> Option<&Playback> {
+        return self.playback.as_ref();
+    }
+
+    // Keeps the solution up if turn is its next move, or drops it
+    fn follow(&mut self, turn: Move) {
+        match &mut self.playback {
+            Some(playback) if playback.next_move() == Some(turn) => playback.position += 1,
+            _ => self.playback = None,
+        }
+    }
+
+    pub fn turn(&mut self, turn: Move) {
+        self.cube.apply_move(turn);
+        self.done.push(turn);
+        self.undone.clear();
+        self.follow(turn);
+    }
+
+    pub fn undo(&mut self) -> Option<Move> {
+        let turn = self.done.pop()?;
+        self.cube.apply_move(turn.inverse());
+        self.undone.push(turn);
+        match &mut self.playback {
+            Some(playback) if playback.position > 0 && playback.solution.moves()[playback.position - 1] == turn => playback.position -= 1,
+            _ => self.playback = None,
+        }
+        return Some(turn);
+    }
+
+    pub fn redo(&mut self) -> Option<Move> {
+        let turn = self.undone.pop()?;
+        self.cube.apply_move(turn);
+        self.done.push(turn);
+        self.follow(turn);
+        return Some(turn);
+    }
+
+    // Starts again from a random scramble of length moves, returned
+    pub fn scramble<R: Rng>(&mut self, length: usize, rng: &mut R) -> Algorithm {
+        let scramble = Algorithm::random(length, rng);
+        let mut cube = self.scheme.solved();
+        cube.apply_algorithm(&scramble);
+        self.reset(cube);
+        return scramble;
+    }
+
+    // Starts again from cube
+    pub fn reset(&mut self, cube: RubixCube) {
+        *self = Simulator::new(cube).with_scheme(self.scheme);
+    }
+
+    // Puts up solution, found by solver, to step through from the cube as it is now
+    pub fn show_solution(&mut self, solver: &str, solution: Algorithm) {
+        self.playback = Some(Playback { solver: solver.to_string(), solution, position: 0 });
+    }
+
+    pub fn step_forward(&mut self) -> Option<Move> {
+        let turn = self.playback.as_ref()?.next_move()?;
+        self.turn(turn);
+        return Some(turn);
+    }
+
+    pub fn step_back(&mut self) -> Option<Move> {
+        if self.playback.as_ref()?.position == 0 {
+            return None;
+        }
+        return self.undo();
+    }
+}
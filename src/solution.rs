@@ -0,0 +1,92 @@
+use std::fmt;
+use std::time::Duration;
+
+use crate::algorithm::Algorithm;
+use crate::rubix_cube::RubixCube;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Stage {
+    pub name: String,
+    pub explanation: String,
+    pub algorithm: Algorithm,
+    // For Thistlethwaite, which of G0 to G4 the cube is in once this stage is done
+    pub subgroup: Option<usize>,
+}
+
+impl Stage {
+    pub fn move_count(&self) -> usize {
+        return self.algorithm.len();
+    }
+}
+
+// A solve split into the stages of the method that found it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Solution {
+    pub method: String,
+    pub stages: Vec<Stage>,
+}
+
+impl Solution {
+    pub fn new(method: &str) -> Solution {
+        return Solution { method: String::from(method), stages: Vec::new() };
+    }
+
+    // A search's answer as a solution of one stage
+    pub fn searched(method: &str, algorithm: Algorithm) -> Solution {
+        let mut solution = Solution::new(method);
+        solution.push_stage("Search", "every face turn tried, shortest first", algorithm);
+        return solution;
+    }
+
+    pub fn push_stage(&mut self, name: &str, explanation: &str, algorithm: Algorithm) {
+        self.stages.push(Stage { name: String::from(name), explanation: String::from(explanation), algorithm, subgroup: None });
+    }
+
+    // Every stage one after another
+    pub fn algorithm(&self) -> Algorithm {
+        let mut algorithm = Algorithm::default();
+        for stage in &self.stages {
+            algorithm = algorithm.concat(&stage.algorithm);
+        }
+        return algorithm;
+    }
+
+    pub fn move_count(&self) -> usize {
+        return self.stages.iter().map(|stage| stage.move_count()).sum();
+    }
+
+    // Index into algorithm() where each stage ends
+    pub fn stage_boundaries(&self) -> Vec<usize> {
+        let mut end = 0;
+        let mut boundaries = Vec::new();
+        for stage in &self.stages {
+            end += stage.move_count();
+            boundaries.push(end);
+        }
+        return boundaries;
+    }
+}
+
+impl fmt::Display for Solution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} ({} moves)", self.method, self.move_count())?;
+        for stage in &self.stages {
+            write!(f, "  {} ({}): {}", stage.name, stage.move_count(), stage.algorithm)?;
+            match stage.subgroup {
+                Some(group) => writeln!(f, " -> G{}", group)?,
+                None => writeln!(f)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+// One solve as reported to someone else: the state solved, the solution, and what it cost.
+// nodes is only known for the searches that count them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SolveReport {
+    pub cube: RubixCube,
+    pub solution: Solution,
+    pub nodes: Option<u64>,
+    pub time: Duration,
+}
@@ -0,0 +1,89 @@
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::bidirectional::BidirectionalSearch;
+use crate::error::CubeError;
+use crate::library::AlgorithmLibrary;
+use crate::method::Method;
+use crate::parallel_search::ParallelSearch;
+use crate::rubix_cube::RubixCube;
+use crate::solution::{SolveReport, Solution};
+use crate::threads::Threads;
+
+// Every solver behind one name, for front ends that let the user pick. The searches carry
+// their settings, which can be changed after parse.
+#[derive(Clone)]
+pub enum Solver {
+    // Shortest solution, by ParallelSearch
+    Optimal(ParallelSearch),
+    Bidirectional(BidirectionalSearch),
+    // A method, the flag that stops it building its tables and the threads it runs on
+    Method { method: Method, cancel: Option<Arc<AtomicBool>>, threads: Threads },
+}
+
+impl Solver {
+    pub const NAMES: [&'static str; 7] = ["optimal", "bidirectional", "beginner", "cfop", "roux", "zz", "thistlethwaite"];
+
+    // One of NAMES, in any case, with default settings
+    pub fn parse(name: &str) -> Option<Solver> {
+        return match name.to_lowercase().as_str() {
+            "optimal" => Some(Solver::Optimal(ParallelSearch::default())),
+            "bidirectional" => Some(Solver::Bidirectional(BidirectionalSearch::default())),
+            other => Method::ALL.iter().find(|method| method.name().to_lowercase() == other).map(|&method| Solver::Method { method, cancel: None, threads: Threads::default() }),
+        };
+    }
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            Solver::Optimal(_) => "optimal",
+            Solver::Bidirectional(_) => "bidirectional",
+            Solver::Method { method, .. } => method.name(),
+        };
+    }
+
+    // Whether it searches for the shortest solution, which can take far longer than a method
+    pub fn is_search(&self) -> bool {
+        return !matches!(self, Solver::Method { .. });
+    }
+
+    // Whether it spreads over threads. Only the optimal search does; the bidirectional search
+    // and the methods run on one thread of whatever they are given.
+    pub fn uses_threads(&self) -> bool {
+        return matches!(self, Solver::Optimal(_));
+    }
+
+    // Runs the solve on threads. A pool's size is a cap: the optimal search spreads over all of
+    // it and the other solvers run inside it on one, so none goes past it.
+    pub fn with_threads(self, threads: Threads) -> Solver {
+        return match self {
+            Solver::Optimal(search) => Solver::Optimal(search.with_threads(threads)),
+            Solver::Bidirectional(search) => Solver::Bidirectional(search.with_threads(threads)),
+            Solver::Method { method, cancel, .. } => Solver::Method { method, cancel, threads },
+        };
+    }
+
+    // Gives up on the solve when cancel is set, searches and methods building tables alike
+    pub fn with_cancel(self, cancel: Arc<AtomicBool>) -> Solver {
+        return match self {
+            Solver::Optimal(search) => Solver::Optimal(search.with_cancel(cancel)),
+            Solver::Bidirectional(search) => Solver::Bidirectional(search.with_cancel(cancel)),
+            Solver::Method { method, threads, .. } => Solver::Method { method, cancel: Some(cancel), threads },
+        };
+    }
+
+    // Solves cube, turning away one that can't be solved before starting
+    pub fn solve(&self, cube: &RubixCube, library: &AlgorithmLibrary) -> Result<SolveReport, CubeError> {
+        cube.check_solvable()?;
+        let start = Instant::now();
+        let (solution, nodes) = match self {
+            Solver::Optimal(search) => {
+                let (algorithm, nodes) = search.solve_counting(cube)?;
+                (Solution::searched(self.name(), algorithm), Some(nodes))
+            }
+            Solver::Bidirectional(search) => (Solution::searched(self.name(), search.solve(cube)?), None),
+            Solver::Method { method, cancel, threads } => (threads.install(|| method.solve_with_cancel(cube, library, cancel.as_deref()))?, None),
+        };
+        return Ok(SolveReport { cube: *cube, solution, nodes, time: start.elapsed() });
+    }
+}
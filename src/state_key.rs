@@ -0,0 +1,97 @@
+use crate::cubies::CubieCube;
+use crate::rubix_cube::RubixCube;
+
+// A cube state packed into one u128, ignoring how the cube got there. From the low bits up:
+//
+//   corner permutation   16 bits  Lehmer code, < 8!
+//   corner twists        13 bits  all eight as base 3 digits
+//   edge permutation     29 bits  Lehmer code, < 12!
+//   edge flips           12 bits
+//   centres              18 bits  colour of each centre, 3 bits a face
+//
+// Keys made from a CubieCube alone leave the centre bits at zero. Every cube whose pieces are
+// all different gets its own key, and two cubes with the same stickers always share one.
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub struct StateKey(pub u128);
+
+impl StateKey {
+    pub fn from_cubies(cubies: &CubieCube) -> StateKey {
+        let corners = permutation_rank(&cubies.corners.map(|(piece, _)| piece)) as u128;
+        let twists = cubies.corners.iter().fold(0, |key, &(_, twist)| key * 3 + twist as u128);
+        let edges = permutation_rank(&cubies.edges.map(|(piece, _)| piece)) as u128;
+        let flips = cubies.edges.iter().fold(0, |key, &(_, flip)| key << 1 | flip as u128);
+        return StateKey(corners | twists << 16 | edges << 29 | flips << 58);
+    }
+
+    // None if some sticker combination is not a real piece
+    pub fn from_rubix(cube: &RubixCube) -> Option<StateKey> {
+        let StateKey(pieces) = StateKey::from_cubies(&CubieCube::from_rubix(cube)?);
+        let centres = cube.get_faces().iter().fold(0, |key, face| key << 3 | face[4] as u128);
+        return Some(StateKey(pieces | centres << 70));
+    }
+
+    // The key folded to 64 bits, for tables indexed by hash. Different states can share one.
+    pub fn fingerprint(&self) -> u64 {
+        let mut mixed = (self.0 as u64) ^ ((self.0 >> 64) as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        mixed = (mixed ^ (mixed >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        mixed = (mixed ^ (mixed >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        return mixed ^ (mixed >> 31);
+    }
+}
+
+// Lehmer code of pieces, a permutation of 0..n
+pub fn permutation_rank(pieces: &[u8]) -> usize {
+    let mut rank = 0;
+    for i in 0..pieces.len() {
+        let smaller_later = pieces[i + 1..].iter().filter(|&&piece| piece < pieces[i]).count();
+        rank = rank * (pieces.len() - i) + smaller_later;
+    }
+    return rank;
+}
+
+// A fixed size table from states to small values, for a search to note what it has already
+// expanded. Each key has one slot, picked by its fingerprint; a new entry simply replaces
+// whatever was there, so memory never grows and a lookup may miss something stored earlier.
+//
+// PieceSearch keeps one, as with only a few pieces tracked many paths lead to the same state.
+// The whole cube searches don't: ParallelSearch's IDA* never turns a layer twice in a row or
+// two opposite layers out of order, and paths that pass those rules almost never meet within
+// the depths it reaches, so a table there costs a key per node and cuts nothing.
+pub struct TranspositionTable<V: Copy> {
+    slots: Vec<Option<(StateKey, V)>>,
+    mask: usize,
+}
+
+impl<V: Copy> TranspositionTable<V> {
+    // Room for the largest power of two number of entries that fits in memory_limit bytes
+    pub fn new(memory_limit: usize) -> TranspositionTable<V> {
+        let entries = (memory_limit / size_of::<Option<(StateKey, V)>>()).max(1);
+        let size = if entries.is_power_of_two() { entries } else { entries.next_power_of_two() / 2 };
+        return TranspositionTable { slots: vec![None; size], mask: size - 1 };
+    }
+
+    pub fn get(&self, key: &StateKey) -> Option<V> {
+        return match self.slots[self.slot(key)] {
+            Some((stored, value)) if stored == *key => Some(value),
+            _ => None,
+        };
+    }
+
+    pub fn insert(&mut self, key: StateKey, value: V) {
+        let slot = self.slot(&key);
+        self.slots[slot] = Some((key, value));
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.fill(None);
+    }
+
+    pub fn capacity(&self) -> usize {
+        return self.slots.len();
+    }
+
+    fn slot(&self, key: &StateKey) -> usize {
+        return key.fingerprint() as usize & self.mask;
+    }
+}
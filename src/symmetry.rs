@@ -0,0 +1,176 @@
+use std::sync::OnceLock;
+
+use crate::algorithm::{Layer, Move};
+use crate::cubies::{CubieCube, B, CORNER_FACELETS, D, EDGE_FACELETS, F, L, R, U};
+use crate::rubix_cube::RubixCube;
+use crate::state_key::StateKey;
+
+// The 48 symmetries of the cube: the 24 rotations, each with or without a mirror through the
+// plane between L and R. Applying one to a state turns the whole cube, stickers and all, then
+// repaints it so the centres are back to their own colours. A state made by some moves comes
+// out as the state made by the same moves turned through the symmetry, so a state and its 48
+// images are all the same distance from solved with the face turns, and a table over face
+// turns only needs one of them.
+
+#[derive(Clone, Debug)]
+pub struct Symmetry {
+    // Where each face goes
+    pub faces: [usize; 6],
+    // Whether it includes a mirror, which turns clockwise into anticlockwise
+    pub mirrored: bool,
+    // Where each corner and edge position goes, and where each of its stickers goes among the
+    // new position's stickers
+    corners: [(u8, [u8; 3]); 8],
+    edges: [(u8, [u8; 2]); 12],
+    // Which sticker of each piece lands on the U/D sticker of the piece it becomes
+    corner_first: [u8; 8],
+    edge_first: [u8; 12],
+    // Index of the symmetry undoing this one
+    inverse: usize,
+}
+
+const FACE_LAYERS: [Layer; 6] = [Layer::U, Layer::L, Layer::F, Layer::R, Layer::D, Layer::B];
+
+impl Symmetry {
+    fn from_faces(faces: [usize; 6]) -> Symmetry {
+        let corners = std::array::from_fn(|position| {
+            let image = CORNER_FACELETS[position].map(|(face, _)| faces[face]);
+            let to = (0..8).find(|&to| image.iter().all(|face| CORNER_FACELETS[to].iter().any(|&(f, _)| f == *face))).unwrap();
+            (to as u8, image.map(|face| CORNER_FACELETS[to].iter().position(|&(f, _)| f == face).unwrap() as u8))
+        });
+        let edges = std::array::from_fn(|position| {
+            let image = EDGE_FACELETS[position].map(|(face, _)| faces[face]);
+            let to = (0..12).find(|&to| image.iter().all(|face| EDGE_FACELETS[to].iter().any(|&(f, _)| f == *face))).unwrap();
+            (to as u8, image.map(|face| EDGE_FACELETS[to].iter().position(|&(f, _)| f == face).unwrap() as u8))
+        });
+        // A rotation keeps the stickers of a corner in the same cyclic order and a mirror reverses it
+        let (_, urf): (u8, [u8; 3]) = corners[0];
+        let mirrored = (urf[1] + 3 - urf[0]) % 3 == 2;
+        let corner_first = corners.map(|(_, stickers)| stickers.iter().position(|&sticker| sticker == 0).unwrap() as u8);
+        let edge_first = edges.map(|(_, stickers)| stickers.iter().position(|&sticker| sticker == 0).unwrap() as u8);
+        return Symmetry { faces, mirrored, corners, edges, corner_first, edge_first, inverse: 0 };
+    }
+
+    pub fn inverse(&self) -> &'static Symmetry {
+        return &symmetries()[self.inverse];
+    }
+
+    // The same turn seen through the symmetry. Only face turns are handled.
+    pub fn map_move(&self, next: Move) -> Move {
+        let face = FACE_LAYERS.iter().position(|&layer| layer == next.layer).expect("face turn");
+        let amount = if self.mirrored { 4 - next.amount } else { next.amount };
+        return Move::new(FACE_LAYERS[self.faces[face]], amount);
+    }
+
+    pub fn apply(&self, cube: &CubieCube) -> CubieCube {
+        let mut image = *cube;
+        for (position, &(piece, twist)) in cube.corners.iter().enumerate() {
+            let (piece, to, twist) = self.map_corner(piece as usize, position, twist);
+            image.corners[to] = (piece as u8, twist);
+        }
+        for (position, &(piece, flip)) in cube.edges.iter().enumerate() {
+            let (piece, to, flip) = self.map_edge(piece as usize, position, flip);
+            image.edges[to] = (piece as u8, flip);
+        }
+        return image;
+    }
+
+    // What the corner piece at position with twist becomes: (piece, position, twist)
+    pub fn map_corner(&self, piece: usize, position: usize, twist: u8) -> (usize, usize, u8) {
+        let (to, stickers) = self.corners[position];
+        // Sticker k of the piece sits on sticker (twist + k) of the position; the new
+        // twist is wherever the new piece's U/D sticker lands
+        let k = self.corner_first[piece];
+        return (self.corners[piece].0 as usize, to as usize, stickers[((twist + k) % 3) as usize]);
+    }
+
+    // What the edge piece at position with flip becomes: (piece, position, flip)
+    pub fn map_edge(&self, piece: usize, position: usize, flip: u8) -> (usize, usize, u8) {
+        let (to, stickers) = self.edges[position];
+        let k = self.edge_first[piece];
+        return (self.edges[piece].0 as usize, to as usize, stickers[((flip + k) % 2) as usize]);
+    }
+}
+
+// All 48, the identity first
+pub fn symmetries() -> &'static [Symmetry] {
+    static SYMMETRIES: OnceLock<Vec<Symmetry>> = OnceLock::new();
+    return SYMMETRIES.get_or_init(|| {
+        // Indexed U L F R D B: a quarter turn about U, one about R, and the mirror
+        let y = [U, B, L, F, D, R];
+        let x = [B, L, U, R, F, D];
+        let mirror = [U, R, F, L, D, B];
+        let mut found = vec![[U, L, F, R, D, B]];
+        let mut next = 0;
+        while next < found.len() {
+            for generator in [y, x, mirror] {
+                let faces = found[next].map(|face| generator[face]);
+                if !found.contains(&faces) {
+                    found.push(faces);
+                }
+            }
+            next += 1;
+        }
+        let mut symmetries: Vec<Symmetry> = found.iter().map(|&faces| Symmetry::from_faces(faces)).collect();
+        for symmetry in symmetries.iter_mut() {
+            let faces = symmetry.faces;
+            symmetry.inverse = found.iter().position(|other| (0..6).all(|face| other[faces[face]] == face)).unwrap();
+        }
+        symmetries
+    });
+}
+
+// The smallest key among the 48 images of cube and the index of the symmetry giving it, so
+// every symmetric state gets the same key
+pub fn reduce<K: Ord>(cube: &CubieCube, key: impl Fn(&CubieCube) -> K) -> (K, usize) {
+    return smallest(0..symmetries().len(), |index| key(&symmetries()[index].apply(cube)));
+}
+
+// The smallest image over the symmetries in within, for keys that only some symmetries keep
+// meaning, and the symmetry giving it
+pub fn smallest<K: Ord>(within: impl Iterator<Item = usize>, image: impl Fn(usize) -> K) -> (K, usize) {
+    return within.map(|index| (image(index), index)).min_by(|a, b| a.0.cmp(&b.0)).unwrap();
+}
+
+impl StateKey {
+    // One key for a state and all its symmetric images, with the symmetry taking cube to it.
+    // The images are compared as pieces, which is cheaper than making 48 keys.
+    pub fn reduced(cube: &CubieCube) -> (StateKey, usize) {
+        let (image, symmetry) = reduce(cube, |image| *image);
+        return (StateKey::from_cubies(&image), symmetry);
+    }
+}
+
+impl RubixCube {
+    // The cube turned through symmetry and repainted so each centre keeps its colour
+    pub fn symmetric(&self, symmetry: &Symmetry) -> RubixCube {
+        let faces = self.get_faces();
+        let mut image = faces;
+        for face in 0..6 {
+            image[symmetry.faces[face]][4] = faces[face][4];
+        }
+        for (position, stickers) in CORNER_FACELETS.iter().enumerate() {
+            let (to, moved) = symmetry.corners[position];
+            for (i, &(face, index)) in stickers.iter().enumerate() {
+                let (new_face, new_index) = CORNER_FACELETS[to as usize][moved[i] as usize];
+                image[new_face][new_index] = faces[face][index];
+            }
+        }
+        for (position, stickers) in EDGE_FACELETS.iter().enumerate() {
+            let (to, moved) = symmetry.edges[position];
+            for (i, &(face, index)) in stickers.iter().enumerate() {
+                let (new_face, new_index) = EDGE_FACELETS[to as usize][moved[i] as usize];
+                image[new_face][new_index] = faces[face][index];
+            }
+        }
+        // The centre of face f now wears the colour of f's old centre; paint it back
+        let repaint = |colour| (0..6).find(|&face| faces[face][4] == colour).map_or(colour, |face| faces[symmetry.faces[face]][4]);
+        let image = image.map(|face| face.map(repaint));
+        return RubixCube::create_custom_rubix(&image[U], &image[L], &image[F], &image[R], &image[D], &image[B]);
+    }
+
+    // The smallest of the cube's 48 images, the same for every cube symmetric to it
+    pub fn canonical(&self) -> RubixCube {
+        return symmetries().iter().map(|symmetry| self.symmetric(symmetry)).min().unwrap();
+    }
+}
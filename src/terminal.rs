@@ -0,0 +1,139 @@
+use std::env;
+use std::io::{self, IsTerminal};
+
+use crate::diagram::Rgb;
+use crate::rubix_cube::{Colour, RubixCube};
+use crate::scheme::ColourScheme;
+
+// The net drawn for a terminal, each sticker a block of 24-bit ANSI background colour, or
+// its letter where colour isn't wanted. print_cube stays the plain dump of the state.
+//
+// Cross, with labels:
+//
+//           U
+//           B B B
+//           B B B
+//           B B B
+//    L      F      R      B
+//    R R R  W W W  O O O  Y Y Y
+//    ...
+//           D
+//           G G G
+//           ...
+//
+// Cross lays the four side faces in a row, B turned round to read on from R. Column is
+// print_cube's layout: B under D, as seen by tipping the cube forwards.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NetLayout {
+    Cross,
+    Column,
+}
+
+impl NetLayout {
+    // Rows of faces, each face by its index in faces and the column it goes in
+    pub(crate) fn bands(self) -> Vec<Vec<(usize, usize)>> {
+        return match self {
+            NetLayout::Cross => vec![vec![(0, 1)], vec![(1, 0), (2, 1), (3, 2), (5, 3)], vec![(4, 1)]],
+            NetLayout::Column => vec![vec![(0, 1)], vec![(1, 0), (2, 1), (3, 2)], vec![(4, 1)], vec![(5, 1)]],
+        };
+    }
+
+    // Index in faces[face] of the sticker drawn at row, column
+    pub(crate) fn sticker(self, face: usize, row: usize, column: usize) -> usize {
+        if face == 5 && self == NetLayout::Cross {
+            return 8 - (row * 3 + column);
+        }
+        return row * 3 + column;
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TerminalNet {
+    pub layout: NetLayout,
+    // Coloured blocks; letters if not
+    pub colour: bool,
+    // The face's letter over each face
+    pub labels: bool,
+    // Indexed by Colour as usize
+    pub shades: [Rgb; 6],
+}
+
+impl Default for TerminalNet {
+    fn default() -> TerminalNet {
+        return TerminalNet { layout: NetLayout::Cross, colour: true, labels: false, shades: ColourScheme::default().shades };
+    }
+}
+
+const FACE_NAMES: [char; 6] = ['U', 'L', 'F', 'R', 'D', 'B'];
+
+// Each sticker is two characters wide, which is about square in most terminals
+const CELL: usize = 2;
+const FACE_WIDTH: usize = 3 * CELL + 1;
+
+impl TerminalNet {
+    // Colour when stdout is a terminal and NO_COLOR isn't set
+    pub fn for_stdout() -> TerminalNet {
+        let colour = io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none_or(|value| value.is_empty());
+        return TerminalNet { colour, ..TerminalNet::default() };
+    }
+
+    pub fn with_layout(self, layout: NetLayout) -> TerminalNet {
+        return TerminalNet { layout, ..self };
+    }
+
+    pub fn with_labels(self, labels: bool) -> TerminalNet {
+        return TerminalNet { labels, ..self };
+    }
+
+    // Draws the stickers in the scheme's shades
+    pub fn with_scheme(self, scheme: &ColourScheme) -> TerminalNet {
+        return TerminalNet { shades: scheme.shades, ..self };
+    }
+
+    fn cell(&self, colour: Colour) -> String {
+        if !self.colour {
+            return format!("{:<width$}", colour.to_string(), width = CELL);
+        }
+        let (red, green, blue) = self.shades[colour as usize];
+        return format!("\x1b[48;2;{};{};{}m{:width$}\x1b[0m", red, green, blue, "", width = CELL);
+    }
+
+    // The net as lines ending in newlines, with no trailing spaces
+    pub fn render(&self, cube: &RubixCube) -> String {
+        let faces = cube.get_faces();
+        let mut text = String::new();
+        for (index, band) in self.layout.bands().iter().enumerate() {
+            if self.labels {
+                let mut line = String::new();
+                for &(face, column) in band {
+                    line.push_str(&" ".repeat(column * FACE_WIDTH - line.len()));
+                    line.push(FACE_NAMES[face]);
+                }
+                text.push_str(&line);
+                text.push('\n');
+            } else if index > 0 {
+                text.push('\n');
+            }
+            for row in 0..3 {
+                let mut line = String::new();
+                let mut width = 0;
+                for &(face, column) in band {
+                    line.push_str(&" ".repeat(column * FACE_WIDTH - width));
+                    width = column * FACE_WIDTH;
+                    for sticker in 0..3 {
+                        line.push_str(&self.cell(faces[face][self.layout.sticker(face, row, sticker)]));
+                        width += CELL;
+                    }
+                }
+                text.push_str(line.trim_end());
+                text.push('\n');
+            }
+        }
+        return text;
+    }
+
+    pub fn print(&self, cube: &RubixCube) {
+        print!("{}", self.render(cube));
+    }
+}
@@ -0,0 +1,270 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::AtomicBool;
+
+use crate::algorithm::{Algorithm, Layer, Move};
+use crate::cubies::{CubieCube, CubieMove};
+use crate::lazy_table::{cancelled, LazyTable};
+use crate::rubix_cube::RubixCube;
+use crate::solution::Solution;
+use crate::state_key::permutation_rank;
+use crate::symmetry;
+
+// Thistlethwaite's algorithm: four phases, each taking the cube from one subgroup into the
+// next smaller one, using only the moves of the group it is already in
+//
+//   G0 = <U, D, L, R, F, B>
+//   G1 = <U, D, L, R, F2, B2>     edges oriented
+//   G2 = <U, D, L2, R2, F2, B2>   corners oriented too, E slice edges in the E slice
+//   G3 = <U2, D2, L2, R2, F2, B2> corners in their tetrads, M slice edges in the M slice
+//   G4 = solved
+//
+// Each phase has a table giving, for every coset of the next group, the fewest moves into it.
+// A coset is identified by a key that only looks at what the phase changes, and the table
+// is a breadth first search out from every key the next group itself reaches.
+
+pub const SUBGROUPS: [&str; 5] = [
+    "G0 = <U, D, L, R, F, B>",
+    "G1 = <U, D, L, R, F2, B2>",
+    "G2 = <U, D, L2, R2, F2, B2>",
+    "G3 = <U2, D2, L2, R2, F2, B2>",
+    "G4 = solved",
+];
+
+const PHASE_GOALS: [&str; 4] = [
+    "Orient all twelve edges so F and B quarter turns are never needed again.",
+    "Orient the corners and bring the four E slice edges into the E slice, so only half turns of L, R, F and B are needed.",
+    "Put each corner in its tetrad, arranged so half turns can solve them, and the M slice edges in the M slice, so only half turns are needed.",
+    "Solve the cube with half turns alone.",
+];
+
+const UNREACHED: u8 = u8::MAX;
+
+// UR UF UL UB DR DF DL DB positions holding M slice edges (UF, UB, DF, DB)
+fn m_slice_key(cube: &CubieCube) -> usize {
+    let mut mask = 0;
+    for position in 0..8 {
+        if cube.edges[position].0 % 2 == 1 && cube.edges[position].0 < 8 {
+            mask |= 1 << position;
+        }
+    }
+    return mask;
+}
+
+fn edge_orientation_key(cube: &CubieCube) -> usize {
+    return (0..12).map(|position| (cube.edges[position].1 as usize) << position).sum();
+}
+
+// Twists as base 3 digits (6561) times the four E slice positions ranked among 495
+fn corner_orientation_key(cube: &CubieCube) -> usize {
+    let twists = cube.corners.iter().fold(0, |key, &(_, twist)| key * 3 + twist as usize);
+    let mut slice = 0;
+    for position in 0..12 {
+        if cube.edges[position].0 >= 8 {
+            slice |= 1 << position;
+        }
+    }
+    return twists * 495 + combination_rank(slice);
+}
+
+fn tetrad_key(cube: &CubieCube) -> usize {
+    return permutation_rank(&cube.corners.map(|(piece, _)| piece)) * 256 + m_slice_key(cube);
+}
+
+// Pieces at every position, 3 bits a corner and 4 an edge
+fn solved_key(cube: &CubieCube) -> usize {
+    let corners = cube.corners.iter().fold(0, |key, &(piece, _)| key << 3 | piece as usize);
+    return cube.edges.iter().fold(corners, |key, &(piece, _)| key << 4 | piece as usize);
+}
+
+// G3 and its half turns look the same through every symmetry, so the last phase only needs
+// one entry for a state and all its images
+fn reduced_solved_key(cube: &CubieCube) -> usize {
+    return symmetry::reduce(cube, solved_key).0;
+}
+
+// Index of a 4 element subset of 12 in lexicographic order
+fn combination_rank(mask: usize) -> usize {
+    let mut rank = 0;
+    let mut remaining = 4;
+    for position in 0..12 {
+        if remaining == 0 {
+            break;
+        }
+        if mask & (1 << position) != 0 {
+            remaining -= 1;
+        } else {
+            rank += binomial(11 - position, remaining - 1);
+        }
+    }
+    return rank;
+}
+
+fn binomial(n: usize, k: usize) -> usize {
+    if k > n {
+        return 0;
+    }
+    return (0..k).fold(1, |result, i| result * (n - i) / (i + 1));
+}
+
+enum Distances {
+    Dense(Vec<u8>),
+    Sparse(HashMap<usize, u8>),
+}
+
+struct Phase {
+    moves: Vec<(Move, CubieMove)>,
+    key: fn(&CubieCube) -> usize,
+    distances: Distances,
+}
+
+impl Phase {
+    // size is the key range for a dense table, 0 for a hash map. None if cancel is set before
+    // the table is finished.
+    fn new(moves: &[Move], next_group: &[Move], key: fn(&CubieCube) -> usize, size: usize, cancel: Option<&AtomicBool>) -> Option<Phase> {
+        let mut phase = Phase {
+            moves: moves.iter().map(|&next| (next, CubieMove::from_move(next))).collect(),
+            key,
+            distances: if size > 0 { Distances::Dense(vec![UNREACHED; size]) } else { Distances::Sparse(HashMap::new()) },
+        };
+        // Every key of the next group counts as done
+        let next_group: Vec<CubieMove> = next_group.iter().map(|&next| CubieMove::from_move(next)).collect();
+        let mut queue = VecDeque::from([CubieCube::solved()]);
+        let mut sources = Vec::new();
+        phase.set(key(&CubieCube::solved()), 0);
+        while let Some(cube) = queue.pop_front() {
+            if cancelled(cancel) {
+                return None;
+            }
+            sources.push(cube);
+            for next in &next_group {
+                let moved = cube.apply(next);
+                if phase.get(key(&moved)) == UNREACHED {
+                    phase.set(key(&moved), 0);
+                    queue.push_back(moved);
+                }
+            }
+        }
+        let mut queue = VecDeque::from(sources);
+        while let Some(cube) = queue.pop_front() {
+            if cancelled(cancel) {
+                return None;
+            }
+            let depth = phase.get(key(&cube));
+            for m in 0..phase.moves.len() {
+                let moved = cube.apply(&phase.moves[m].1);
+                if phase.get(key(&moved)) == UNREACHED {
+                    phase.set(key(&moved), depth + 1);
+                    queue.push_back(moved);
+                }
+            }
+        }
+        return Some(phase);
+    }
+
+    fn get(&self, key: usize) -> u8 {
+        return match &self.distances {
+            Distances::Dense(distances) => distances[key],
+            Distances::Sparse(distances) => *distances.get(&key).unwrap_or(&UNREACHED),
+        };
+    }
+
+    fn set(&mut self, key: usize, depth: u8) {
+        match &mut self.distances {
+            Distances::Dense(distances) => distances[key] = depth,
+            Distances::Sparse(distances) => {
+                distances.insert(key, depth);
+            }
+        }
+    }
+
+    fn distance(&self, cube: &CubieCube) -> u8 {
+        return self.get((self.key)(cube));
+    }
+
+    // Walks the table down to the next group, None if the cube is not in this phase's group
+    fn solve(&self, cube: &mut CubieCube) -> Option<Algorithm> {
+        let mut algorithm = Algorithm::default();
+        let mut depth = self.distance(cube);
+        if depth == UNREACHED {
+            return None;
+        }
+        while depth > 0 {
+            let (next, moved) = self
+                .moves
+                .iter()
+                .map(|(next, table)| (*next, cube.apply(table)))
+                .find(|(_, moved)| self.distance(moved) == depth - 1)?;
+            algorithm.push(next);
+            *cube = moved;
+            depth -= 1;
+        }
+        return Some(algorithm);
+    }
+}
+
+fn turns(quarter: &[Layer], half: &[Layer]) -> Vec<Move> {
+    let mut moves = Vec::new();
+    for &layer in quarter {
+        moves.extend([Move::new(layer, 1), Move::new(layer, 2), Move::new(layer, 3)]);
+    }
+    for &layer in half {
+        moves.push(Move::new(layer, 2));
+    }
+    return moves;
+}
+
+// The generators of each subgroup, G0 to G4
+fn group_moves(group: usize) -> Vec<Move> {
+    use Layer::*;
+    return match group {
+        0 => turns(&[U, D, L, R, F, B], &[]),
+        1 => turns(&[U, D, L, R], &[F, B]),
+        2 => turns(&[U, D], &[L, R, F, B]),
+        3 => turns(&[], &[U, D, L, R, F, B]),
+        _ => Vec::new(),
+    };
+}
+
+fn phases(cancel: Option<&AtomicBool>) -> Option<&'static [Phase; 4]> {
+    static PHASES: LazyTable<[Phase; 4]> = LazyTable::new();
+    return PHASES.get(cancel, || {
+        Some([
+            Phase::new(&group_moves(0), &group_moves(1), edge_orientation_key, 1 << 12, cancel)?,
+            Phase::new(&group_moves(1), &group_moves(2), corner_orientation_key, 6561 * 495, cancel)?,
+            Phase::new(&group_moves(2), &group_moves(3), tetrad_key, 40320 * 256, cancel)?,
+            Phase::new(&group_moves(3), &group_moves(4), reduced_solved_key, 0, cancel)?,
+        ])
+    });
+}
+
+// Smallest of G0 to G4 the cube is in, None if it can't be read as pieces
+pub fn subgroup(cube: &RubixCube) -> Option<usize> {
+    let cubies = CubieCube::from_rubix(cube)?;
+    let phases = phases(None)?;
+    return Some((0..4).find(|&phase| phases[phase].distance(&cubies) != 0).unwrap_or(4));
+}
+
+// Solves any valid cube in four phases, each stage giving the group the cube is in after it.
+// Gives up if cancel is set while the tables are built.
+pub fn solve_thistlethwaite(cube: &RubixCube, cancel: Option<&AtomicBool>) -> Result<Solution, String> {
+    let mut cubies = CubieCube::from_rubix(cube).ok_or("cube has stickers that don't make real pieces")?;
+    let mut solution = Solution::new("Thistlethwaite");
+    let mut turned = *cube;
+    let phases = phases(cancel).ok_or("cancelled while building the phase tables")?;
+    for (phase, table) in phases.iter().enumerate() {
+        let algorithm = table.solve(&mut cubies).ok_or(format!("phase {} could not be solved, the cube is not solvable", phase + 1))?;
+        turned.apply_algorithm(&algorithm);
+        let group = subgroup(&turned).filter(|&group| group > phase).ok_or(format!("phase {} left the cube outside G{}", phase + 1, phase + 1))?;
+        let explanation = format!("{} Now in {}.", PHASE_GOALS[phase], SUBGROUPS[group]);
+        solution.push_stage(&format!("Phase {}", phase + 1), &explanation, algorithm);
+        if let Some(stage) = solution.stages.last_mut() {
+            stage.subgroup = Some(group);
+        }
+    }
+    let mut check = *cube;
+    check.apply_algorithm(&solution.algorithm());
+    if !check.is_solved() {
+        return Err(String::from("cube could not be solved"));
+    }
+    return Ok(solution);
+}
@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use rayon::ThreadPool;
+
+// Where a solver runs its work. Only ParallelSearch (and Iddfs on top of it) spreads over
+// threads; the method solvers and BidirectionalSearch run on one thread of a pool they are
+// given, so its size caps every solver alike.
+#[derive(Clone, Default)]
+pub enum Threads {
+    // rayon's global pool, one thread per core
+    #[default]
+    Global,
+    // Everything on the calling thread in a fixed order, so a search finds the same solution
+    // every time
+    Single,
+    // A pool the caller made, to share between solves or cap how many cores one may use
+    Pool(Arc<ThreadPool>),
+}
+
+impl Threads {
+    // A pool of exactly threads threads; 1 is Single
+    pub fn count(threads: usize) -> Result<Threads, String> {
+        if threads == 0 {
+            return Err(String::from("thread count must be at least 1"));
+        }
+        if threads == 1 {
+            return Ok(Threads::Single);
+        }
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().map_err(|error| error.to_string())?;
+        return Ok(Threads::Pool(Arc::new(pool)));
+    }
+
+    pub fn is_single(&self) -> bool {
+        return matches!(self, Threads::Single);
+    }
+
+    // Threads work may be spread over
+    pub fn thread_count(&self) -> usize {
+        return match self {
+            Threads::Global => rayon::current_num_threads(),
+            Threads::Single => 1,
+            Threads::Pool(pool) => pool.current_num_threads(),
+        };
+    }
+
+    // Runs work with any rayon parallelism inside it going to this pool
+    pub fn install<R: Send>(&self, work: impl FnOnce() -> R + Send) -> R {
+        return match self {
+            Threads::Pool(pool) => pool.install(work),
+            _ => work(),
+        };
+    }
+}
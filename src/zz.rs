@@ -0,0 +1,101 @@
+use std::sync::atomic::AtomicBool;
+
+use crate::algorithm::Layer;
+use crate::cubies::{D, L, R};
+use crate::lazy_table::LazyTable;
+use crate::library::{AlgorithmLibrary, Goal};
+use crate::method::StageSolver;
+use crate::piece_search::PieceSearch;
+use crate::rubix_cube::RubixCube;
+use crate::solution::Solution;
+
+// ZZ: orient every edge while placing DF and DB (EOLine), then build F2L as a left and a
+// right block with R, U and L alone, which keeps the edges oriented, and finish the last
+// layer. EOLine and both blocks are searched for and optimal. With the edges oriented the
+// last layer is tried as one ZBLL or COLL algorithm before falling back to OLL and PLL.
+
+const LINE_EDGES: [usize; 2] = [5, 7]; // DF, DB
+const LEFT_BLOCK_CORNERS: [usize; 2] = [5, 6]; // DLF, DBL
+const LEFT_BLOCK_EDGES: [usize; 3] = [6, 9, 10]; // DL, FL, BL
+const RIGHT_BLOCK_CORNERS: [usize; 2] = [4, 7]; // DFR, DRB
+const RIGHT_BLOCK_EDGES: [usize; 3] = [4, 8, 11]; // DR, FR, BR
+
+fn eoline_search(cancel: Option<&AtomicBool>) -> Option<&'static PieceSearch> {
+    static SEARCH: LazyTable<PieceSearch> = LazyTable::new();
+    return SEARCH.get(cancel, || {
+        let moves = PieceSearch::turns_of(&[Layer::U, Layer::D, Layer::L, Layer::R, Layer::F, Layer::B]);
+        PieceSearch::build(&[], &LINE_EDGES, true, &moves, cancel)
+    });
+}
+
+fn left_block_search(cancel: Option<&AtomicBool>) -> Option<&'static PieceSearch> {
+    static SEARCH: LazyTable<PieceSearch> = LazyTable::new();
+    return SEARCH.get(cancel, || {
+        let moves = PieceSearch::turns_of(&[Layer::L, Layer::U, Layer::R]);
+        PieceSearch::build(&LEFT_BLOCK_CORNERS, &LEFT_BLOCK_EDGES, false, &moves, cancel)
+    });
+}
+
+fn right_block_search(cancel: Option<&AtomicBool>) -> Option<&'static PieceSearch> {
+    static SEARCH: LazyTable<PieceSearch> = LazyTable::new();
+    return SEARCH.get(cancel, || {
+        let moves = PieceSearch::turns_of(&[Layer::R, Layer::U]);
+        PieceSearch::build(&RIGHT_BLOCK_CORNERS, &RIGHT_BLOCK_EDGES, false, &moves, cancel)
+    });
+}
+
+fn eoline(solver: &mut StageSolver) -> Option<()> {
+    let algorithm = eoline_search(solver.cancel)?.solve(&solver.cube)?;
+    let explanation = format!(
+        "Orient all twelve edges and place the two {} edges at the front and back of D in {} moves, the fewest it can be done in.",
+        solver.colour_name(D),
+        algorithm.len()
+    );
+    solver.push_stage("EOLine", &explanation, algorithm);
+    return Some(());
+}
+
+fn blocks(solver: &mut StageSolver) -> Option<()> {
+    let left = left_block_search(solver.cancel)?.solve(&solver.cube)?;
+    let explanation = format!(
+        "Build the 1x2x3 block on the {} side around the line with L, U and R in {} moves.",
+        solver.colour_name(L),
+        left.len()
+    );
+    solver.push_stage("Left block", &explanation, left);
+    let right = right_block_search(solver.cancel)?.solve(&solver.cube)?;
+    let explanation = format!(
+        "Build the block on the {} side with R and U alone, leaving the left block alone, in {} moves.",
+        solver.colour_name(R),
+        right.len()
+    );
+    solver.push_stage("Right block", &explanation, right);
+    return Some(());
+}
+
+fn last_layer(solver: &mut StageSolver) -> Option<()> {
+    if let Some(found) = solver.best_match(Goal::Zbll).or_else(|| solver.best_match(Goal::Ll)) {
+        let algorithm = solver.play_match(&found, 0);
+        let explanation = format!("Solve the whole last layer in one go: {} {}.", found.entry.set, found.entry.case);
+        solver.record("ZBLL", &explanation, algorithm);
+        return Some(());
+    }
+    if let Some(found) = solver.best_match(Goal::Coll) {
+        let algorithm = solver.play_match(&found, 0);
+        let explanation = format!("Solve the last layer corners with the edges kept oriented: {} {}.", found.entry.set, found.entry.case);
+        solver.record("COLL", &explanation, algorithm);
+        return solver.pll();
+    }
+    solver.oll()?;
+    return solver.pll();
+}
+
+// Solves any valid cube by ZZ with the line and F2L on D (F5). Fails if the cube can't be
+// solved or the library has no algorithm for a last layer case that comes up.
+pub fn solve_zz(cube: &RubixCube, library: &AlgorithmLibrary, cancel: Option<&AtomicBool>) -> Result<Solution, String> {
+    let mut solver = StageSolver::new(cube, library, "ZZ", cancel);
+    eoline(&mut solver).ok_or("EOLine could not be solved")?;
+    blocks(&mut solver).ok_or("F2L could not be solved with R, U and L")?;
+    last_layer(&mut solver).ok_or("no library algorithm for this last layer case")?;
+    return solver.finish();
+}
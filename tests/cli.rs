@@ -0,0 +1,118 @@
+#![allow(clippy::needless_return)]
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use rubix::{Algorithm, RubixCube};
+
+// Runs the command line with args and stdin, returning the exit code, stdout and stderr
+fn run(args: &[&str], stdin: &str) -> (i32, String, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_RubixCube"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(stdin.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    return (output.status.code().unwrap(), String::from_utf8(output.stdout).unwrap(), String::from_utf8(output.stderr).unwrap());
+}
+
+fn facelets(moves: &str) -> String {
+    let mut cube = RubixCube::create_solved_rubix();
+    cube.apply_algorithm(&Algorithm::parse(moves).unwrap());
+    return cube.facelets();
+}
+
+// A solved cube with the UF edge flipped
+fn flipped_edge() -> String {
+    let mut cube = RubixCube::create_solved_rubix();
+    let mut faces = cube.get_faces();
+    (faces[0][7], faces[2][1]) = (faces[2][1], faces[0][7]);
+    cube = RubixCube::create_custom_rubix(&faces[0], &faces[1], &faces[2], &faces[3], &faces[4], &faces[5]);
+    assert!(cube.check_solvable().is_err());
+    return cube.facelets();
+}
+
+#[test]
+fn bad_command_lines_exit_with_usage() {
+    assert_eq!(run(&[], "").0, 2);
+    assert_eq!(run(&["turn"], "").0, 2);
+    assert_eq!(run(&["solve", "--colour", "never"], "").0, 2);
+    assert_eq!(run(&["solve", "--scramble"], "").0, 2);
+    assert_eq!(run(&["solve", "--state", "WWW"], "").0, 2);
+    assert_eq!(run(&["solve", "--solver", "guess"], "").0, 2);
+    assert_eq!(run(&["scramble", "--moves", "many"], "").0, 2);
+    let (code, out, _) = run(&["help"], "");
+    assert_eq!(code, 0);
+    assert!(out.starts_with("usage:"));
+}
+
+#[test]
+fn solve_scramble_apply_and_verify() {
+    let (code, out, _) = run(&["solve", "--scramble", "R U"], "");
+    assert_eq!((code, out.trim()), (0, "U' R'"));
+    let (code, out, err) = run(&["solve", "--scramble", "R U F'", "--solver", "cfop", "--threads", "3"], "");
+    assert_eq!(code, 0, "{}", err);
+    let mut cube = RubixCube::from_facelets(&facelets("R U F'")).unwrap();
+    cube.apply_algorithm(&Algorithm::parse(out.trim()).unwrap());
+    assert!(cube.is_solved());
+
+    let (code, out, _) = run(&["scramble", "--moves", "6", "--seed", "40"], "");
+    assert_eq!(code, 0);
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines, [lines[0], facelets(lines[0]).as_str()]);
+    assert_eq!(run(&["scramble", "--moves", "6", "--seed", "40"], "").1, out);
+
+    let (code, out, _) = run(&["apply", "R U"], "");
+    assert_eq!((code, out.trim()), (0, facelets("R U").as_str()));
+    let state = facelets("R U");
+    assert_eq!(run(&["verify", "--state", &state], "").1.trim(), "valid");
+    assert_eq!(run(&["verify", "--state", &state, "--solution", "U' R'"], "").1.trim(), "solved");
+    assert_eq!(run(&["verify", "--state", &state, "--solution", "U"], "").0, 1);
+}
+
+#[test]
+fn unsolvable_states_fail_the_same_way_everywhere() {
+    let state = flipped_edge();
+    for command in ["solve", "verify"] {
+        let (code, _, err) = run(&[command, "--state", &state], "");
+        assert_eq!(code, 1, "{}: {}", command, err);
+        assert!(err.contains("an edge is flipped"), "{}: {}", command, err);
+    }
+    assert_eq!(run(&["solve", "--state", &state, "--solver", "beginner"], "").0, 1);
+    assert_eq!(run(&["solve", "--scramble", "R U F' L2 D B'", "--max-depth", "3"], "").0, 1);
+}
+
+#[test]
+fn show_draw_animate_bench_and_batch() {
+    let (code, out, _) = run(&["show", "--scramble", "R", "--colour", "never"], "");
+    assert_eq!(code, 0);
+    assert!(out.contains('W') && out.contains('B'));
+    let (code, out, _) = run(&["draw", "--scramble", "R", "--view", "3d"], "");
+    assert_eq!(code, 0);
+    assert!(out.starts_with("<svg"));
+    let (code, out, _) = run(&["animate", "--scramble", "R", "--solution", "R'"], "");
+    assert_eq!(code, 0);
+    assert!(out.starts_with("<svg"));
+    let (code, out, err) = run(&["bench", "--solver", "cfop", "--count", "2", "--moves", "5", "--seed", "1"], "");
+    assert_eq!(code, 0, "{}", err);
+    assert!(out.contains("solved"));
+
+    let (code, out, err) = run(&["batch", "--format", "csv", "--solver", "beginner", "--threads", "2"], "R U\n# skipped\n\nF2 D'\n");
+    assert_eq!(code, 0, "{}", err);
+    assert_eq!(out.lines().count(), 3);
+    assert!(err.to_lowercase().starts_with("beginner"), "{}", err);
+    let (code, out, _) = run(&["batch"], "R U\n");
+    assert_eq!(code, 0);
+    if cfg!(feature = "serde") {
+        assert!(out.starts_with("{\"line\":1,"), "{}", out);
+    } else {
+        assert!(out.starts_with("line,input,"), "{}", out);
+        assert_eq!(run(&["batch", "--format", "jsonl"], "R U\n").0, 2);
+    }
+    let (code, out, _) = run(&["batch", "--format", "csv", "--solver", "optimal"], &format!("R U\n{}\n", flipped_edge()));
+    assert_eq!(code, 1);
+    assert_eq!(out.lines().count(), 3);
+}
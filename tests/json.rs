@@ -0,0 +1,114 @@
+#![cfg(feature = "serde")]
+#![allow(clippy::needless_return)]
+
+use std::time::Duration;
+
+use serde_json::json;
+
+use rubix::{solve_batch, Algorithm, AlgorithmLibrary, CaseResult, Colour, Method, Move, RubixCube, SolveReport, Solution, Solver, Threads};
+
+fn scrambled(moves: &str) -> RubixCube {
+    let mut cube = RubixCube::create_solved_rubix();
+    cube.apply_algorithm(&Algorithm::parse(moves).unwrap());
+    return cube;
+}
+
+#[test]
+fn colours_are_letters() {
+    for colour in Colour::ALL {
+        let text = serde_json::to_string(&colour).unwrap();
+        assert_eq!(text, format!("\"{}\"", colour));
+        assert_eq!(serde_json::from_str::<Colour>(&text).unwrap(), colour);
+    }
+    assert!(serde_json::from_str::<Colour>("\"WY\"").is_err());
+    assert!(serde_json::from_str::<Colour>("\"X\"").is_err());
+}
+
+#[test]
+fn cubes_are_facelets() {
+    let cube = scrambled("R U F' D2 L B'");
+    let value = serde_json::to_value(cube).unwrap();
+    assert_eq!(value, json!(cube.facelets()));
+    assert_eq!(serde_json::from_value::<RubixCube>(value).unwrap(), cube);
+    assert!(serde_json::from_value::<RubixCube>(json!("WWW")).is_err());
+}
+
+#[test]
+fn algorithms_are_move_lists() {
+    let algorithm = Algorithm::parse("R U2 R' x M' Rw2 y'").unwrap();
+    let value = serde_json::to_value(&algorithm).unwrap();
+    assert_eq!(value, json!(["R", "U2", "R'", "x", "M'", "r2", "y'"]));
+    assert_eq!(serde_json::from_value::<Algorithm>(value).unwrap(), algorithm);
+    assert_eq!(serde_json::from_value::<Move>(json!("F'")).unwrap(), Algorithm::parse("F'").unwrap().moves()[0]);
+    assert!(serde_json::from_value::<Move>(json!("R U")).is_err());
+    assert!(serde_json::from_value::<Algorithm>(json!(["R", "Q"])).is_err());
+}
+
+#[test]
+fn solutions_round_trip_with_metrics() {
+    let cube = scrambled("R U F' D2 L B' U2 R'");
+    let library = AlgorithmLibrary::built_in().unwrap();
+    for method in Method::ALL {
+        let solution = method.solve(&cube, &library).unwrap();
+        let value = serde_json::to_value(&solution).unwrap();
+        assert_eq!(value["method"], json!(method.name()));
+        assert_eq!(value["move_count"], json!(solution.move_count()));
+        assert_eq!(value["algorithm"].as_array().unwrap().len(), solution.move_count());
+        assert_eq!(value["stages"][0]["move_count"], json!(solution.stages[0].move_count()));
+        assert_eq!(value["stages"][0].get("subgroup").is_some(), method == Method::Thistlethwaite);
+        assert_eq!(serde_json::from_value::<Solution>(value).unwrap(), solution);
+    }
+}
+
+#[test]
+fn derived_fields_are_optional_when_reading() {
+    let solution: Solution = serde_json::from_value(json!({
+        "method": "by hand",
+        "stages": [{ "name": "all", "explanation": "undo the scramble", "algorithm": ["R", "U'"] }]
+    }))
+    .unwrap();
+    assert_eq!(solution.algorithm(), Algorithm::parse("R U'").unwrap());
+}
+
+#[test]
+fn reports_round_trip() {
+    let cube = scrambled("U R");
+    let report = SolveReport {
+        cube,
+        solution: Solution::searched("optimal", Algorithm::parse("R' U'").unwrap()),
+        nodes: Some(42),
+        time: Duration::from_micros(1500),
+    };
+    let text = serde_json::to_string(&report).unwrap();
+    let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(value["facelets"], json!(cube.facelets()));
+    assert_eq!(value["nodes"], json!(42));
+    assert_eq!(value["time_us"], json!(1500));
+    assert_eq!(serde_json::from_str::<SolveReport>(&text).unwrap(), report);
+
+    let unknown = SolveReport { nodes: None, ..report };
+    let value = serde_json::to_value(&unknown).unwrap();
+    assert_eq!(value["nodes"], json!(null));
+    assert_eq!(serde_json::from_value::<SolveReport>(value).unwrap(), unknown);
+}
+
+#[test]
+fn batch_lines_hold_a_report_or_an_error() {
+    let cube = scrambled("R U");
+    let lines = ["R U", "R Q", "WWW", "scramble: U2"].map(|line| Ok::<String, std::io::Error>(line.to_string()));
+    let mut results = Vec::new();
+    solve_batch(lines.into_iter(), &Solver::parse("optimal").unwrap(), &AlgorithmLibrary::built_in().unwrap(), &Threads::Single, |result: &CaseResult| {
+        results.push(serde_json::from_str::<serde_json::Value>(&result.json_line()).unwrap());
+        return Ok(());
+    })
+    .unwrap();
+    assert_eq!(results[0]["line"], json!(1));
+    assert_eq!(results[0]["input"], json!("R U"));
+    assert_eq!(results[0]["report"]["facelets"], json!(cube.facelets()));
+    assert_eq!(results[0]["report"]["solution"]["algorithm"], json!(["U'", "R'"]));
+    assert_eq!(serde_json::from_value::<SolveReport>(results[0]["report"].clone()).unwrap().cube, cube);
+    assert!(results[0].get("error").is_none() && results[0].get("facelets").is_none());
+    assert_eq!(results[1], json!({ "line": 2, "input": "R Q", "error": "Unknown move 'Q'" }));
+    assert!(results[2]["error"].as_str().unwrap().starts_with("bad facelets"));
+    assert_eq!(results[3]["report"]["solution"]["move_count"], json!(1));
+}
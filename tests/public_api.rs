@@ -0,0 +1,908 @@
+#![allow(clippy::needless_return)]
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use rubix::{
+    compare_methods, parse_case, recognise_f2l, recognise_last_layer, recognise_oll, recognise_pll, run_worker, solve_batch, subgroup, symmetries, Algorithm, AlgorithmLibrary, Animation, BidirectionalSearch, CaseResult, CaseSet, Checkpoint, Collect, Colour, ColourScheme, Coordinator, CubeError, Diagram, Goal, History, Iddfs, Keymap, Method, Move, NetLayout, Palette, ParallelSearch, RubixCube, Simulator, Slot, Solution, Solver, StateKey, StickerMask, Symmetry, TerminalNet, TranspositionTable, Threads, View, OLL_CASES, PLL_CASES,
+};
+
+fn scrambled(moves: &str) -> RubixCube {
+    let mut cube = RubixCube::create_solved_rubix();
+    cube.apply_algorithm(&Algorithm::parse(moves).unwrap());
+    return cube;
+}
+
+fn solves(cube: &RubixCube, algorithm: &Algorithm) -> bool {
+    let mut cube = *cube;
+    cube.apply_algorithm(algorithm);
+    return cube.is_solved();
+}
+
+// The cube after the moves of the first count stages of solution
+fn after_stages(cube: &RubixCube, solution: &Solution, count: usize) -> RubixCube {
+    let mut cube = *cube;
+    for stage in &solution.stages[..count] {
+        cube.apply_algorithm(&stage.algorithm);
+    }
+    return cube;
+}
+
+// Whether each of the stickers of face (U L F R D B as 0 to 5) is its centre's colour
+fn matches_centre(cube: &RubixCube, face: usize, stickers: impl IntoIterator<Item = usize>) -> bool {
+    let faces = cube.get_faces();
+    return stickers.into_iter().all(|index| faces[face][index] == faces[face][4]);
+}
+
+// The D layer and the middle layer; B is stored tipped forwards, its D row first
+fn first_two_layers_solved(cube: &RubixCube) -> bool {
+    return matches_centre(cube, 4, 0..9) && [1, 2, 3].iter().all(|&face| matches_centre(cube, face, 3..9)) && matches_centre(cube, 5, 0..6);
+}
+
+fn random_cubes(seed: u64, count: usize) -> Vec<RubixCube> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    return (0..count)
+        .map(|_| {
+            let mut cube = RubixCube::create_solved_rubix();
+            cube.apply_algorithm(&Algorithm::random(25, &mut rng));
+            cube
+        })
+        .collect();
+}
+
+#[test]
+fn algorithm_and_inverse_cancel() {
+    let algorithm = Algorithm::parse("R U R' U' r M2 x (F2 B')").unwrap();
+    let cube = scrambled("R U R' U' r M2 x F2 B'");
+    assert!(!cube.is_solved());
+    assert!(solves(&cube, &algorithm.inverse()));
+    assert_eq!(Algorithm::parse(&algorithm.to_string()).unwrap(), algorithm);
+    assert!(Algorithm::parse("R Q").is_err());
+}
+
+#[test]
+fn every_move_has_order_four() {
+    let start = scrambled("R U F' L2 D B'");
+    for code in 0..18 {
+        let mut cube = start;
+        for turns in 1..=4 {
+            cube.turn_cube(&code);
+            assert!(cube.check_solvable().is_ok(), "code {} made a state no real cube reaches", code);
+            assert_eq!(cube == start, turns == 4, "code {} after {} turns", code, turns);
+        }
+    }
+    for symbol in ["U", "D", "L", "R", "F", "B", "M", "E", "S", "u", "d", "l", "r", "f", "b", "x", "y", "z"] {
+        let turn = Algorithm::parse(symbol).unwrap();
+        let mut cube = start;
+        for turns in 1..=4 {
+            cube.apply_algorithm(&turn);
+            assert_eq!(cube == start, turns == 4, "{} after {} turns", symbol, turns);
+        }
+    }
+    assert_eq!(Move::from_code(18), Err(CubeError::InvalidTurn(18)));
+    assert_eq!(Algorithm::from_codes(&[8, 3, 3]), Ok(Algorithm::parse("R U2").unwrap()));
+    assert!(Algorithm::from_codes(&[8, 40]).is_err());
+}
+
+#[test]
+fn known_sequences_return_to_solved() {
+    let solved = RubixCube::create_solved_rubix();
+    let sexy = Algorithm::parse("R U R' U'").unwrap();
+    let mut cube = solved;
+    for times in 1..=6 {
+        cube.apply_algorithm(&sexy);
+        assert_eq!(cube == solved, times == 6, "sexy move {} times", times);
+    }
+
+    // Every edge flipped in place, the corners and centres untouched
+    let superflip = Algorithm::parse("U R2 F B R B2 R U2 L B2 R U' D' R2 F R' L B2 U2 F2").unwrap();
+    let mut cube = solved;
+    cube.apply_algorithm(&superflip);
+    for face in cube.get_faces() {
+        for (index, sticker) in face.iter().enumerate() {
+            assert_eq!(*sticker == face[4], index % 2 == 0);
+        }
+    }
+    cube.apply_algorithm(&superflip);
+    assert_eq!(cube, solved);
+}
+
+#[test]
+fn every_last_layer_case_is_recognised() {
+    for (name, algorithm) in OLL_CASES {
+        let algorithm = Algorithm::parse(algorithm).unwrap();
+        for auf in ["", "U", "U2", "U'"] {
+            let cube = scrambled(&format!("{} {}", algorithm.inverse(), auf));
+            let case = recognise_last_layer(&cube).unwrap();
+            assert_eq!(case.name, name);
+            let mut oriented = cube;
+            oriented.apply_algorithm(&Algorithm::new(case.pre_auf_move().into_iter().collect()).concat(&algorithm));
+            assert_eq!(recognise_oll(&oriented).unwrap().name, "OLL skip", "{} from {}", name, auf);
+        }
+    }
+    for (name, algorithm) in PLL_CASES {
+        let algorithm = Algorithm::parse(algorithm).unwrap();
+        for auf in ["", "U", "U2", "U'"] {
+            let cube = scrambled(&format!("{} {}", algorithm.inverse(), auf));
+            let case = recognise_pll(&cube).unwrap();
+            assert_eq!(case.name, name);
+            let mut moves: Vec<Move> = case.pre_auf_move().into_iter().collect();
+            moves.extend(algorithm.moves());
+            moves.extend(case.post_auf_move());
+            assert!(solves(&cube, &Algorithm::new(moves)), "{} from {}", name, auf);
+        }
+    }
+    assert_eq!(recognise_pll(&scrambled("U")).unwrap().post_auf, 3);
+    assert!(recognise_oll(&scrambled("R U R'")).is_none());
+}
+
+#[test]
+fn f2l_pairs_are_recognised_by_slot() {
+    let cube = scrambled("R U R'");
+    let case = recognise_f2l(&cube, Slot::FR).unwrap();
+    assert_eq!(case.set, CaseSet::F2l);
+    assert_ne!(case.name, "F2L solved");
+    for slot in [Slot::FL, Slot::BL, Slot::BR] {
+        assert_eq!(recognise_f2l(&cube, slot).unwrap().name, "F2L solved");
+    }
+}
+
+#[test]
+fn facelets_round_trip() {
+    let cube = scrambled("F R U' L2 D B'");
+    let facelets = cube.facelets();
+    assert_eq!(facelets.len(), 54);
+    assert_eq!(RubixCube::from_facelets(&facelets).unwrap(), cube);
+    assert!(RubixCube::from_facelets(&facelets[1..]).is_err());
+    assert!(RubixCube::from_facelets(&facelets.replace('W', "Y")).is_err());
+}
+
+#[test]
+fn unsolvable_states_are_rejected() {
+    assert!(scrambled("R U F' D2").check_solvable().is_ok());
+    let mut faces = RubixCube::create_solved_rubix().get_faces();
+    // Swap two stickers of one edge, flipping it
+    let flipped = faces[0][7];
+    faces[0][7] = faces[2][1];
+    faces[2][1] = flipped;
+    let cube = RubixCube::create_custom_rubix(&faces[0], &faces[1], &faces[2], &faces[3], &faces[4], &faces[5]);
+    assert!(cube.check_solvable().is_err());
+}
+
+#[test]
+fn history_undoes_turns() {
+    let mut cube = RubixCube::create_solved_rubix();
+    let mut history = History::new();
+    for code in [3, 8, 15] {
+        cube.turn_recorded(code, &mut history);
+    }
+    assert_eq!(history.len(), 3);
+    while cube.undo_turn(&mut history).is_some() {}
+    assert!(cube.is_solved());
+    assert!(history.is_empty());
+}
+
+#[test]
+fn fallible_operations_return_errors() {
+    let mut cube = RubixCube::create_solved_rubix();
+    let mut history = History::new();
+    assert_eq!(cube.try_turn(18), Err(CubeError::InvalidTurn(18)));
+    assert_eq!(cube.try_undo_turn(&mut history), Err(CubeError::NothingToUndo));
+    cube.try_turn_recorded(8, &mut history).unwrap();
+    history.push(40);
+    assert_eq!(cube.try_undo_turn(&mut history), Err(CubeError::InvalidTurn(40)));
+    assert_eq!(cube.undo_turn(&mut history), None);
+    assert_eq!(history.len(), 2);
+    history.pop();
+    assert_eq!(cube.try_undo_turn(&mut history), Ok(8));
+    assert!(cube.is_solved());
+    assert!(matches!(RubixCube::from_facelets("WWW"), Err(CubeError::Facelets(_))));
+}
+
+#[test]
+fn astar_solves_or_refuses() {
+    let cube = scrambled("R U");
+    let path = cube.try_search_astar(4).unwrap();
+    let mut solved = cube;
+    for &code in path.moves() {
+        solved.try_turn(code).unwrap();
+    }
+    assert!(solved.is_solved());
+    assert_eq!(scrambled("R U F' L2 D B").try_search_astar(2), Err(CubeError::NoSolution(2)));
+    let mut faces = RubixCube::create_solved_rubix().get_faces();
+    faces[0][0] = faces[4][4];
+    let unsolvable = RubixCube::create_custom_rubix(&faces[0], &faces[1], &faces[2], &faces[3], &faces[4], &faces[5]);
+    assert!(matches!(unsolvable.try_search_astar(20), Err(CubeError::Unsolvable(_))));
+}
+
+#[test]
+fn random_scrambles_are_repeatable() {
+    let first = Algorithm::random(25, &mut StdRng::seed_from_u64(7));
+    let second = Algorithm::random(25, &mut StdRng::seed_from_u64(7));
+    assert_eq!(first.len(), 25);
+    assert_eq!(first, second);
+    let mut cube = RubixCube::create_solved_rubix();
+    cube.apply_algorithm(&first);
+    assert!(cube.check_solvable().is_ok());
+}
+
+#[test]
+fn libraries_load_user_files_and_reject_bad_lines() {
+    let built_in = AlgorithmLibrary::built_in().unwrap();
+    for name in ["F2L", "OLL", "PLL", "COLL", "ZBLL"] {
+        assert!(!built_in.set(name).unwrap().entries.is_empty(), "{} is empty", name);
+    }
+
+    let path = std::env::temp_dir().join(format!("rubix-library-{}.txt", std::process::id()));
+    std::fs::write(&path, "# mine\nset: Mine\ngoal: pll\nUa: R U' R U R U R U' R' U' R2\n").unwrap();
+    let mut library = AlgorithmLibrary::new();
+    let loaded = library.load_file(&path);
+    std::fs::remove_file(&path).unwrap();
+    loaded.unwrap();
+    assert_eq!(library.set("Mine").unwrap().goal, Goal::Pll);
+    assert_eq!(library.algorithms_for_case("Mine", "Ua").len(), 1);
+    assert!(library.load_file(&std::env::temp_dir().join("rubix-no-such-library.txt")).is_err());
+
+    let sune = "R U R' U R U2 R'";
+    let other_coll = "R' U2 R U F R' U R U' F'";
+    for (text, error) in [
+        (String::from("set: X\nnot a case\n"), "line 2: expected 'case: algorithm'"),
+        (String::from("set: X\ngoal: nonsense\n"), "line 2: unknown goal 'nonsense'"),
+        (String::from("goal: pll\n"), "line 1: goal before set"),
+        (String::from("Ua: R U\n"), "line 1: algorithm before set"),
+        (String::from("set: X\ngoal: pll\nUa: R Q\n"), "line 3:"),
+        (format!("set: X\ngoal: pll\nT: {}\n", sune), "does not fit a Pll case"),
+        (String::from("set: X\ngoal: pll\nUb: R U' R U R U R U' R' U' R2\n"), "solves Ua, not Ub"),
+        (format!("set: X\ngoal: coll\nA: {}\nB: {}\n", sune, sune), "solves A, not B"),
+        (format!("set: X\ngoal: coll\nA: {}\nA: {}\n", sune, other_coll), "solves a different case"),
+    ] {
+        let result = AlgorithmLibrary::new().load_str(&text);
+        assert!(result.as_ref().is_err_and(|message| message.contains(error)), "{:?} gave {:?}", text, result);
+    }
+    // The same case under one name, from another angle, is fine
+    let mut library = AlgorithmLibrary::new();
+    library.load_str(&format!("set: X\ngoal: zbll\nA: {}\nA: U {} U'\n", sune, sune)).unwrap();
+}
+
+#[test]
+fn beginner_solves_layer_by_layer() {
+    let library = AlgorithmLibrary::built_in().unwrap();
+    for cube in random_cubes(28, 8) {
+        let solution = Method::Beginner.solve(&cube, &library).unwrap();
+        assert_eq!(solution.method, "Beginner");
+        let names: Vec<&str> = solution.stages.iter().map(|stage| stage.name.as_str()).collect();
+        assert_eq!(names[..3], ["Cross", "First layer corners", "Second layer"]);
+        assert!(matches_centre(&after_stages(&cube, &solution, 1), 4, [1, 3, 5, 7]));
+        assert!(matches_centre(&after_stages(&cube, &solution, 2), 4, 0..9));
+        assert!(first_two_layers_solved(&after_stages(&cube, &solution, 3)));
+        assert!(solves(&cube, &solution.algorithm()));
+        assert_eq!(solution.move_count(), solution.stages.iter().map(|stage| stage.move_count()).sum::<usize>());
+    }
+    assert_eq!(Method::Beginner.solve(&RubixCube::create_solved_rubix(), &library).unwrap().move_count(), 0);
+    let mut faces = RubixCube::create_solved_rubix().get_faces();
+    // One edge flipped in place
+    (faces[0][7], faces[2][1]) = (faces[2][1], faces[0][7]);
+    let flipped = RubixCube::create_custom_rubix(&faces[0], &faces[1], &faces[2], &faces[3], &faces[4], &faces[5]);
+    assert!(Method::Beginner.solve(&flipped, &library).is_err());
+}
+
+#[test]
+fn cfop_reports_cross_pairs_oll_and_pll() {
+    let library = AlgorithmLibrary::built_in().unwrap();
+    for cube in random_cubes(29, 8) {
+        let solution = Method::Cfop.solve(&cube, &library).unwrap();
+        let names: Vec<&str> = solution.stages.iter().map(|stage| stage.name.as_str()).collect();
+        assert_eq!(names[0], "Cross");
+        assert!(matches_centre(&after_stages(&cube, &solution, 1), 4, [1, 3, 5, 7]));
+        let pairs = names.iter().filter(|name| name.starts_with("F2L pair")).count();
+        assert!(pairs <= 4 && names[1..=pairs].iter().all(|name| name.starts_with("F2L pair")), "{:?}", names);
+        assert!(first_two_layers_solved(&after_stages(&cube, &solution, 1 + pairs)));
+        let oll = names.iter().position(|&name| name == "OLL").map_or(1 + pairs, |stage| stage + 1);
+        assert!(matches_centre(&after_stages(&cube, &solution, oll), 0, 0..9));
+        assert!(solves(&cube, &solution.algorithm()));
+        let text = solution.to_string();
+        assert!(solution.stages.iter().all(|stage| text.contains(&stage.name)));
+    }
+    assert!(Method::Cfop.solve(&scrambled("R U R' U'"), &AlgorithmLibrary::new()).is_err());
+}
+
+#[test]
+fn roux_and_zz_build_blocks_and_compare() {
+    let library = AlgorithmLibrary::built_in().unwrap();
+    let scrambles = random_cubes(30, 4);
+    for cube in &scrambles {
+        let roux = Method::Roux.solve(cube, &library).unwrap();
+        let names: Vec<&str> = roux.stages.iter().map(|stage| stage.name.as_str()).collect();
+        assert_eq!(names[..2], ["First block", "Second block"]);
+        assert!(matches_centre(&after_stages(cube, &roux, 1), 1, 3..9));
+        let blocks = after_stages(cube, &roux, 2);
+        assert!(matches_centre(&blocks, 1, 3..9) && matches_centre(&blocks, 3, 3..9));
+        assert!(solves(cube, &roux.algorithm()));
+
+        let zz = Method::Zz.solve(cube, &library).unwrap();
+        let names: Vec<&str> = zz.stages.iter().map(|stage| stage.name.as_str()).collect();
+        assert_eq!(names[..3], ["EOLine", "Left block", "Right block"]);
+        assert!(matches_centre(&after_stages(cube, &zz, 2), 1, 3..9));
+        assert!(first_two_layers_solved(&after_stages(cube, &zz, 3)));
+        assert!(solves(cube, &zz.algorithm()));
+    }
+
+    let mut faces = RubixCube::create_solved_rubix().get_faces();
+    (faces[0][7], faces[2][1]) = (faces[2][1], faces[0][7]);
+    let mut cases = scrambles.clone();
+    cases.push(RubixCube::create_custom_rubix(&faces[0], &faces[1], &faces[2], &faces[3], &faces[4], &faces[5]));
+    let reports = compare_methods(&[Method::Roux, Method::Zz], &cases, &library);
+    assert_eq!(reports.iter().map(|report| report.method).collect::<Vec<_>>(), [Method::Roux, Method::Zz]);
+    for report in &reports {
+        assert_eq!((report.solved, report.failed), (4, 1));
+        assert!(report.fewest_moves as f64 <= report.average_moves() && report.average_moves() <= report.most_moves as f64);
+        assert!(report.to_string().starts_with(report.method.name()));
+    }
+    assert_eq!(Method::Roux.solve(&cases[4], &library).unwrap_err(), CubeError::Unsolvable(String::from("an edge is flipped")));
+}
+
+#[test]
+fn thistlethwaite_phases_end_in_their_subgroups() {
+    let library = AlgorithmLibrary::built_in().unwrap();
+    assert_eq!(subgroup(&RubixCube::create_solved_rubix()), Some(4));
+    assert_eq!(subgroup(&scrambled("F")), Some(0));
+    assert_eq!(subgroup(&scrambled("F2 R2")), Some(3));
+    assert_eq!(subgroup(&scrambled("U F2")), Some(2));
+    assert_eq!(subgroup(&scrambled("R U2 L'")), Some(1));
+    for cube in random_cubes(31, 5) {
+        let solution = Method::Thistlethwaite.solve(&cube, &library).unwrap();
+        assert_eq!(solution.stages.len(), 4);
+        for (phase, stage) in solution.stages.iter().enumerate() {
+            let group = subgroup(&after_stages(&cube, &solution, phase + 1)).unwrap();
+            assert!(group > phase, "phase {} left the cube in G{}", phase + 1, group);
+            assert_eq!(stage.subgroup, Some(group));
+        }
+        assert!(solution.to_string().contains(" -> G4"));
+    }
+}
+
+#[test]
+fn every_method_solves() {
+    let library = AlgorithmLibrary::built_in().unwrap();
+    let mut rng = StdRng::seed_from_u64(1);
+    for _ in 0..5 {
+        let mut cube = RubixCube::create_solved_rubix();
+        cube.apply_algorithm(&Algorithm::random(20, &mut rng));
+        for method in Method::ALL {
+            let solution = method.solve(&cube, &library).unwrap();
+            assert!(solves(&cube, &solution.algorithm()), "{} failed", method.name());
+            assert_eq!(solution.move_count(), solution.algorithm().len());
+        }
+    }
+}
+
+#[test]
+fn searches_find_shortest_solutions() {
+    let cube = scrambled("R U F' L2 D");
+    let parallel = ParallelSearch::new(2, 8).with_threads(Threads::Single).solve(&cube).unwrap();
+    assert_eq!(parallel.len(), 5);
+    assert!(solves(&cube, &parallel));
+    let bidirectional = BidirectionalSearch::new(8, 1 << 26).solve(&cube).unwrap();
+    assert_eq!(bidirectional.len(), 5);
+    assert!(solves(&cube, &bidirectional));
+    assert!(ParallelSearch::new(2, 3).solve(&cube).is_err());
+
+    let codes = cube.thread_search_iddfs(8).unwrap();
+    let mut turned = cube;
+    for code in codes.moves() {
+        turned.turn_cube(code);
+    }
+    assert!(turned.is_solved());
+    assert!(cube.thread_search_iddfs(3).is_err());
+}
+
+#[test]
+fn bidirectional_search_matches_optimal_lengths_and_stops_at_its_limits() {
+    let mut rng = StdRng::seed_from_u64(32);
+    for _ in 0..4 {
+        let mut cube = RubixCube::create_solved_rubix();
+        cube.apply_algorithm(&Algorithm::random(6, &mut rng));
+        let optimal = ParallelSearch::new(2, 6).with_threads(Threads::Single).solve(&cube).unwrap();
+        let bidirectional = BidirectionalSearch::new(6, 1 << 26).solve(&cube).unwrap();
+        assert_eq!(bidirectional.len(), optimal.len());
+        assert!(solves(&cube, &bidirectional));
+    }
+    assert!(BidirectionalSearch::default().solve(&RubixCube::create_solved_rubix()).unwrap().is_empty());
+    assert_eq!(BidirectionalSearch::default().max_depth, 12);
+
+    let cube = scrambled("R U F' L2 D B'");
+    let error = BidirectionalSearch::new(5, 1 << 26).solve(&cube).unwrap_err();
+    assert_eq!((error.to_string().as_str(), error), ("no solution of 5 moves or fewer", CubeError::NoSolution(5)));
+    let error = BidirectionalSearch::new(6, 1 << 12).solve(&cube).unwrap_err();
+    assert!(matches!(error, CubeError::LimitExceeded(_)), "{:?}", error);
+    assert!(error.to_string().starts_with("memory limit of 4096 bytes reached"), "{}", error);
+}
+
+#[test]
+fn transposition_table_replaces_on_collision() {
+    let entry = size_of::<Option<(StateKey, u8)>>();
+    let mut table = TranspositionTable::new(3 * entry);
+    assert_eq!(table.capacity(), 2);
+    let keys: Vec<StateKey> = ["R", "U", "F", "L", "D", "B"].iter().map(|moves| StateKey::from_rubix(&scrambled(moves)).unwrap()).collect();
+    let (first, second) = (0..keys.len())
+        .flat_map(|i| (i + 1..keys.len()).map(move |j| (i, j)))
+        .find(|&(i, j)| keys[i].fingerprint() % 2 == keys[j].fingerprint() % 2)
+        .unwrap();
+    table.insert(keys[first], 1u8);
+    assert_eq!(table.get(&keys[first]), Some(1));
+    assert_eq!(table.get(&keys[second]), None);
+    table.insert(keys[second], 2);
+    assert_eq!(table.get(&keys[second]), Some(2));
+    assert_eq!(table.get(&keys[first]), None);
+    table.insert(keys[second], 3);
+    assert_eq!(table.get(&keys[second]), Some(3));
+    table.clear();
+    assert_eq!(table.get(&keys[second]), None);
+    assert_eq!(table.capacity(), 2);
+    assert_eq!(TranspositionTable::<u8>::new(0).capacity(), 1);
+}
+
+#[test]
+fn symmetries_form_a_group_and_share_a_canonical_key() {
+    let all = symmetries();
+    assert_eq!(all.len(), 48);
+    assert_eq!(all.iter().filter(|symmetry| symmetry.mirrored).count(), 24);
+    assert_eq!(all[0].faces, [0, 1, 2, 3, 4, 5]);
+    for (i, a) in all.iter().enumerate() {
+        assert!(all[i + 1..].iter().all(|b| b.faces != a.faces), "symmetry {} repeated", i);
+    }
+    let compose = |a: &Symmetry, b: &Symmetry| a.faces.map(|face| b.faces[face]);
+    for a in all {
+        assert_eq!(compose(a, a.inverse()), all[0].faces);
+        for b in all {
+            assert!(all.iter().any(|c| c.faces == compose(a, b)));
+        }
+    }
+
+    let cube = random_cubes(34, 1)[0];
+    let images: Vec<RubixCube> = all.iter().map(|symmetry| cube.symmetric(symmetry)).collect();
+    assert_eq!(images[0], cube);
+    for (i, image) in images.iter().enumerate() {
+        assert!(!images[i + 1..].contains(image), "two symmetries give the same image");
+        assert!(image.check_solvable().is_ok());
+        assert_eq!(image.symmetric(all[i].inverse()), cube);
+        assert_eq!(image.canonical(), cube.canonical());
+        assert_eq!(StateKey::from_rubix(&image.canonical()), StateKey::from_rubix(&cube.canonical()));
+    }
+    for (a, image) in all.iter().zip(&images) {
+        for b in all {
+            let faces = compose(a, b);
+            let c = all.iter().position(|c| c.faces == faces).unwrap();
+            assert_eq!(image.symmetric(b), images[c]);
+        }
+    }
+
+    let scramble = Algorithm::parse("R U F' L2 D B'").unwrap();
+    for symmetry in all {
+        let mut turned = RubixCube::create_solved_rubix();
+        turned.apply_algorithm(&Algorithm::new(scramble.moves().iter().map(|&next| symmetry.map_move(next)).collect()));
+        assert_eq!(scrambled("R U F' L2 D B'").symmetric(symmetry), turned);
+    }
+}
+
+#[test]
+fn search_tables_keep_one_distance_per_symmetry_class() {
+    let (kept, full) = ParallelSearch::table_entries();
+    assert!(kept * 16 <= full, "{} distances kept of {}", kept, full);
+    // Symmetric states are as far from solved as each other, and still come out shortest
+    let cube = scrambled("R U F' L2 D B' R2");
+    let search = ParallelSearch::new(2, 8).with_threads(Threads::Single);
+    let length = search.solve(&cube).unwrap().len();
+    assert_eq!(length, BidirectionalSearch::new(8, 1 << 28).solve(&cube).unwrap().len());
+    for symmetry in symmetries().iter().step_by(5) {
+        let image = cube.symmetric(symmetry);
+        assert!(solves(&image, &search.solve(&image).unwrap()));
+        assert_eq!(search.solve(&image).unwrap().len(), length);
+    }
+}
+
+#[test]
+fn parallel_search_agrees_across_thread_counts() {
+    let mut rng = StdRng::seed_from_u64(36);
+    for _ in 0..4 {
+        let mut cube = RubixCube::create_solved_rubix();
+        cube.apply_algorithm(&Algorithm::random(7, &mut rng));
+        let single = ParallelSearch::new(2, 7).with_threads(Threads::Single);
+        let (first, nodes) = single.solve_counting(&cube).unwrap();
+        assert!(nodes > 0);
+        assert!(solves(&cube, &first));
+        assert_eq!(single.solve(&cube).unwrap(), first, "one thread should always find the same solution");
+        for threads in [Threads::Global, Threads::count(3).unwrap()] {
+            let found = ParallelSearch::new(3, 7).with_threads(threads).solve(&cube).unwrap();
+            assert_eq!(found.len(), first.len());
+            assert!(solves(&cube, &found));
+        }
+        assert_eq!(BidirectionalSearch::new(7, 1 << 26).solve(&cube).unwrap().len(), first.len());
+    }
+
+    assert!(ParallelSearch::default().solve(&RubixCube::create_solved_rubix()).unwrap().is_empty());
+    let cube = scrambled("R U");
+    assert_eq!(ParallelSearch::new(3, 5).solve(&cube).unwrap().len(), 2, "solutions shorter than the split depth are found while splitting");
+    assert_eq!(ParallelSearch::new(2, 1).solve(&cube).unwrap_err(), CubeError::NoSolution(1));
+    let cancelled = ParallelSearch::new(2, 10).with_cancel(Arc::new(AtomicBool::new(true)));
+    assert_eq!(cancelled.solve(&scrambled("R U F' L2 D B' R2")).unwrap_err(), CubeError::Cancelled);
+}
+
+#[test]
+fn solvers_by_name_report_and_cancel() {
+    let cube = scrambled("R U F' L2");
+    let library = AlgorithmLibrary::built_in().unwrap();
+    for name in Solver::NAMES {
+        let report = Solver::parse(name).unwrap().solve(&cube, &library).unwrap();
+        assert!(solves(&cube, &report.solution.algorithm()), "{} failed", name);
+        assert_eq!(report.cube, cube);
+    }
+    assert!(Solver::parse("guess").is_none());
+    for name in Solver::NAMES {
+        let solver = Solver::parse(name).unwrap();
+        assert_eq!(solver.uses_threads(), name == "optimal");
+        let pool = Arc::new(rayon::ThreadPoolBuilder::new().num_threads(2).build().unwrap());
+        let pooled = solver.with_threads(Threads::Pool(Arc::clone(&pool)));
+        let report = pooled.solve(&cube, &library).unwrap();
+        assert!(solves(&cube, &report.solution.algorithm()), "{} failed in a pool", name);
+    }
+    let cancel = Arc::new(AtomicBool::new(true));
+    for name in ["optimal", "bidirectional"] {
+        let solver = Solver::parse(name).unwrap().with_cancel(Arc::clone(&cancel));
+        assert_eq!(solver.solve(&scrambled("R U F' L2 D B' R2"), &library).unwrap_err(), CubeError::Cancelled, "{} ignored cancel", name);
+    }
+}
+
+#[test]
+fn batches_keep_input_order_and_count_failures() {
+    let cube = scrambled("R U F'");
+    assert_eq!(parse_case("R U F'").unwrap(), cube);
+    assert_eq!(parse_case(&cube.facelets()).unwrap(), cube);
+    assert!(parse_case("WWW").is_err());
+    assert_eq!(parse_case(&format!("facelets: {}", cube.facelets())).unwrap(), cube);
+    assert_eq!(parse_case("  scramble: R U F'").unwrap(), cube);
+    assert!(parse_case("facelets: R U F'").is_err());
+    assert!(parse_case(&format!("scramble: {}", cube.facelets())).is_err());
+    // Bare R and B letters are moves unless there are 54 of them
+    assert_eq!(parse_case("R B").unwrap(), scrambled("R B"));
+    let long = "R B ".repeat(27);
+    assert!(parse_case(&long).is_err());
+    assert_eq!(parse_case(&format!("scramble: {}", long)).unwrap(), scrambled(&long));
+
+    let input = format!("# cases\nR U F'\n\n{}\nR Q\nD2 L B\n", cube.facelets());
+    let lines = input.lines().map(|line| Ok::<String, io::Error>(line.to_string()));
+    let solver = Solver::parse("thistlethwaite").unwrap();
+    let mut results: Vec<(usize, String)> = Vec::new();
+    let stats = solve_batch(lines, &solver, &AlgorithmLibrary::built_in().unwrap(), &Threads::Global, |result: &CaseResult| {
+        results.push((result.line, result.csv_row()));
+        return Ok(());
+    })
+    .unwrap();
+    assert_eq!(results.iter().map(|(line, _)| *line).collect::<Vec<_>>(), vec![2, 4, 5, 6]);
+    assert_eq!((stats.cases, stats.solved, stats.failed), (4, 3, 1));
+    assert_eq!(CaseResult::CSV_HEADER.split(',').count(), results[0].1.split(',').count());
+    assert!(results[2].1.ends_with("Unknown move 'Q'"));
+}
+
+#[test]
+fn nets_draw_in_letters_or_colour() {
+    let cube = scrambled("R U");
+    let plain = TerminalNet { colour: false, ..TerminalNet::default() };
+    let expected = [
+        "       B B B",
+        "       B B B",
+        "       W W W",
+        "",
+        "W W G  O O O  B Y Y  R R R",
+        "R R R  W W G  O O O  B Y Y",
+        "R R R  W W G  O O O  B Y Y",
+        "",
+        "       G G Y",
+        "       G G Y",
+        "       G G Y",
+    ];
+    assert_eq!(plain.render(&cube).lines().collect::<Vec<_>>(), expected);
+
+    let labelled = plain.with_labels(true).with_layout(NetLayout::Column).render(&cube);
+    assert!(labelled.starts_with("       U\n"));
+    assert!(labelled.contains("\nL      F      R\n"));
+    assert!(labelled.ends_with("       B\n       Y Y B\n       Y Y B\n       R R R\n"));
+
+    let coloured = TerminalNet::default().render(&cube);
+    assert_eq!(coloured.matches("\x1b[48;2;").count(), 54);
+    assert_eq!(coloured.matches("\x1b[48;2;255;255;255m").count(), 9);
+}
+
+#[test]
+fn diagrams_colour_masked_stickers_grey() {
+    let cube = scrambled("R U R' U R U2 R'");
+    let count = |svg: &str, colour: &str| svg.matches(&format!("fill=\"{}\"", colour)).count();
+
+    let net = Diagram::default().svg(&cube);
+    assert!(net.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert_eq!(net.matches("<polygon").count(), 6 + 54);
+    assert_eq!(count(&net, "#ffffff"), 9);
+
+    let isometric = Diagram::default().with_view(View::Isometric).svg(&cube);
+    assert_eq!(isometric.matches("<polygon").count(), 3 + 27);
+
+    // Only last layer stickers the colour of the U centre are left
+    let oll = Diagram::default().with_mask(StickerMask::Oll).svg(&cube);
+    assert_eq!(count(&oll, "#0046ad"), 9);
+    assert_eq!(count(&oll, "#969696"), 45);
+    let pll = Diagram::default().with_mask(StickerMask::LastLayer).svg(&cube);
+    assert_eq!(count(&pll, "#969696"), 54 - 21);
+
+    let palette = Palette::parse("B=#000080, Y=ffff00").unwrap();
+    assert_eq!(palette.rgb(Colour::Blue), (0, 0, 128));
+    assert_eq!(count(&Diagram::default().with_palette(palette).svg(&cube), "#000080"), 9);
+    assert!(Palette::parse("B=#00008").is_err());
+    assert!(Palette::parse("Q=#000080").is_err());
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn diagrams_rasterise_to_png() {
+    let png = Diagram::default().with_view(View::Isometric).png(&scrambled("R U")).unwrap();
+    assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+}
+
+#[test]
+fn animations_step_through_each_move() {
+    let cube = scrambled("R U");
+    let solution = Algorithm::parse("U' R'").unwrap();
+    let turning = Animation { turn_frames: 4, ..Animation::default() }.svg(&cube, &solution);
+    assert_eq!(turning.matches("<g ").count(), 1 + 2 * (4 + 1));
+    assert_eq!(turning.matches("<animate ").count(), 1 + 2 * (4 + 1));
+    assert!(turning.contains(">0/2</text>"));
+    assert_eq!(turning.matches(">2/2 R'</text>").count(), 4 + 1);
+
+    let net = Animation { captions: false, ..Animation::default() }.with_diagram(Diagram::default());
+    let stepping = net.svg(&cube, &solution);
+    assert_eq!(stepping.matches("<g ").count(), 3);
+    assert!(!stepping.contains("<text"));
+    // The last state is the solved cube
+    let polygons = |svg: &str| svg.lines().filter(|line| line.starts_with("<polygon")).map(String::from).collect::<Vec<_>>();
+    let last = &stepping[stepping.rfind("<g ").unwrap()..];
+    assert_eq!(polygons(last), polygons(&Diagram::default().svg(&RubixCube::create_solved_rubix())));
+}
+
+#[cfg(feature = "gif")]
+#[test]
+fn animations_encode_as_gif() {
+    let gif = Animation::default().gif(&scrambled("R"), &Algorithm::parse("R'").unwrap()).unwrap();
+    assert!(gif.starts_with(b"GIF89a"));
+}
+
+#[test]
+fn keymaps_turn_standard_moves() {
+    let keys = |keymap: Keymap, keys: &str| Algorithm::new(keys.chars().map(|key| keymap.move_for(key).unwrap()).collect()).to_string();
+    assert_eq!(keys(Keymap::Notation, "rRuUmy"), "R R' U U' M y");
+    assert_eq!(keys(Keymap::Speedcubing, "ikjfhgdesl"), "R R' U U' F F' L L' D D'");
+    assert_eq!(Keymap::Notation.move_for('q'), None);
+    assert_eq!(Keymap::parse("Speedcubing"), Some(Keymap::Speedcubing));
+}
+
+#[test]
+fn simulator_undoes_redoes_and_steps_through_solutions() {
+    let mut simulator = Simulator::new(scrambled("R U"));
+    let turn = |notation: &str| Algorithm::parse(notation).unwrap().moves()[0];
+    simulator.turn(turn("F"));
+    assert_eq!(simulator.undo(), Some(turn("F")));
+    assert_eq!(*simulator.cube(), scrambled("R U"));
+    assert_eq!(simulator.redo(), Some(turn("F")));
+    assert_eq!(simulator.undo(), Some(turn("F")));
+    assert!(simulator.can_redo());
+    assert_eq!(simulator.undo(), None);
+
+    simulator.show_solution("by hand", Algorithm::parse("U' R'").unwrap());
+    assert_eq!(simulator.step_forward(), Some(turn("U'")));
+    assert_eq!(simulator.step_back(), Some(turn("U'")));
+    assert_eq!(simulator.step_back(), None);
+    // Redoing and following by hand keep the solution up
+    simulator.redo();
+    simulator.turn(turn("R'"));
+    assert!(simulator.cube().is_solved());
+    assert!(simulator.playback().unwrap().finished());
+    assert_eq!(simulator.step_forward(), None);
+    // Anything else drops it
+    simulator.turn(turn("D"));
+    assert!(simulator.playback().is_none());
+
+    let scramble = simulator.scramble(10, &mut StdRng::seed_from_u64(3));
+    let mut expected = RubixCube::create_solved_rubix();
+    expected.apply_algorithm(&scramble);
+    assert_eq!((*simulator.cube(), *simulator.start(), simulator.history().len()), (expected, expected, 0));
+}
+
+#[test]
+fn checkpoint_text_round_trips() {
+    let checkpoint = Checkpoint {
+        cube: scrambled("R U"),
+        split_depth: 3,
+        max_depth: 20,
+        bound: 9,
+        next_bound: 10,
+        nodes: 1234,
+        done: vec![0, 2, 5],
+    };
+    assert_eq!(Checkpoint::parse(&checkpoint.to_text()).unwrap(), checkpoint);
+    assert!(Checkpoint::parse("not a checkpoint").is_err());
+}
+
+// Solves cube with a coordinator on a free local port. before runs once it is listening,
+// given its address, and then workers run_worker threads connect.
+fn solve_distributed(cube: &RubixCube, collect: Collect, workers: usize, before: impl FnOnce(SocketAddr) -> Vec<TcpStream>) -> (Vec<String>, u64) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let coordinator = Coordinator { split_depth: 2, max_depth: 8, collect, idle_timeout: Duration::from_secs(20), task_timeout: Duration::from_millis(300) };
+    let cube = *cube;
+    let solving = thread::spawn(move || coordinator.solve_counting(listener, &cube));
+    let held = before(address);
+    let workers: Vec<_> = (0..workers).map(|_| thread::spawn(move || run_worker(address))).collect();
+    let (solutions, nodes) = solving.join().unwrap().unwrap();
+    for worker in workers {
+        worker.join().unwrap().unwrap();
+    }
+    drop(held);
+    let mut solutions: Vec<String> = solutions.iter().map(|solution| solution.to_string()).collect();
+    solutions.sort();
+    return (solutions, nodes);
+}
+
+// Connects as a worker and takes a subtree without answering
+fn take_task(address: SocketAddr) -> TcpStream {
+    let stream = TcpStream::connect(address).unwrap();
+    writeln!(&stream, "hello").unwrap();
+    let mut reader = BufReader::new(&stream);
+    for expected in ["job ", "task "] {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        assert!(line.starts_with(expected), "{}", line);
+    }
+    return stream;
+}
+
+#[test]
+fn distributed_search_matches_the_parallel_search() {
+    let cube = scrambled("R U F' L2 D");
+    let expected = ParallelSearch::new(2, 8).with_threads(Threads::Single).solve(&cube).unwrap();
+
+    let (first, nodes) = solve_distributed(&cube, Collect::First, 3, |_| Vec::new());
+    assert_eq!(first.len(), 1);
+    assert!(nodes > 0);
+    let found = Algorithm::parse(&first[0]).unwrap();
+    assert_eq!(found.len(), expected.len());
+    assert!(solves(&cube, &found));
+
+    let (all, nodes) = solve_distributed(&cube, Collect::All, 2, |_| Vec::new());
+    assert!(all.contains(&expected.to_string()));
+    for solution in &all {
+        let solution = Algorithm::parse(solution).unwrap();
+        assert_eq!(solution.len(), expected.len());
+        assert!(solves(&cube, &solution));
+    }
+
+    // One worker hangs up holding a subtree and another sits on the one it gets next; both
+    // go back on the queue, so the others search everything exactly once
+    let (requeued, requeued_nodes) = solve_distributed(&cube, Collect::All, 2, |address| {
+        drop(take_task(address));
+        vec![take_task(address)]
+    });
+    assert_eq!(requeued, all);
+    assert_eq!(requeued_nodes, nodes);
+}
+
+#[test]
+fn workers_keep_subtrees_that_outlast_the_task_timeout() {
+    // With no split each iteration is a single subtree, and the last takes many times
+    // task_timeout; the worker's progress lines keep it from being taken away
+    let cube = scrambled("R U F' L2 D B' R2 U' F D2 L");
+    let expected = ParallelSearch::new(2, 12).solve(&cube).unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let task_timeout = Duration::from_millis(100);
+    let coordinator = Coordinator { split_depth: 0, max_depth: 12, collect: Collect::All, idle_timeout: Duration::from_secs(5), task_timeout };
+    let start = Instant::now();
+    let solving = thread::spawn(move || coordinator.solve(listener, &cube));
+    let worker = thread::spawn(move || run_worker(address));
+    let solutions = solving.join().unwrap().unwrap();
+    let elapsed = start.elapsed();
+    let searched = worker.join().unwrap().unwrap();
+    assert!(elapsed > task_timeout * 2 * searched as u32, "{} subtrees in {:?}", searched, elapsed);
+    assert!(!solutions.is_empty());
+    for solution in &solutions {
+        assert_eq!(solution.len(), expected.len());
+        assert!(solves(&cube, solution));
+    }
+}
+
+#[test]
+fn stopped_searches_resume_from_their_checkpoint() {
+    let mut cube = RubixCube::create_solved_rubix();
+    cube.apply_algorithm(&Algorithm::random(10, &mut StdRng::seed_from_u64(38)));
+    let search = ParallelSearch::new(3, 12).with_threads(Threads::Single);
+    let expected = search.solve(&cube).unwrap();
+
+    // Saved only as each iteration starts and when cancelled, so the tasks finished part way
+    // through the last iteration are only kept if cancelling saves them
+    let path = std::env::temp_dir().join(format!("rubix-checkpoint-{}.txt", std::process::id()));
+    let cancel = Arc::new(AtomicBool::new(false));
+    let finished = Arc::new(AtomicBool::new(false));
+    let watcher = {
+        let (path, cancel, finished) = (path.clone(), Arc::clone(&cancel), Arc::clone(&finished));
+        let last = expected.len() as u8;
+        thread::spawn(move || {
+            let mut started = None;
+            while !finished.load(Ordering::Relaxed) && started.is_none() {
+                started = Checkpoint::load(&path).ok().filter(|checkpoint| checkpoint.bound == last);
+                thread::sleep(Duration::from_millis(1));
+            }
+            thread::sleep(Duration::from_millis(20));
+            cancel.store(true, Ordering::Relaxed);
+            started
+        })
+    };
+    let stopped = search.clone().with_cancel(Arc::clone(&cancel)).solve_saving(&cube, &path, Duration::from_secs(3600));
+    finished.store(true, Ordering::Relaxed);
+    let started = watcher.join().unwrap().expect("the last iteration never started");
+    assert_eq!(stopped.unwrap_err(), CubeError::Cancelled);
+    let saved = Checkpoint::load(&path).unwrap();
+    assert_eq!((saved.cube, saved.split_depth, saved.max_depth, saved.bound), (cube, 3, 12, started.bound));
+    assert!(!saved.done.is_empty() && saved.nodes > started.nodes, "cancelling lost the finished tasks");
+
+    assert_eq!(search.clone().with_cancel(cancel).resume(&path, Duration::ZERO).unwrap_err(), CubeError::Cancelled, "resume dropped the cancel flag");
+    assert_eq!(Checkpoint::load(&path).unwrap(), saved);
+
+    // Resumed from the start of the iteration and from the cancel, the same tasks finish, but
+    // the second search skips the ones already done
+    let from_start = std::env::temp_dir().join(format!("rubix-checkpoint-start-{}.txt", std::process::id()));
+    started.save(&from_start).unwrap();
+    let resumed = ParallelSearch::default().with_threads(Threads::Single).resume(&from_start, Duration::ZERO).unwrap();
+    assert_eq!(resumed, expected);
+    let resumed = ParallelSearch::default().with_threads(Threads::Single).resume(&path, Duration::ZERO).unwrap();
+    assert_eq!(resumed, expected);
+    let (whole, rest) = (Checkpoint::load(&from_start).unwrap(), Checkpoint::load(&path).unwrap());
+    assert_eq!(rest.done, whole.done);
+    assert!(saved.done.iter().all(|task| rest.done.contains(task)));
+    assert!(rest.nodes - saved.nodes < whole.nodes - started.nodes, "finished tasks were searched again");
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_file(&from_start).unwrap();
+}
+
+#[test]
+fn colours_read_from_letters() {
+    for colour in Colour::ALL {
+        assert_eq!(Colour::from_letter(colour.to_string().chars().next().unwrap()), Some(colour));
+    }
+    assert_eq!(Colour::from_letter('X'), None);
+}
+
+#[test]
+fn colour_schemes_check_centres_and_colour_drawings() {
+    assert_eq!(ColourScheme::default().solved(), RubixCube::create_solved_rubix());
+    let japanese = ColourScheme::parse("japanese").unwrap();
+    assert_eq!(ColourScheme::parse("WOGRBY"), Ok(japanese));
+    assert_eq!(japanese.opposite(Colour::White), Some(Colour::Blue));
+    assert!(matches!(ColourScheme::parse("WOGRWY"), Err(CubeError::Scheme(_))));
+
+    let mut cube = japanese.solved();
+    cube.apply_algorithm(&Algorithm::parse("x R U' y2 F2").unwrap());
+    assert_eq!(japanese.from_facelets(&cube.facelets()), Ok(cube));
+    assert_eq!(japanese.check_solvable(&cube), Ok(()));
+    assert!(matches!(ColourScheme::western().check_centres(&cube), Err(CubeError::Scheme(_))));
+    // The default scheme has the western opposites, but in mirror image
+    assert!(ColourScheme::western().check_centres(&RubixCube::create_solved_rubix()).is_err());
+
+    let scheme = japanese.with_shades("W=#fafafa").unwrap();
+    let net = TerminalNet::default().with_scheme(&scheme).render(&scheme.solved());
+    assert_eq!(net.matches("\x1b[48;2;250;250;250m").count(), 9);
+    assert_eq!(Palette::for_scheme(&scheme).rgb(Colour::White), (250, 250, 250));
+}
@@ -0,0 +1,187 @@
+#![cfg(feature = "server")]
+#![allow(clippy::needless_return)]
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+// A rubix-server on a free local port, killed when dropped
+struct Server {
+    child: Child,
+    address: SocketAddr,
+}
+
+impl Server {
+    fn start(args: &[&str]) -> Server {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_rubix-server"))
+            .args(["--address", "127.0.0.1:0"])
+            .args(args)
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut line = String::new();
+        BufReader::new(child.stderr.take().unwrap()).read_line(&mut line).unwrap();
+        let address = line.trim().strip_prefix("listening on ").unwrap().parse().unwrap();
+        return Server { child, address };
+    }
+
+    // Sends raw as the whole request and returns the status and JSON body
+    fn send(&self, raw: &[u8]) -> (u16, Value) {
+        let mut stream = TcpStream::connect(self.address).unwrap();
+        stream.write_all(raw).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let status = response.split_whitespace().nth(1).unwrap().parse().unwrap();
+        let body = response.split_once("\r\n\r\n").unwrap().1;
+        return (status, serde_json::from_str(body).unwrap());
+    }
+
+    fn get(&self, target: &str) -> (u16, Value) {
+        return self.send(format!("GET {} HTTP/1.1\r\nHost: test\r\n\r\n", target).as_bytes());
+    }
+
+    fn post(&self, path: &str, body: &Value) -> (u16, Value) {
+        let body = body.to_string();
+        return self.send(format!("POST {} HTTP/1.1\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}", path, body.len(), body).as_bytes());
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+// A cube no search finishes with in a second
+const HARD: &str = "R U2 F' L D2 B R' U F2 L' D B2 R2 U' F L2 D' B' R F'";
+
+fn facelets(server: &Server, moves: &str) -> Value {
+    let (status, body) = server.post("/apply", &json!({ "algorithm": moves }));
+    assert_eq!(status, 200);
+    return body["facelets"].clone();
+}
+
+#[test]
+fn routes_and_parses_requests() {
+    let server = Server::start(&[]);
+    assert_eq!(server.get("/health"), (200, json!({ "status": "ok" })));
+    assert_eq!(server.get("/nowhere").0, 404);
+    assert_eq!(server.post("/health", &json!({})).0, 405);
+    assert_eq!(server.get("/solve").0, 405);
+
+    let (status, body) = server.get("/scramble?moves=5&seed=3");
+    assert_eq!(status, 200);
+    assert_eq!(body["scramble"].as_array().unwrap().len(), 5);
+    assert_eq!(server.get("/scramble?moves=5&seed=3").1, body);
+    assert_eq!(server.get("/scramble?moves=lots").0, 400);
+    assert_eq!(server.get("/scramble?moves=5000").0, 400);
+
+    let state = facelets(&server, "R U");
+    assert_eq!(server.post("/apply", &json!({ "facelets": state, "algorithm": ["U'", "R'"] })).1, json!({ "facelets": facelets(&server, "") }));
+    assert_eq!(server.post("/validate", &json!({ "facelets": state })).1, json!({ "valid": true }));
+    assert_eq!(server.post("/validate", &json!({ "facelets": "WWW" })).1["valid"], json!(false));
+    let (status, body) = server.post("/solve", &json!({ "facelets": state, "max_depth": 1000 }));
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["solution"]["stages"][0]["algorithm"], json!(["U'", "R'"]));
+    assert_eq!(server.post("/solve", &json!({ "facelets": state, "solver": "guess" })).0, 400);
+
+    assert_eq!(server.send(b"POST /apply HTTP/1.1\r\nContent-Length: 5\r\n\r\n{oops").0, 400);
+    assert_eq!(server.send(b"POST /apply HTTP/1.1\r\nContent-Length: many\r\n\r\n").0, 400);
+    assert_eq!(server.send(b"POST /apply HTTP/1.1\r\nContent-Length: 100000\r\n\r\n").0, 400);
+    assert_eq!(server.send(b"nonsense\r\n\r\n").0, 400);
+    let (status, body) = server.send(format!("GET /health HTTP/1.1\r\nX-Padding: {}\r\n\r\n", "x".repeat(10_000)).as_bytes());
+    assert_eq!(status, 400);
+    assert!(body["error"].as_str().unwrap().contains("over"), "{}", body);
+}
+
+#[test]
+fn busy_slots_give_503_and_timeouts_give_504_and_cancel() {
+    let server = Server::start(&["--max-searches", "1", "--timeout-ms", "5000"]);
+    let hard = facelets(&server, HARD);
+    let holding = {
+        let (address, hard) = (server.address, hard.clone());
+        thread::spawn(move || {
+            let body = json!({ "facelets": hard, "timeout_ms": 1000 }).to_string();
+            let mut stream = TcpStream::connect(address).unwrap();
+            write!(stream, "POST /solve HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        })
+    };
+    thread::sleep(Duration::from_millis(200));
+    let (status, body) = server.post("/solve", &json!({ "facelets": hard, "timeout_ms": 300 }));
+    assert_eq!(status, 503, "{}", body);
+    let methods = server.post("/solve", &json!({ "facelets": hard, "solver": "cfop", "timeout_ms": 300 }));
+    assert_eq!(methods.0, 200, "methods have slots of their own");
+    assert!(holding.join().unwrap().starts_with("HTTP/1.1 504"));
+
+    // The timed out search was cancelled, so its slot is free for the next one
+    let start = Instant::now();
+    let (status, body) = server.post("/solve", &json!({ "facelets": facelets(&server, "R U F'"), "timeout_ms": 2000 }));
+    assert_eq!(status, 200, "{}", body);
+    assert!(start.elapsed() < Duration::from_secs(2));
+}
+
+#[test]
+fn connections_past_the_limit_get_503() {
+    let server = Server::start(&["--max-connections", "1"]);
+    let idle = TcpStream::connect(server.address).unwrap();
+    thread::sleep(Duration::from_millis(100));
+    let (status, body) = server.get("/health");
+    assert_eq!(status, 503);
+    assert_eq!(body["error"], json!("too many connections"));
+
+    // A turned away client that keeps trickling bytes doesn't hold up answering the next one
+    let trickling = TcpStream::connect(server.address).unwrap();
+    let trickle = thread::spawn(move || {
+        for _ in 0..50 {
+            if (&trickling).write_all(b"x").is_err() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(30));
+        }
+    });
+    thread::sleep(Duration::from_millis(100));
+    let start = Instant::now();
+    assert_eq!(server.get("/health").0, 503);
+    assert!(start.elapsed() < Duration::from_millis(500), "answered after {:?}", start.elapsed());
+    trickle.join().unwrap();
+    drop(idle);
+    let start = Instant::now();
+    while server.get("/health").0 != 200 {
+        assert!(start.elapsed() < Duration::from_secs(5), "the idle connection was never let go");
+        thread::sleep(Duration::from_millis(20));
+    }
+}
+
+#[test]
+fn methods_take_slots_and_stop_building_tables_when_cancelled() {
+    let server = Server::start(&["--max-methods", "1", "--timeout-ms", "5000"]);
+    let state = facelets(&server, "R U F'");
+    // Thistlethwaite's tables take seconds to build, all the while holding the only method slot
+    let building = {
+        let (address, state) = (server.address, state.clone());
+        thread::spawn(move || {
+            let body = json!({ "facelets": state, "solver": "thistlethwaite", "timeout_ms": 500 }).to_string();
+            let mut stream = TcpStream::connect(address).unwrap();
+            write!(stream, "POST /solve HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        })
+    };
+    thread::sleep(Duration::from_millis(200));
+    let (status, body) = server.post("/solve", &json!({ "facelets": state, "solver": "cfop", "timeout_ms": 200 }));
+    assert_eq!((status, body["error"].as_str().unwrap().starts_with("every method slot")), (503, true), "{}", body);
+    assert!(building.join().unwrap().starts_with("HTTP/1.1 504"));
+
+    // The timed out solve stopped building, so the slot is free long before the tables could be
+    let (status, body) = server.post("/solve", &json!({ "facelets": state, "solver": "cfop", "timeout_ms": 1000 }));
+    assert_eq!(status, 200, "{}", body);
+}